@@ -0,0 +1,118 @@
+// Modo benchmark (`--bench`): recorre sin ventana una trayectoria fija de 600 frames con paso de
+// tiempo constante y al final imprime en stdout un resumen en JSON (tiempos por frame y por etapa,
+// triángulos y fragmentos). La trayectoria tiene tres tramos que cargan etapas distintas del pipeline,
+// así una regresión en cualquiera de ellas se nota en su tramo
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+use crate::camera::Camera;
+use crate::headless::TIMESTEP;
+use crate::solar_system::{CelestialBody, SolarSystem};
+use crate::renderer::{advance_clock, Renderer};
+use crate::stats::{FrameSample, STAGE_COUNT, STAGE_NAMES};
+
+// Frames de cada tramo: acercamiento al Sol (bloom y bruma), vista cenital (muchos cuerpos chicos)
+// y vuelo rasante sobre el planeta más grande (la pantalla entera pasa por el fragment shader)
+const SEGMENT_FRAMES: usize = 200;
+const SEGMENTS: [&str; 3] = ["sun", "overview", "skim"];
+
+pub fn run(renderer: &mut Renderer, solar_system: &mut SolarSystem, camera: &mut Camera) {
+    let mut clock = 0.0;
+    let mut resolution = (0, 0);
+    let frames = SEGMENTS.len() * SEGMENT_FRAMES;
+    let mut samples = Vec::with_capacity(frames);
+
+    for frame in 0..frames {
+        let start = Instant::now();
+        let timestep = if frame == 0 { 0.0 } else { TIMESTEP };
+        solar_system.update(timestep, camera);
+        let (eye, target) = pose(solar_system, frame);
+        camera.look_at(eye, target);
+
+        clock = advance_clock(clock, solar_system.simulation_delta(timestep));
+        let frame = renderer.render_scene(solar_system, camera, clock, timestep);
+        resolution = (frame.width, frame.height);
+        samples.push((start.elapsed(), renderer.stats.frame_sample()));
+        renderer.stats.end_frame();
+    }
+
+    println!("{}", summary(&samples, resolution.0, resolution.1));
+}
+
+// Pose de la cámara en un frame; cada tramo se mueve respecto de la posición actual de su cuerpo
+fn pose(solar_system: &SolarSystem, frame: usize) -> (Vec3, Vec3) {
+    let t = (frame % SEGMENT_FRAMES) as f32 / SEGMENT_FRAMES as f32;
+    match frame / SEGMENT_FRAMES {
+        0 => {
+            // Media vuelta alrededor del Sol, lo bastante cerca para que llene buena parte de la pantalla
+            let sun = &solar_system.bodies[0];
+            let angle = t * PI;
+            let distance = sun.scale * 2.5;
+            (sun.position + Vec3::new(angle.cos() * distance, sun.scale * 0.5, angle.sin() * distance), sun.position)
+        }
+        1 => {
+            // Desde arriba, con todo el sistema a la vista; el pequeño desvío en z evita mirar justo por el eje vertical
+            let height = solar_system.system_radius() * 1.1;
+            let angle = t * 0.5 * PI;
+            (Vec3::new(angle.cos() * height * 0.05, height, angle.sin() * height * 0.05 + 0.01), Vec3::new(0.0, 0.0, 0.0))
+        }
+        _ => {
+            // Rasante sobre el planeta más grande (Júpiter en el sistema fijo) mirando a su centro
+            let planet = largest_planet(solar_system);
+            let angle = t * PI * 0.5;
+            let distance = planet.scale * 1.15;
+            (planet.position + Vec3::new(angle.cos() * distance, planet.scale * 0.2, angle.sin() * distance), planet.position)
+        }
+    }
+}
+
+fn largest_planet(solar_system: &SolarSystem) -> &CelestialBody {
+    solar_system.bodies.iter()
+        .skip(1)
+        .filter(|body| body.parent.is_none())
+        .max_by(|a, b| a.scale.total_cmp(&b.scale))
+        .unwrap_or(&solar_system.bodies[0])
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Media, mediana y percentil 99 de tiempos en milisegundos
+fn distribution(mut times: Vec<f64>) -> String {
+    times.sort_by(f64::total_cmp);
+    let mean = times.iter().sum::<f64>() / times.len().max(1) as f64;
+    let at = |fraction: f64| times.get(((times.len() as f64 - 1.0) * fraction).round() as usize).copied().unwrap_or(0.0);
+    format!("{{\"mean\": {:.3}, \"median\": {:.3}, \"p99\": {:.3}}}", mean, at(0.5), at(0.99))
+}
+
+fn summary(samples: &[(Duration, FrameSample)], width: usize, height: usize) -> String {
+    let frame_ms = |range: &[(Duration, FrameSample)]| distribution(range.iter().map(|(time, _)| milliseconds(*time)).collect());
+
+    let stages: Vec<String> = (0..STAGE_COUNT)
+        .map(|stage| {
+            let total: f64 = samples.iter().map(|(_, sample)| milliseconds(sample.stages[stage])).sum();
+            format!("\"{}\": {:.3}", STAGE_NAMES[stage].to_lowercase(), total / samples.len().max(1) as f64)
+        })
+        .collect();
+    let segments: Vec<String> = SEGMENTS.iter()
+        .zip(samples.chunks(SEGMENT_FRAMES))
+        .map(|(name, range)| format!("\"{}\": {}", name, frame_ms(range)))
+        .collect();
+    let triangles: usize = samples.iter().map(|(_, sample)| sample.triangles).sum();
+    let fragments: usize = samples.iter().map(|(_, sample)| sample.fragments).sum();
+
+    format!(
+        "{{\n  \"frames\": {},\n  \"resolution\": [{}, {}],\n  \"frame_ms\": {},\n  \"stage_mean_ms\": {{{}}},\n  \"segment_frame_ms\": {{{}}},\n  \"triangles_rasterized\": {},\n  \"fragments_shaded\": {},\n  \"triangles_per_frame\": {:.1},\n  \"fragments_per_frame\": {:.1}\n}}",
+        samples.len(),
+        width,
+        height,
+        frame_ms(samples),
+        stages.join(", "),
+        segments.join(", "),
+        triangles,
+        fragments,
+        triangles as f64 / samples.len().max(1) as f64,
+        fragments as f64 / samples.len().max(1) as f64,
+    )
+}
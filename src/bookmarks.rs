@@ -0,0 +1,132 @@
+// Marcadores de cámara: Ctrl+1..4 guardan la pose actual y Alt+1..4 vuelven a ella.
+// Se guardan en `bookmarks.ron`, junto al ejecutable, para que sobrevivan entre ejecuciones
+use nalgebra_glm::Vec3;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use crate::camera::Camera;
+
+pub const SLOTS: usize = 4;
+const FILE_NAME: &str = "bookmarks.ron";
+
+#[derive(Clone, Copy)]
+pub struct Bookmark {
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub up: Vec3,
+}
+
+#[derive(Debug)]
+pub enum BookmarkError {
+    Io(PathBuf, std::io::Error),
+    Parse { path: PathBuf, line: usize, text: String },
+}
+
+impl fmt::Display for BookmarkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BookmarkError::Io(path, err) => write!(f, "no se pudieron acceder los marcadores '{}': {}", path.display(), err),
+            BookmarkError::Parse { path, line, text } => {
+                write!(f, "{}:{}: se esperaba None o Some((eye: (x, y, z), center: (x, y, z), up: (x, y, z))): '{}'", path.display(), line, text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BookmarkError {}
+
+pub struct Bookmarks {
+    slots: [Option<Bookmark>; SLOTS],
+    path: PathBuf,
+}
+
+impl Bookmarks {
+    // Todos los espacios vacíos, guardando en el archivo junto al ejecutable
+    pub fn empty() -> Self {
+        let path = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(FILE_NAME)))
+            .unwrap_or_else(|| PathBuf::from(FILE_NAME));
+        Bookmarks { slots: [None; SLOTS], path }
+    }
+
+    // Lee los marcadores guardados; si el archivo no existe todos los espacios empiezan vacíos
+    pub fn load() -> Result<Self, BookmarkError> {
+        let mut bookmarks = Bookmarks::empty();
+
+        let contents = match fs::read_to_string(&bookmarks.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(bookmarks),
+            Err(err) => return Err(BookmarkError::Io(bookmarks.path, err)),
+        };
+
+        // Lista RON con una entrada por línea, como la escribe `save`
+        let mut slot = 0;
+        for (index, line) in contents.lines().enumerate() {
+            let text = line.trim().trim_end_matches(',');
+            if text.is_empty() || text.starts_with("//") || text == "[" || text == "]" {
+                continue;
+            }
+            let entry = parse_entry(text).ok_or_else(|| BookmarkError::Parse {
+                path: bookmarks.path.clone(),
+                line: index + 1,
+                text: text.to_string(),
+            })?;
+            if slot < SLOTS {
+                bookmarks.slots[slot] = entry;
+            }
+            slot += 1;
+        }
+        Ok(bookmarks)
+    }
+
+    pub fn get(&self, slot: usize) -> Option<Bookmark> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    // Guarda la pose de la cámara en `slot` y reescribe el archivo
+    pub fn store(&mut self, slot: usize, camera: &Camera) -> Result<(), BookmarkError> {
+        if slot >= SLOTS {
+            return Ok(());
+        }
+        self.slots[slot] = Some(Bookmark { eye: camera.eye, center: camera.center(), up: camera.up });
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), BookmarkError> {
+        let mut contents = String::from("// Marcadores de cámara (Ctrl+1..4 guarda, Alt+1..4 vuelve)\n[\n");
+        for slot in &self.slots {
+            match slot {
+                Some(bookmark) => contents.push_str(&format!(
+                    "    Some((eye: {}, center: {}, up: {})),\n",
+                    tuple(bookmark.eye), tuple(bookmark.center), tuple(bookmark.up)
+                )),
+                None => contents.push_str("    None,\n"),
+            }
+        }
+        contents.push_str("]\n");
+        fs::write(&self.path, contents).map_err(|err| BookmarkError::Io(self.path.clone(), err))
+    }
+}
+
+fn tuple(v: Vec3) -> String {
+    format!("({:?}, {:?}, {:?})", v.x, v.y, v.z)
+}
+
+// `None` o `Some((eye: (..), center: (..), up: (..)))`: los nueve números en ese orden
+fn parse_entry(text: &str) -> Option<Option<Bookmark>> {
+    if text == "None" {
+        return Some(None);
+    }
+    let inner = text.strip_prefix("Some(")?;
+    let numbers: Vec<f32> = inner
+        .split(|c: char| c == '(' || c == ')' || c == ',' || c == ':' || c.is_whitespace())
+        .filter(|token| !token.is_empty() && !matches!(*token, "eye" | "center" | "up"))
+        .map(|token| token.parse().ok())
+        .collect::<Option<Vec<f32>>>()?;
+    if numbers.len() != 9 {
+        return None;
+    }
+    let vector = |i: usize| Vec3::new(numbers[i], numbers[i + 1], numbers[i + 2]);
+    Some(Some(Bookmark { eye: vector(0), center: vector(3), up: vector(6) }))
+}
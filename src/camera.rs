@@ -1,28 +1,68 @@
-use nalgebra_glm::{Vec3, rotate_vec3};
+use nalgebra_glm::{Vec2, Vec3, Mat4, Quat, look_at, quat_angle_axis, quat_normalize, quat_rotate_vec3};
 use std::f32::consts::PI;
 
+// Límite de inclinación para no pasar por encima del vector up
+const MAX_PITCH: f32 = PI / 2.0 - 0.1;
+const MIN_DISTANCE: f32 = 0.5;
+// Inclinación máxima que deja la nivelación; lo que pase de ahí se considera deriva y se corrige
+const LEVEL_MAX_PITCH: f32 = PI / 4.0;
+
 pub struct Camera {
   pub eye: Vec3,
-  pub center: Vec3,
-  pub up: Vec3,
+  pub up: Vec3,            // Vector up del mundo, eje del yaw
+  orientation: Quat,       // Rotación desde la orientación base (mirando hacia -Z)
+  distance: f32,           // Distancia del ojo al punto de interés
+  min_distance: f32,       // Distancia mínima permitida al hacer zoom
+  pub orbit_mode: bool,    // El punto de interés sigue a un cuerpo y el mouse gira alrededor de él
   pub has_changed: bool
 }
 
 impl Camera {
   pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-    Camera {
+    let mut camera = Camera {
       eye,
-      center,
       up,
+      orientation: Quat::identity(),
+      distance: 1.0,
+      min_distance: MIN_DISTANCE,
+      orbit_mode: false,
       has_changed: true,
+    };
+    camera.look_at(eye, center);
+    camera
+  }
+
+  // Coloca la cámara en `eye` mirando hacia `target`
+  pub fn look_at(&mut self, eye: Vec3, target: Vec3) {
+    let offset = target - eye;
+    let distance = offset.magnitude();
+    if distance > f32::EPSILON {
+      let direction = offset / distance;
+      let yaw = (-direction.x).atan2(-direction.z);
+      let pitch = direction.y.clamp(-1.0, 1.0).asin();
+      self.orientation = quat_normalize(
+        &(quat_angle_axis(yaw, &self.up) * quat_angle_axis(pitch, &Vec3::new(1.0, 0.0, 0.0)))
+      );
+      self.distance = distance;
     }
+    self.eye = eye;
+    self.has_changed = true;
   }
+
+  pub fn center(&self) -> Vec3 {
+    self.eye + self.get_forward() * self.distance
+  }
+
   pub fn get_forward(&self) -> Vec3 {
-    (self.center - self.eye).normalize()
+    quat_rotate_vec3(&self.orientation, &Vec3::new(0.0, 0.0, -1.0)).normalize()
   }
 
   pub fn get_right(&self) -> Vec3 {
-      self.get_forward().cross(&self.up).normalize()
+    quat_rotate_vec3(&self.orientation, &Vec3::new(1.0, 0.0, 0.0)).normalize()
+  }
+
+  pub fn get_up(&self) -> Vec3 {
+    quat_rotate_vec3(&self.orientation, &Vec3::new(0.0, 1.0, 0.0)).normalize()
   }
 
   pub fn get_rotation(&self) -> Vec3 {
@@ -33,6 +73,20 @@ impl Camera {
           0.0
       )
   }
+
+  pub fn view_matrix(&self) -> Mat4 {
+    look_at(&self.eye, &self.center(), &self.get_up())
+  }
+
+  // Vista con el ojo desplazado `eye_offset` y la mirada girada `look_offset` radianes, ambos sobre los
+  // ejes derecha y arriba (sacudida de un impacto); la pose de la cámara no cambia
+  pub fn shaken_view_matrix(&self, eye_offset: Vec2, look_offset: Vec2) -> Mat4 {
+    let (right, up) = (self.get_right(), self.get_up());
+    let eye = self.eye + right * eye_offset.x + up * eye_offset.y;
+    let forward = self.get_forward() + right * look_offset.x + up * look_offset.y;
+    look_at(&eye, &(eye + forward * self.distance), &up)
+  }
+
   pub fn handle_mouse_movement(&mut self, delta_x: f32, delta_y: f32, sensitivity: f32) {
     self.orbit(delta_x * sensitivity, delta_y * sensitivity);
   }
@@ -41,49 +95,89 @@ impl Camera {
     self.zoom(delta);
   }
 
+  // Gira el ojo alrededor del punto de interés
   pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
-    let radius_vector = self.eye - self.center;
-    let radius = radius_vector.magnitude();
-
-    let current_yaw = radius_vector.z.atan2(radius_vector.x);
-
-    let radius_xz = (radius_vector.x * radius_vector.x + radius_vector.z * radius_vector.z).sqrt();
-    let current_pitch = (-radius_vector.y).atan2(radius_xz);
-
-    let new_yaw = (current_yaw + delta_yaw) % (2.0 * PI);
-    let new_pitch = (current_pitch + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
-
-    let new_eye = self.center + Vec3::new(
-      radius * new_yaw.cos() * new_pitch.cos(),
-      -radius * new_pitch.sin(),
-      radius * new_yaw.sin() * new_pitch.cos()
-    );
+    let center = self.center();
+    self.rotate(delta_yaw, delta_pitch);
+    self.eye = center - self.get_forward() * self.distance;
+  }
 
-    self.eye = new_eye;
+  // Gira la orientación sin mover el ojo: yaw sobre el up del mundo, pitch sobre el eje derecho local
+  pub fn rotate(&mut self, delta_yaw: f32, delta_pitch: f32) {
+    let current_pitch = self.get_forward().y.clamp(-1.0, 1.0).asin();
+    // Fuera del límite (p. ej. vista de pájaro) solo se permite volver hacia el horizonte
+    let pitch_step = if current_pitch.abs() > MAX_PITCH {
+      if (current_pitch + delta_pitch).abs() < current_pitch.abs() { delta_pitch } else { 0.0 }
+    } else {
+      (current_pitch + delta_pitch).clamp(-MAX_PITCH, MAX_PITCH) - current_pitch
+    };
+
+    let yaw_rotation = quat_angle_axis(-delta_yaw, &self.up);
+    let pitch_rotation = quat_angle_axis(pitch_step, &Vec3::new(1.0, 0.0, 0.0));
+    self.orientation = quat_normalize(&(yaw_rotation * self.orientation * pitch_rotation));
     self.has_changed = true;
   }
 
-  pub fn zoom(&mut self, delta: f32) {
-    let direction = (self.center - self.eye).normalize();
-    self.eye += direction * delta;
+  // Acerca la cámara a una pose nivelada una fracción `amount` (1 la deja nivelada): quita el alabeo
+  // respecto del eje Y del mundo y lleva la inclinación a no más de LEVEL_MAX_PITCH. La orientación se
+  // descompone en rumbo, inclinación y alabeo, y el rumbo no se toca; el ojo tampoco se mueve
+  pub fn relevel(&mut self, amount: f32) {
+    let amount = amount.clamp(0.0, 1.0);
+    let world_up = Vec3::new(0.0, 1.0, 0.0);
+    let forward = self.get_forward();
+    // Mirando justo hacia arriba o abajo el rumbo lo da el up de la cámara
+    let horizontal = Vec3::new(forward.x, 0.0, forward.z);
+    let heading = if horizontal.magnitude() > 1e-3 { horizontal } else { self.get_up() * -forward.y.signum() };
+    let yaw_rotation = quat_angle_axis((-heading.x).atan2(-heading.z), &world_up);
+
+    let pitch = forward.y.clamp(-1.0, 1.0).asin();
+    let level = quat_normalize(&(yaw_rotation * quat_angle_axis(pitch, &Vec3::new(1.0, 0.0, 0.0))));
+    let level_up = quat_rotate_vec3(&level, &world_up);
+    let level_right = quat_rotate_vec3(&level, &Vec3::new(1.0, 0.0, 0.0));
+    let up = self.get_up();
+    let roll = (-up.dot(&level_right)).atan2(up.dot(&level_up));
+
+    let target_pitch = pitch.clamp(-LEVEL_MAX_PITCH, LEVEL_MAX_PITCH);
+    self.orientation = quat_normalize(&(
+      yaw_rotation
+        * quat_angle_axis(pitch + (target_pitch - pitch) * amount, &Vec3::new(1.0, 0.0, 0.0))
+        * quat_angle_axis(roll * (1.0 - amount), &Vec3::new(0.0, 0.0, 1.0))
+    ));
+    self.up = world_up;
     self.has_changed = true;
   }
 
-  pub fn move_center(&mut self, direction: Vec3) {
-    let radius_vector = self.center - self.eye;
-    let radius = radius_vector.magnitude();
-
-    let angle_x = direction.x * 0.05; // Adjust this factor to control rotation speed
-    let angle_y = direction.y * 0.05;
+  // Modo órbita: el punto de interés queda fijo en `target` y el zoom no baja de `min_distance`
+  pub fn enter_orbit_mode(&mut self, target: Vec3, min_distance: f32) {
+    self.min_distance = min_distance;
+    self.orbit_mode = true;
+    self.look_at(self.eye, target);
+    self.follow(target);
+  }
 
-    let rotated = rotate_vec3(&radius_vector, angle_x, &Vec3::new(0.0, 1.0, 0.0));
+  // Vuelve al vuelo libre desde la pose actual, sin saltos
+  pub fn exit_orbit_mode(&mut self) {
+    self.min_distance = MIN_DISTANCE;
+    self.orbit_mode = false;
+  }
 
-    let right = rotated.cross(&self.up).normalize();
-    let final_rotated = rotate_vec3(&rotated, angle_y, &right);
+  // Mantiene el ojo a la misma distancia y ángulo de un objetivo que se mueve
+  pub fn follow(&mut self, target: Vec3) {
+    self.distance = self.distance.max(self.min_distance);
+    self.eye = target - self.get_forward() * self.distance;
+    self.has_changed = true;
+  }
 
-    self.center = self.eye + final_rotated.normalize() * radius;
+  // Cámara de persecución: el ojo queda detrás y por encima del objetivo, con la orientación actual
+  pub fn chase(&mut self, target: Vec3, distance: f32, height: f32) {
+    self.eye = target - self.get_forward() * distance + self.get_up() * height;
     self.has_changed = true;
   }
-  
 
-}
\ No newline at end of file
+  pub fn zoom(&mut self, delta: f32) {
+    let step = delta.min(self.distance - self.min_distance);
+    self.eye += self.get_forward() * step;
+    self.distance -= step;
+    self.has_changed = true;
+  }
+}
@@ -5,17 +5,18 @@ pub struct Color {
   r: u8,
   g: u8,
   b: u8,
+  a: u8, // Opacity: 255 is opaque, lower values are blended over what is behind
 }
 
 impl Color {
   // Constructor to initialize the color using r, g, b values as u8
-  pub fn new(r: u8, g: u8, b: u8) -> Self {
-    Color { r, g, b }
+  pub const fn new(r: u8, g: u8, b: u8) -> Self {
+    Color { r, g, b, a: 255 }
   }
 
   // default color
-  pub fn black() -> Self {
-    Color { r: 0, g: 0, b: 0 }
+  pub const fn black() -> Self {
+    Color { r: 0, g: 0, b: 0, a: 255 }
   }
 
   // New constructor to initialize the color using r, g, b values as f32 (0.0 to 1.0)
@@ -24,19 +25,29 @@ impl Color {
       r: (r.clamp(0.0, 1.0) * 255.0) as u8,
       g: (g.clamp(0.0, 1.0) * 255.0) as u8,
       b: (b.clamp(0.0, 1.0) * 255.0) as u8,
+      a: 255,
     }
   }
 
-  // Function to create a color from a hex value
-  pub fn from_hex(hex: u32) -> Self {
+  // Function to create a color from a hex value (const, so shader palettes can be constants)
+  pub const fn from_hex(hex: u32) -> Self {
     let r = ((hex >> 16) & 0xFF) as u8;
     let g = ((hex >> 8) & 0xFF) as u8;
     let b = (hex & 0xFF) as u8;
-    Color { r, g, b }
+    Color { r, g, b, a: 255 }
   }
 
-  // Function to return the color as a hex value
-  pub fn to_hex(&self) -> u32 {
+  // Same color with the given opacity (0.0 transparent to 1.0 opaque)
+  pub fn with_alpha(self, alpha: f32) -> Self {
+    Color { a: (alpha.clamp(0.0, 1.0) * 255.0).round() as u8, ..self }
+  }
+
+  pub fn alpha(&self) -> f32 {
+    self.a as f32 / 255.0
+  }
+
+  // Function to return the color as a hex value (alpha is not packed)
+  pub fn to_hex(self) -> u32 {
     ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
   }
 
@@ -47,50 +58,33 @@ impl Color {
       r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
       g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
       b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+      a: (self.a as f32 + (other.a as f32 - self.a as f32) * t).round() as u8,
     }
   }
 
-  pub fn is_black(&self) -> bool {
-    self.r == 0 && self.g == 0 && self.b == 0 
-  }
-
-  // New blend mode methods
-  pub fn blend_normal(&self, blend: &Color) -> Color {
-    if blend.is_black() { *self } else { *blend }
-  }
-
-  pub fn blend_multiply(&self, blend: &Color) -> Color {
-    Color::new(
-      ((self.r as f32 * blend.r as f32) / 255.0) as u8,
-      ((self.g as f32 * blend.g as f32) / 255.0) as u8,
-      ((self.b as f32 * blend.b as f32) / 255.0) as u8
-    )
-  }
-
-  pub fn blend_add(&self, blend: &Color) -> Color {
-    Color::new(
-      (self.r as u16 + blend.r as u16).min(255) as u8,
-      (self.g as u16 + blend.g as u16).min(255) as u8,
-      (self.b as u16 + blend.b as u16).min(255) as u8
-    )
-  }
-
-  pub fn blend_subtract(&self, blend: &Color) -> Color {
-    let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-    let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-    let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
-
-    Color::new(r, g, b)
-  }
-
-  pub fn blend_screen(&self, blend: &Color) -> Color {
-    Color::new(
-      255 - ((255 - self.r as u16) * (255 - blend.r as u16) / 255) as u8,
-      255 - ((255 - self.g as u16) * (255 - blend.g as u16) / 255) as u8,
-      255 - ((255 - self.b as u16) * (255 - blend.b as u16) / 255) as u8
-    )
+  // Hue in degrees (wraps around), saturation and value in 0.0..1.0
+  pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let saturation = saturation.clamp(0.0, 1.0);
+    let value = value.clamp(0.0, 1.0);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+      0 => (chroma, x, 0.0),
+      1 => (x, chroma, 0.0),
+      2 => (0.0, chroma, x),
+      3 => (0.0, x, chroma),
+      4 => (x, 0.0, chroma),
+      _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    Color {
+      r: ((r + m) * 255.0).round() as u8,
+      g: ((g + m) * 255.0).round() as u8,
+      b: ((b + m) * 255.0).round() as u8,
+      a: 255,
+    }
   }
-
 }
 
 // Implement addition for Color
@@ -104,6 +98,7 @@ impl Add for Color {
       r: self.r.saturating_add(other.r),
       g: self.g.saturating_add(other.g),
       b: self.b.saturating_add(other.b),
+      a: self.a,
     }
   }
 }
@@ -119,6 +114,21 @@ impl Mul<f32> for Color {
       r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
       g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
       b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+      a: self.a,
+    }
+  }
+}
+
+// Channel-wise modulation (e.g. a surface color lit by a tinted light); keeps the left alpha
+impl Mul<Color> for Color {
+  type Output = Color;
+
+  fn mul(self, other: Color) -> Color {
+    Color {
+      r: (self.r as u16 * other.r as u16 / 255) as u8,
+      g: (self.g as u16 * other.g as u16 / 255) as u8,
+      b: (self.b as u16 * other.b as u16 / 255) as u8,
+      a: self.a,
     }
   }
 }
@@ -126,6 +136,88 @@ impl Mul<f32> for Color {
 // Implement display formatting for Color
 impl fmt::Display for Color {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+    write!(f, "Color(r: {}, g: {}, b: {}, a: {})", self.r, self.g, self.b, self.a)
+  }
+}
+
+// sRGB <-> linear conversion for a single channel in 0.0..1.0 (gamma 2.2 approximation)
+pub fn srgb_to_linear(value: f32) -> f32 {
+  value.max(0.0).powf(2.2)
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+  value.max(0.0).powf(1.0 / 2.2)
+}
+
+// Filmic ACES curve (Narkowicz approximation): maps HDR linear values to 0.0..1.0 without a hard clip
+pub fn tone_map_aces(value: f32) -> f32 {
+  let x = value.max(0.0);
+  ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn srgb_and_linear_round_trip() {
+    for i in 0..=255 {
+      let value = i as f32 / 255.0;
+      assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5, "value {}", value);
+      assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5, "value {}", value);
+    }
+    assert_eq!(srgb_to_linear(-0.5), 0.0);
+    assert_eq!(linear_to_srgb(-0.5), 0.0);
+  }
+
+  #[test]
+  fn aces_is_monotonic_and_bounded() {
+    assert_eq!(tone_map_aces(0.0), 0.0);
+    assert_eq!(tone_map_aces(-1.0), 0.0);
+    let mut previous = 0.0;
+    for i in 0..=4000 {
+      let mapped = tone_map_aces(i as f32 * 0.01);
+      assert!((0.0..=1.0).contains(&mapped), "{} -> {}", i as f32 * 0.01, mapped);
+      assert!(mapped >= previous, "{} -> {}", i as f32 * 0.01, mapped);
+      previous = mapped;
+    }
+    assert_eq!(tone_map_aces(1e6), 1.0);
+  }
+
+  #[test]
+  fn arithmetic_saturates() {
+    let bright = Color::new(200, 100, 250);
+    assert_eq!((bright + Color::new(100, 100, 10)).to_hex(), 0xFFC8FF);
+    assert_eq!((bright * 2.0).to_hex(), 0xFFC8FF);
+    assert_eq!((bright * -1.0).to_hex(), 0x000000);
+    assert_eq!((bright * Color::new(255, 255, 255)).to_hex(), bright.to_hex());
+    assert_eq!((bright * Color::black()).to_hex(), 0x000000);
+    // The alpha of the left operand is kept
+    assert_eq!((bright.with_alpha(0.5) + bright).alpha(), bright.with_alpha(0.5).alpha());
+  }
+
+  #[test]
+  fn lerp_clamps_t() {
+    let (a, b) = (Color::new(0, 100, 200), Color::new(200, 100, 0));
+    assert_eq!(a.lerp(&b, -1.0).to_hex(), a.to_hex());
+    assert_eq!(a.lerp(&b, 2.0).to_hex(), b.to_hex());
+    assert_eq!(a.lerp(&b, 0.5).to_hex(), 0x646464);
+  }
+
+  #[test]
+  fn hex_round_trips() {
+    for hex in [0x000000, 0xFFFFFF, 0x123456, 0xFEDCBA, 0x00FF7F] {
+      assert_eq!(Color::from_hex(hex).to_hex(), hex);
+    }
+    // Bits above the 24 color bits are ignored
+    assert_eq!(Color::from_hex(0xAB123456).to_hex(), 0x123456);
+  }
+
+  #[test]
+  fn hue_wraps_around() {
+    assert_eq!(Color::from_hsv(0.0, 1.0, 1.0).to_hex(), 0xFF0000);
+    assert_eq!(Color::from_hsv(360.0, 1.0, 1.0).to_hex(), 0xFF0000);
+    assert_eq!(Color::from_hsv(480.0, 1.0, 1.0).to_hex(), Color::from_hsv(120.0, 1.0, 1.0).to_hex());
+    assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0).to_hex(), 0x0000FF);
   }
 }
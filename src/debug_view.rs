@@ -0,0 +1,125 @@
+// Vistas de depuración (F4): reemplazan el sombreado para revisar normales, UVs, iluminación,
+// profundidad o cuántos fragmentos escribió cada pixel. Los modos de dibujo (F5) cambian
+// además cómo se rasteriza la geometría
+use crate::framebuffer::Framebuffer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugMode {
+    Off,
+    ViewNormals,   // Normal en espacio de vista como RGB
+    WorldNormals,  // Normal en espacio del mundo como RGB
+    Depth,         // Z-buffer en escala de grises (cerca = claro)
+    Uv,            // Coordenadas de textura como rojo y verde, con un tablero fino encima
+    Lighting,      // Solo la luz del Sol que recibe la superficie, con sombras
+    Overdraw,      // Mapa de calor de escrituras por pixel
+}
+
+const CYCLE: [DebugMode; 7] = [
+    DebugMode::Off,
+    DebugMode::ViewNormals,
+    DebugMode::WorldNormals,
+    DebugMode::Depth,
+    DebugMode::Uv,
+    DebugMode::Lighting,
+    DebugMode::Overdraw,
+];
+
+// Modo de dibujo de toda la geometría (planetas, anillos, colas y la nave), para separar los
+// problemas de la malla o del rasterizador de los de los shaders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    Filled,     // Sombreado completo
+    Wireframe,  // Solo las aristas de los triángulos, coloreadas por profundidad
+    Flat,       // Una normal por triángulo e iluminación de Lambert, sin el ruido de los shaders
+}
+
+const RENDER_MODE_CYCLE: [RenderMode; 3] = [RenderMode::Filled, RenderMode::Wireframe, RenderMode::Flat];
+
+impl RenderMode {
+    pub fn next(self) -> Self {
+        let index = RENDER_MODE_CYCLE.iter().position(|&mode| mode == self).unwrap_or(0);
+        RENDER_MODE_CYCLE[(index + 1) % RENDER_MODE_CYCLE.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Filled => "RELLENO",
+            RenderMode::Wireframe => "ALAMBRE",
+            RenderMode::Flat => "PLANO",
+        }
+    }
+}
+
+// Escrituras a partir de las cuales el mapa de calor queda en blanco
+const OVERDRAW_MAX: u16 = 8;
+
+impl DebugMode {
+    pub fn next(self) -> Self {
+        let index = CYCLE.iter().position(|&mode| mode == self).unwrap_or(0);
+        CYCLE[(index + 1) % CYCLE.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugMode::Off => "NORMAL",
+            DebugMode::ViewNormals => "NORMALES (VISTA)",
+            DebugMode::WorldNormals => "NORMALES (MUNDO)",
+            DebugMode::Depth => "PROFUNDIDAD",
+            DebugMode::Uv => "UV",
+            DebugMode::Lighting => "ILUMINACIÓN",
+            DebugMode::Overdraw => "SOBREDIBUJO",
+        }
+    }
+}
+
+// Modos que se resuelven sobre los buffers ya dibujados; el resto lo hace el fragment shader
+pub fn resolve(framebuffer: &mut Framebuffer, mode: DebugMode) {
+    match mode {
+        DebugMode::Depth => depth_view(framebuffer),
+        DebugMode::Overdraw => overdraw_view(framebuffer),
+        _ => {}
+    }
+}
+
+// Escala logarítmica entre la profundidad mínima y máxima del frame, para que se distingan
+// tanto los cuerpos cercanos como los lejanos. El fondo (estrellas y vacío) queda negro
+fn depth_view(framebuffer: &mut Framebuffer) {
+    let finite = framebuffer.zbuffer.iter().copied().filter(|&depth| depth > 0.0 && depth < f32::MAX);
+    let (near, far) = finite.fold((f32::MAX, 0.0f32), |(near, far), depth| (near.min(depth), far.max(depth)));
+    if near > far {
+        framebuffer.buffer.iter_mut().for_each(|pixel| *pixel = 0);
+        return;
+    }
+    let range = (far / near).ln().max(f32::EPSILON);
+
+    for (pixel, &depth) in framebuffer.buffer.iter_mut().zip(framebuffer.zbuffer.iter()) {
+        *pixel = if depth > 0.0 && depth < f32::MAX {
+            let gray = (255.0 * (1.0 - 0.9 * (depth / near).ln() / range)) as u32;
+            (gray << 16) | (gray << 8) | gray
+        } else {
+            0
+        };
+    }
+}
+
+// Negro = ninguna escritura, luego azul, verde, amarillo y rojo hasta blanco con OVERDRAW_MAX o más
+fn overdraw_view(framebuffer: &mut Framebuffer) {
+    const RAMP: [[f32; 3]; 6] = [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.2, 1.0],
+        [0.0, 0.9, 0.3],
+        [1.0, 0.9, 0.0],
+        [1.0, 0.1, 0.0],
+        [1.0, 1.0, 1.0],
+    ];
+    for (pixel, &count) in framebuffer.buffer.iter_mut().zip(framebuffer.overdraw.iter()) {
+        let t = count.min(OVERDRAW_MAX) as f32 / OVERDRAW_MAX as f32 * (RAMP.len() - 1) as f32;
+        let index = (t as usize).min(RAMP.len() - 2);
+        let blend = t - index as f32;
+        let channel = |c: usize| {
+            let value = RAMP[index][c] + (RAMP[index + 1][c] - RAMP[index][c]) * blend;
+            (value * 255.0) as u32
+        };
+        *pixel = (channel(0) << 16) | (channel(1) << 8) | channel(2);
+    }
+}
@@ -7,7 +7,6 @@ pub struct Fragment {
     pub color: Color,
     pub depth: f32,
     pub normal: Vec3,
-    pub intensity: f32,
     pub vertex_position: Vec3,
     pub uv: Option<Vec2>,
 }
@@ -18,7 +17,6 @@ impl Fragment {
         color: Color,
         depth: f32,
         normal: Vec3,
-        intensity: f32,
         vertex_position: Vec3,
         uv: Option<Vec2>
     ) -> Self {  
@@ -27,7 +25,6 @@ impl Fragment {
             color,
             depth,
             normal,
-            intensity,
             vertex_position,
             uv
         }
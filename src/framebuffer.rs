@@ -6,9 +6,15 @@ pub struct Framebuffer {
   pub height: usize,
   pub buffer: Vec<u32>,
   pub zbuffer: Vec<f32>,
-  pub emissive_buffer: Vec<u32>,
+  pub emissive_buffer: Vec<u32>, // Color emisivo por pixel (0xRRGGBB) usado para el bloom
+  pub emissive_count: usize,     // Pixeles emisivos escritos desde el último clear
+  emissive_written: bool,        // Algo escribió emisión desde el último clear (si no, el buffer sigue en cero)
+  pub overdraw: Vec<u16>,        // Escrituras de color por pixel desde el último clear (vista de depuración)
+  pub accumulation: Vec<[f32; 3]>, // Promedio de los frames anteriores (anti-aliasing temporal), 0-255 por canal
+  pub accumulated_frames: u32,     // Frames en el promedio desde el último reinicio
   background_color: u32,
   current_color: u32,
+  current_alpha: f32,
 }
 
 impl Framebuffer {
@@ -19,39 +25,272 @@ impl Framebuffer {
           buffer: vec![0; width * height],
           zbuffer: vec![f32::INFINITY; width * height],
           emissive_buffer: vec![0; width * height],
+          emissive_count: 0,
+          emissive_written: false,
+          overdraw: vec![0; width * height],
+          accumulation: vec![[0.0; 3]; width * height],
+          accumulated_frames: 0,
           background_color: 0x000000,
           current_color: 0xFFFFFF,
+          current_alpha: 1.0,
       }
   }
 
   pub fn clear(&mut self) {
-      for pixel in self.buffer.iter_mut() {
-          *pixel = self.background_color;
-      }
-      for depth in self.zbuffer.iter_mut() {
-          *depth = f32::INFINITY;
+      self.buffer.fill(self.background_color);
+      self.clear_depth();
+  }
+
+  // Como `clear` pero sin tocar el color, para cuando el cielo se dibuja después de lo opaco
+  // y cubre todo lo que quedó sin dibujar (ver `fill_sky`)
+  pub fn clear_depth(&mut self) {
+      self.zbuffer.fill(f32::INFINITY);
+      if self.emissive_written {
+          self.emissive_buffer.fill(0);
+          self.emissive_written = false;
       }
-      for emission in self.emissive_buffer.iter_mut() {
-          *emission = 0; // Limpia el buffer emisivo
+      self.emissive_count = 0;
+      self.overdraw.fill(0);
+  }
+
+  // Color de fondo en los pixeles a los que no llegó nada (los que siguen a profundidad infinita)
+  pub fn fill_sky(&mut self) {
+      let background = self.background_color;
+      for (pixel, &depth) in self.buffer.iter_mut().zip(self.zbuffer.iter()) {
+          *pixel = if depth == f32::INFINITY { background } else { *pixel };
       }
   }
 
+  // Con el color actual opaco escribe color y profundidad; si es translúcido lo mezcla
+  // ("source over") con lo que hay detrás, respetando la profundidad pero sin escribirla
   pub fn point(&mut self, x: usize, y: usize, depth: f32, emit: u32) {
       if x < self.width && y < self.height {
           let index = y * self.width + x;
-          if self.zbuffer[index] > depth {
+          if self.zbuffer[index] > depth && self.current_alpha < 1.0 {
+              self.overdraw[index] = self.overdraw[index].saturating_add(1);
+              self.buffer[index] = mix_colors(self.buffer[index], self.current_color, self.current_alpha);
+              if emit != 0 {
+                  self.emissive_buffer[index] = mix_colors(self.emissive_buffer[index], emit, self.current_alpha);
+                  self.emissive_count += 1;
+                  self.emissive_written = true;
+              }
+          } else if self.zbuffer[index] > depth {
+              self.overdraw[index] = self.overdraw[index].saturating_add(1);
               self.buffer[index] = self.current_color;
               self.zbuffer[index] = depth;
               self.emissive_buffer[index] = emit;
+              if emit != 0 {
+                  self.emissive_count += 1;
+                  self.emissive_written = true;
+              }
+          }
+      }
+  }
+
+  // Fragmento translúcido aditivo: respeta la profundidad pero no la escribe
+  pub fn add_point(&mut self, x: usize, y: usize, depth: f32, color: u32, emit: u32) {
+      if x < self.width && y < self.height {
+          let index = y * self.width + x;
+          if self.zbuffer[index] > depth {
+              self.overdraw[index] = self.overdraw[index].saturating_add(1);
+              self.buffer[index] = add_colors(self.buffer[index], color);
+              if emit != 0 {
+                  self.emissive_buffer[index] = add_colors(self.emissive_buffer[index], emit);
+                  self.emissive_count += 1;
+                  self.emissive_written = true;
+              }
+          }
+      }
+  }
+
+  // Mezcla el frame actual con el promedio acumulado y deja el resultado en `buffer`. Los primeros
+  // frames pesan 1/n (promedio exacto); después nunca menos de `min_weight`, para que la imagen
+  // siga los cambios lentos de la escena. Antes de mezclar, el historial se limita al rango de
+  // colores de los 3x3 vecinos en el frame actual: lo que se movió (un planeta en su órbita) no
+  // deja estela, y un borde quieto conserva su promedio porque sus vecinos cubren ambos lados
+  pub fn accumulate(&mut self, min_weight: f32) {
+      self.accumulated_frames += 1;
+      let weight = (1.0 / self.accumulated_frames as f32).max(min_weight);
+      let first = self.accumulated_frames == 1;
+
+      // Rango de los 3x3 vecinos en dos pasadas: por columna (la fila y sus vecinas) y después por fila
+      let mut column_low = vec![[0u8; 3]; self.width];
+      let mut column_high = vec![[0u8; 3]; self.width];
+      for y in 0..self.height {
+          let rows = y.saturating_sub(1)..(y + 2).min(self.height);
+          for x in 0..self.width {
+              let (mut low, mut high) = ([255u8; 3], [0u8; 3]);
+              for ny in rows.clone() {
+                  let rgb = unpack(self.buffer[ny * self.width + x]);
+                  for c in 0..3 {
+                      low[c] = low[c].min(rgb[c]);
+                      high[c] = high[c].max(rgb[c]);
+                  }
+              }
+              column_low[x] = low;
+              column_high[x] = high;
+          }
+
+          for x in 0..self.width {
+              let columns = x.saturating_sub(1)..(x + 2).min(self.width);
+              let index = y * self.width + x;
+              let rgb = unpack(self.buffer[index]);
+              let average = &mut self.accumulation[index];
+              for c in 0..3 {
+                  let value = rgb[c] as f32;
+                  let history = if first {
+                      value
+                  } else {
+                      let low = column_low[columns.clone()].iter().map(|range| range[c]).min().unwrap_or(0);
+                      let high = column_high[columns.clone()].iter().map(|range| range[c]).max().unwrap_or(255);
+                      average[c].clamp(low as f32, high as f32)
+                  };
+                  average[c] = history + (value - history) * weight;
+              }
+          }
+      }
+
+      for (pixel, average) in self.buffer.iter_mut().zip(self.accumulation.iter()) {
+          let channel = |value: f32| ((value + 0.5) as u32).min(255);
+          *pixel = (channel(average[0]) << 16) | (channel(average[1]) << 8) | channel(average[2]);
+      }
+  }
+
+  // Descarta el promedio: el próximo `accumulate` empieza de cero con el frame actual
+  pub fn reset_accumulation(&mut self) {
+      self.accumulated_frames = 0;
+  }
+
+  // Amplía (o copia, si tienen el mismo tamaño) otro framebuffer sobre este: el color con
+  // interpolación bilineal y la profundidad del pixel más cercano, para las pruebas de oclusión
+  pub fn upscale_from(&mut self, source: &Framebuffer) {
+      if source.width == self.width && source.height == self.height {
+          self.buffer.copy_from_slice(&source.buffer);
+          self.zbuffer.copy_from_slice(&source.zbuffer);
+          return;
+      }
+      self.upscale_into(source, 0, 0, self.width, self.height);
+  }
+
+  // Igual que `upscale_from` pero en el rectángulo (left, top, width, height) de este framebuffer
+  // (el resto no se toca), para componer las vistas adicionales sobre la principal
+  pub fn upscale_into(&mut self, source: &Framebuffer, left: usize, top: usize, width: usize, height: usize) {
+      let width = width.min(self.width.saturating_sub(left));
+      let height = height.min(self.height.saturating_sub(top));
+
+      // Pixeles de origen a cada lado del centro del pixel de destino y el peso del segundo;
+      // las columnas son iguales en todas las filas
+      let sample = |position: usize, size: usize, source_size: usize| {
+          let scale = source_size as f32 / size as f32;
+          let f = ((position as f32 + 0.5) * scale - 0.5).clamp(0.0, (source_size - 1) as f32);
+          let low = f as usize;
+          let nearest = (((position as f32 + 0.5) * scale) as usize).min(source_size - 1);
+          (low, (low + 1).min(source_size - 1), f - low as f32, nearest)
+      };
+      let columns: Vec<_> = (0..width).map(|x| sample(x, width, source.width)).collect();
+
+      for y in 0..height {
+          let (y0, y1, ty, nearest_y) = sample(y, height, source.height);
+          let (row0, row1) = (y0 * source.width, y1 * source.width);
+          for (x, &(x0, x1, tx, nearest_x)) in columns.iter().enumerate() {
+              let corners = [source.buffer[row0 + x0], source.buffer[row0 + x1], source.buffer[row1 + x0], source.buffer[row1 + x1]];
+              let mut color = 0;
+              for shift in [16, 8, 0] {
+                  let [a, b, c, d] = corners.map(|pixel| ((pixel >> shift) & 0xFF) as f32);
+                  let top = a + (b - a) * tx;
+                  let bottom = c + (d - c) * tx;
+                  color |= ((top + (bottom - top) * ty + 0.5) as u32).min(255) << shift;
+              }
+              let index = (top + y) * self.width + left + x;
+              self.buffer[index] = color;
+              self.zbuffer[index] = source.zbuffer[nearest_y * source.width + nearest_x];
           }
       }
   }
 
+  pub fn has_emission(&self) -> bool {
+      self.emissive_count > 0
+  }
+
   pub fn set_background_color(&mut self, color: u32) {
       self.background_color = color;
   }
 
+  // Color opaco para los siguientes puntos
   pub fn set_current_color(&mut self, color: u32) {
       self.current_color = color;
+      self.current_alpha = 1.0;
   }
+
+  // Color con opacidad en [0, 1] para los siguientes puntos
+  pub fn set_current_color_alpha(&mut self, color: u32, alpha: f32) {
+      self.current_color = color;
+      self.current_alpha = alpha.clamp(0.0, 1.0);
+  }
+
+  // Primitivas 2D para superposiciones (minimapa): escriben solo el color, sin profundidad,
+  // y recortan contra los bordes de la pantalla
+
+  // Rectángulo relleno mezclado con lo que hay debajo según `alpha`
+  pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32, alpha: f32) {
+      let alpha = alpha.clamp(0.0, 1.0);
+      for py in y..(y + height).min(self.height) {
+          for px in x..(x + width).min(self.width) {
+              let index = py * self.width + px;
+              self.buffer[index] = mix_colors(self.buffer[index], color, alpha);
+          }
+      }
+  }
+
+  // Segmento de un pixel de ancho (DDA) entre dos puntos de pantalla
+  pub fn draw_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: u32) {
+      let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+      for i in 0..=steps {
+          let t = i as f32 / steps as f32;
+          self.put_pixel(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, color);
+      }
+  }
+
+  // Contorno de un círculo, con suficientes segmentos para que no se vean los vértices
+  pub fn draw_circle(&mut self, cx: f32, cy: f32, radius: f32, color: u32) {
+      let segments = ((radius * std::f32::consts::TAU / 2.0).ceil() as usize).clamp(8, 512);
+      let point = |i: usize| {
+          let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+          (cx + radius * angle.cos(), cy + radius * angle.sin())
+      };
+      for i in 0..segments {
+          let (x0, y0) = point(i);
+          let (x1, y1) = point(i + 1);
+          self.draw_line(x0, y0, x1, y1, color);
+      }
+  }
+
+  fn put_pixel(&mut self, x: f32, y: f32, color: u32) {
+      if x >= 0.0 && y >= 0.0 && (x as usize) < self.width && (y as usize) < self.height {
+          let index = y as usize * self.width + x as usize;
+          self.buffer[index] = color;
+      }
+  }
+}
+
+fn unpack(color: u32) -> [u8; 3] {
+  [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
+
+// Suma saturada de dos colores 0xRRGGBB canal por canal
+fn add_colors(a: u32, b: u32) -> u32 {
+  let r = (((a >> 16) & 0xFF) + ((b >> 16) & 0xFF)).min(255);
+  let g = (((a >> 8) & 0xFF) + ((b >> 8) & 0xFF)).min(255);
+  let bl = ((a & 0xFF) + (b & 0xFF)).min(255);
+  (r << 16) | (g << 8) | bl
+}
+
+// Interpola de `dst` a `src` canal por canal según `alpha`
+fn mix_colors(dst: u32, src: u32, alpha: f32) -> u32 {
+  let mix = |shift: u32| {
+      let d = ((dst >> shift) & 0xFF) as f32;
+      let s = ((src >> shift) & 0xFF) as f32;
+      ((s * alpha + d * (1.0 - alpha)).round() as u32).min(255)
+  };
+  (mix(16) << 16) | (mix(8) << 8) | mix(0)
 }
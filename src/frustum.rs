@@ -0,0 +1,26 @@
+// Recorte por frustum: descarta objetos cuya esfera envolvente queda fuera del volumen de visión
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+pub struct Frustum {
+    planes: [Vec4; 6], // (normal hacia adentro, distancia), normalizados
+}
+
+impl Frustum {
+    // Extrae los seis planos de una matriz proyección * vista (método de Gribb y Hartmann)
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let row = |i: usize| -> Vec4 { view_projection.row(i).transpose() };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = Vec3::new(plane.x, plane.y, plane.z).magnitude();
+            if length > 0.0 { plane / length } else { plane }
+        });
+        Frustum { planes }
+    }
+
+    // Verdadero si alguna parte de la esfera puede estar dentro del frustum
+    pub fn sphere_visible(&self, center: Vec3, radius: f32) -> bool {
+        self.planes.iter().all(|plane| {
+            plane.x * center.x + plane.y * center.y + plane.z * center.z + plane.w >= -radius
+        })
+    }
+}
@@ -0,0 +1,170 @@
+// Generador de sistemas aleatorios (`--seed N` o la tecla R): a partir de una semilla arma un Sol y
+// entre 4 y 10 planetas con órbitas, tamaños, rotaciones y superficies propias. Todo sale de un único
+// generador pseudoaleatorio, así la misma semilla reproduce exactamente el mismo sistema
+use nalgebra_glm::Vec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::material::{ShaderKind, SurfaceParams};
+use crate::solar_system::{generate_orbit_points, CelestialBody, Ring};
+use crate::trail::OrbitTrail;
+
+const MIN_PLANETS: usize = 4;
+const MAX_PLANETS: usize = 10;
+
+// La primera órbita queda fuera del radio de colisión del Sol con este margen; entre órbitas
+// vecinas hay al menos el tamaño de los dos planetas (con sus anillos) más un hueco aleatorio
+const SUN_SCALE: f32 = 3.0;
+const SUN_COLLISION_RADIUS: f32 = 3.5;
+const FIRST_ORBIT_MARGIN: f32 = 1.0;
+const ORBIT_GAP: (f32, f32) = (1.5, 4.0);
+
+// Velocidad orbital = ORBITAL_SPEED_FACTOR / radio^1.5 (tercera ley de Kepler, ajustada a la escala del sistema fijo)
+const ORBITAL_SPEED_FACTOR: f32 = 8.0;
+
+// Sílabas para los nombres de los planetas
+const SYLLABLES: [&str; 16] = [
+    "ka", "lo", "ri", "ve", "zu", "ta", "mi", "no", "sa", "el", "dra", "xi", "or", "be", "nu", "qua",
+];
+
+pub fn generate_bodies(seed: u64) -> Vec<CelestialBody> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut bodies = vec![sun(&mut rng)];
+
+    let count = rng.gen_range(MIN_PLANETS..=MAX_PLANETS);
+    let mut previous_edge = SUN_COLLISION_RADIUS + FIRST_ORBIT_MARGIN;
+    for i in 0..count {
+        let planet = planet(&mut rng, i as f32 / (count - 1) as f32, previous_edge);
+        previous_edge = planet.orbital_radius + extent(&planet);
+        bodies.push(planet);
+    }
+    bodies
+}
+
+fn sun(rng: &mut StdRng) -> CelestialBody {
+    CelestialBody {
+        name: "Sol".to_string(),
+        position: Vec3::new(0.0, 0.0, 0.0),
+        rotation: 0.0,
+        orbital_radius: 0.0,
+        orbital_speed: 0.0,
+        orbital_phase: 0.0,
+        rotation_speed: 0.01,
+        axial_tilt: rng.gen_range(0.0..10.0_f32).to_radians(),
+        scale: SUN_SCALE,
+        shape: Vec3::new(1.0, 1.0, 1.0),
+        tidally_locked: false,
+        shader: ShaderKind::Sun,
+        orbit_points: Vec::new(),
+        collision_radius: SUN_COLLISION_RADIUS,
+        parent: None,
+        ring: None,
+        kepler: None,
+        has_tail: false,
+        mass: 300.0,
+        surface: None,
+        texture: None,
+        orbit_trail: OrbitTrail::new(),
+    }
+}
+
+// Un planeta a partir de su posición relativa en el sistema (0 = el más interno, 1 = el más externo):
+// cerca del Sol predominan los rocosos y lejos los gigantes gaseosos y los helados
+fn planet(rng: &mut StdRng, distance: f32, previous_edge: f32) -> CelestialBody {
+    let shader = if rng.gen::<f32>() > distance * 1.5 {
+        ShaderKind::Rocky
+    } else if rng.gen_bool(0.6) {
+        ShaderKind::GasGiant
+    } else {
+        ShaderKind::Icy
+    };
+
+    // (escala, densidad para la masa, probabilidad de anillo)
+    let (scale, density, ring_chance) = match shader {
+        ShaderKind::GasGiant => (rng.gen_range(1.0..1.8), 12.0, 0.5),
+        ShaderKind::Icy => (rng.gen_range(0.5..1.0), 8.0, 0.25),
+        _ => (rng.gen_range(0.3..0.8), 6.0, 0.0),
+    };
+    let ring = rng.gen_bool(ring_chance).then(|| {
+        let inner_scale = rng.gen_range(1.2..1.6);
+        Ring {
+            inner_scale,
+            outer_scale: inner_scale + rng.gen_range(0.4..1.2),
+            tilt: 0.0,
+            shader: if rng.gen_bool(0.5) { ShaderKind::Ring } else { ShaderKind::UranusRing },
+        }
+    });
+
+    // Casi siempre poco inclinados; de vez en cuando uno gira de costado como Urano
+    let axial_tilt: f32 = if rng.gen_bool(0.1) { rng.gen_range(60.0..100.0) } else { rng.gen_range(0.0..30.0) };
+
+    let mut body = CelestialBody {
+        name: name(rng),
+        position: Vec3::new(0.0, 0.0, 0.0),
+        rotation: 0.0,
+        orbital_radius: 0.0,
+        orbital_speed: 0.0,
+        orbital_phase: rng.gen_range(0.0..2.0 * PI),
+        rotation_speed: rng.gen_range(0.01..0.04) * if rng.gen_bool(0.15) { -1.0 } else { 1.0 },
+        axial_tilt: axial_tilt.to_radians(),
+        scale,
+        shape: Vec3::new(1.0, 1.0, 1.0),
+        tidally_locked: false,
+        shader,
+        orbit_points: Vec::new(),
+        collision_radius: scale * 1.2,
+        parent: None,
+        ring,
+        kepler: None,
+        has_tail: false,
+        mass: density * scale * scale * scale,
+        surface: Some(surface(rng, shader)),
+        texture: None,
+        orbit_trail: OrbitTrail::new(),
+    };
+
+    let orbital_radius = previous_edge + extent(&body) + rng.gen_range(ORBIT_GAP.0..ORBIT_GAP.1);
+    body.orbital_radius = orbital_radius;
+    body.orbital_speed = ORBITAL_SPEED_FACTOR / orbital_radius.powf(1.5);
+    body.orbit_points = generate_orbit_points(orbital_radius);
+    body.position = Vec3::new(orbital_radius * body.orbital_phase.cos(), 0.0, orbital_radius * body.orbital_phase.sin());
+    body
+}
+
+// Radio que ocupa un planeta alrededor de su órbita, contando el anillo
+fn extent(body: &CelestialBody) -> f32 {
+    let ring = body.ring.map_or(0.0, |ring| body.scale * ring.outer_scale);
+    body.collision_radius.max(ring)
+}
+
+// Paleta en HSV: un tono base con una versión oscura y un acento de tono desplazado. Los rocosos
+// son poco saturados, los gaseosos más vivos y los helados van del cian al azul casi blancos
+fn surface(rng: &mut StdRng, shader: ShaderKind) -> SurfaceParams {
+    let (hue, saturation, value, frequency) = match shader {
+        ShaderKind::GasGiant => (rng.gen_range(0.0..360.0), rng.gen_range(0.3..0.6), rng.gen_range(0.75..0.95), rng.gen_range(0.8..2.0)),
+        ShaderKind::Icy => (rng.gen_range(170.0..250.0), rng.gen_range(0.1..0.35), rng.gen_range(0.8..1.0), rng.gen_range(1.5..4.0)),
+        _ => (rng.gen_range(0.0..360.0), rng.gen_range(0.15..0.5), rng.gen_range(0.5..0.8), rng.gen_range(1.0..3.0)),
+    };
+    let accent_shift = rng.gen_range(20.0..60.0) * if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+    SurfaceParams {
+        seed: rng.gen(),
+        frequency,
+        palette: [
+            Color::from_hsv(hue, saturation * 1.2, value * 0.45),
+            Color::from_hsv(hue, saturation, value),
+            Color::from_hsv(hue + accent_shift, saturation * 1.3, (value * 1.1).min(1.0)),
+        ],
+    }
+}
+
+// Dos o tres sílabas con la primera letra en mayúscula
+fn name(rng: &mut StdRng) -> String {
+    let syllables = rng.gen_range(2..=3);
+    let name: String = (0..syllables).map(|_| SYLLABLES[rng.gen_range(0..SYLLABLES.len())]).collect();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
@@ -0,0 +1,81 @@
+// Resplandor del polvo alrededor del Sol: cada pixel suma la luz dispersada a lo largo de su rayo,
+// con una densidad que cae con el cuadrado de la distancia al Sol y hasta la profundidad guardada
+use nalgebra_glm::{Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Uniforms;
+
+// Distancia de la cámara al Sol, en radios solares, con la que el efecto es completo y con la que desaparece
+const FULL_DISTANCE: f32 = 2.0;
+const FADE_DISTANCE: f32 = 5.0;
+
+// Luz dispersada por unidad de la integral (la integral vale hasta ~PI junto al borde del Sol)
+const STRENGTH: f32 = 0.12;
+const HAZE_COLOR: [f32; 3] = [255.0, 190.0, 115.0];
+
+pub fn apply(framebuffer: &mut Framebuffer, uniforms: &Uniforms, sun_position: Vec3, sun_radius: f32) {
+    let to_camera = uniforms.camera_position - sun_position;
+    let distance = to_camera.magnitude() / sun_radius;
+    let t = ((FADE_DISTANCE - distance) / (FADE_DISTANCE - FULL_DISTANCE)).clamp(0.0, 1.0);
+    let fade = t * t * (3.0 - 2.0 * t);
+    if fade <= 0.0 {
+        return;
+    }
+
+    // La dirección del rayo es afín en (x, y) de pantalla. Con z = -1 en espacio de vista, el
+    // parámetro s del rayo ojo + s * dirección coincide con la profundidad que guarda el z-buffer
+    let direction = |x: f32, y: f32| {
+        let viewport = &uniforms.viewport_matrix;
+        let ndc_x = (x - viewport[(0, 3)]) / viewport[(0, 0)];
+        let ndc_y = (y - viewport[(1, 3)]) / viewport[(1, 1)];
+        let point = uniforms.inverse_projection * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+        let view = Vec4::new(point.x, point.y, point.z, 0.0) / -point.z;
+        let world = uniforms.inverse_view * view;
+        Vec3::new(world.x, world.y, world.z)
+    };
+    let origin = direction(0.5, 0.5);
+    let step_x = direction(1.5, 0.5) - origin;
+    let step_y = direction(0.5, 1.5) - origin;
+
+    // Con la densidad escalada por el radio solar la integral no tiene unidades
+    let scale = STRENGTH * sun_radius * fade;
+    let c = to_camera.dot(&to_camera);
+    let sun_radius_sq = sun_radius * sun_radius;
+
+    for y in 0..framebuffer.height {
+        let row = origin + step_y * y as f32;
+        for x in 0..framebuffer.width {
+            let index = y * framebuffer.width + x;
+            let ray = row + step_x * x as f32;
+            let depth = framebuffer.zbuffer[index];
+
+            // |ojo + s * rayo - sol|² = a s² + 2 b s + c
+            let a = ray.dot(&ray);
+            let b = ray.dot(&to_camera);
+            let q_sq = a * c - b * b;
+
+            // El disco del Sol ya brilla por sí mismo: ahí no se suma nada
+            if b < 0.0 && q_sq < a * sun_radius_sq {
+                let hit = (-b - (b * b - a * (c - sun_radius_sq)).max(0.0).sqrt()) / a;
+                if depth >= hit * 0.99 {
+                    continue;
+                }
+            }
+
+            let q = q_sq.max(1e-6).sqrt();
+            let end = if depth.is_finite() { ((a * depth + b) / q).atan() } else { std::f32::consts::FRAC_PI_2 };
+            let integral = a.sqrt() / q * (end - (b / q).atan());
+            let amount = (integral * scale).min(1.0);
+            if amount < 1.0 / 255.0 {
+                continue;
+            }
+
+            let pixel = framebuffer.buffer[index];
+            let mut output = 0;
+            for (channel, shift) in [16, 8, 0].into_iter().enumerate() {
+                let value = ((pixel >> shift) & 0xFF) as f32 + HAZE_COLOR[channel] * amount;
+                output |= (value as u32).min(255) << shift;
+            }
+            framebuffer.buffer[index] = output;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// Modo sin ventana: simula con un paso de tiempo fijo, mueve la cámara por una trayectoria
+// y guarda cada frame como PNG numerado (útil para videos y para CI)
+use minifb::Key;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::bookmarks::Bookmarks;
+use crate::camera::Camera;
+use crate::png::write_png;
+use crate::session::Session;
+use crate::renderer::{advance_clock, Renderer};
+use crate::solar_system::SolarSystem;
+use crate::{handle_input, handle_view_input, simulation_input};
+
+// Paso de simulación por frame exportado (60 FPS) si no se fija otro con `--fixed-dt`
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+// Puntos de control de la trayectoria por defecto: una vuelta completa alrededor del Sol
+const DEFAULT_PATH_KEYFRAMES: usize = 64;
+
+pub struct HeadlessOptions {
+    pub frames: usize,
+    pub output: String,
+    pub camera_path: Option<String>,
+    pub replay: Option<String>,   // Sesión grabada; reemplaza a la trayectoria y fija la cantidad de frames
+    pub timestep: f32,            // Segundos de simulación por frame
+}
+
+#[derive(Debug)]
+pub enum PathError {
+    Io(String, std::io::Error),
+    Parse { path: String, line: usize, text: String },
+    Empty(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Io(path, err) => write!(f, "no se pudo leer la trayectoria '{}': {}", path, err),
+            PathError::Parse { path, line, text } => {
+                write!(f, "{}:{}: se esperaban 6 números (ojo x y z, objetivo x y z): '{}'", path, line, text)
+            }
+            PathError::Empty(path) => write!(f, "la trayectoria '{}' no tiene puntos de control", path),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+// Trayectoria de la cámara: poses (ojo, objetivo) repartidas uniformemente entre el primer y el último frame
+pub struct CameraPath {
+    keyframes: Vec<(Vec3, Vec3)>,
+}
+
+impl CameraPath {
+    // Vuelta alrededor del origen a la altura y distancia de `eye`, empezando en `eye`
+    pub fn orbit(eye: Vec3) -> Self {
+        let radius = (eye.x * eye.x + eye.z * eye.z).sqrt();
+        let start = eye.z.atan2(eye.x);
+        let keyframes = (0..=DEFAULT_PATH_KEYFRAMES)
+            .map(|i| {
+                let angle = start + i as f32 / DEFAULT_PATH_KEYFRAMES as f32 * 2.0 * PI;
+                (Vec3::new(radius * angle.cos(), eye.y, radius * angle.sin()), Vec3::new(0.0, 0.0, 0.0))
+            })
+            .collect();
+        CameraPath { keyframes }
+    }
+
+    // Archivo de texto con una pose por línea: "ojo_x ojo_y ojo_z objetivo_x objetivo_y objetivo_z";
+    // las líneas vacías y las que empiezan con '#' se ignoran
+    pub fn load(path: &str) -> Result<Self, PathError> {
+        let contents = fs::read_to_string(path).map_err(|err| PathError::Io(path.to_string(), err))?;
+        let mut keyframes = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let values: Vec<f32> = text.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            if values.len() != 6 || text.split_whitespace().count() != 6 {
+                return Err(PathError::Parse { path: path.to_string(), line: index + 1, text: text.to_string() });
+            }
+            keyframes.push((Vec3::new(values[0], values[1], values[2]), Vec3::new(values[3], values[4], values[5])));
+        }
+        if keyframes.is_empty() {
+            return Err(PathError::Empty(path.to_string()));
+        }
+        Ok(CameraPath { keyframes })
+    }
+
+    // Pose interpolada linealmente para el frame `frame` de un total de `frames`
+    pub fn pose(&self, frame: usize, frames: usize) -> (Vec3, Vec3) {
+        let last = self.keyframes.len() - 1;
+        if last == 0 || frames <= 1 {
+            return self.keyframes[0];
+        }
+        let position = frame as f32 / (frames - 1) as f32 * last as f32;
+        let index = (position.floor() as usize).min(last - 1);
+        let t = position - index as f32;
+        let (eye_a, target_a) = self.keyframes[index];
+        let (eye_b, target_b) = self.keyframes[index + 1];
+        (eye_a.lerp(&eye_b, t), target_a.lerp(&target_b, t))
+    }
+}
+
+pub fn run(
+    options: &HeadlessOptions,
+    renderer: &mut Renderer,
+    solar_system: &mut SolarSystem,
+    camera: &mut Camera,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let replay = options.replay.as_deref().map(Session::load).transpose()?;
+    let path = match (&replay, &options.camera_path) {
+        (Some(_), _) => None,
+        (None, Some(file)) => Some(CameraPath::load(file)?),
+        (None, None) => Some(CameraPath::orbit(camera.eye)),
+    };
+    let frames = replay.as_ref().map_or(options.frames, |session| session.frames().len());
+    let output = Path::new(&options.output);
+    fs::create_dir_all(output).map_err(|err| format!("no se pudo crear '{}': {}", options.output, err))?;
+
+    let mut clock = 0.0;
+    let mut bookmarks = Bookmarks::load().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        Bookmarks::empty()
+    });
+
+    for frame in 0..frames {
+        let timestep = match &replay {
+            Some(session) => session.frames()[frame].timestep,
+            None if frame == 0 => 0.0,
+            None => options.timestep,
+        };
+        if let Some(session) = &replay {
+            // Mismo orden que el bucle con ventana: simulación, teclas y al final la pose grabada
+            let recorded = &session.frames()[frame];
+            solar_system.update(timestep, camera);
+            handle_input(&simulation_input(&recorded.input, renderer), camera, solar_system, &mut bookmarks);
+            handle_view_input(&recorded.input, renderer, solar_system);
+            renderer.settings.update_fov(recorded.input.is_key_down(Key::C), timestep);
+            recorded.apply_camera(camera);
+            solar_system.selected_body = recorded.selected_body;
+        } else if let Some(path) = &path {
+            // La pose de la trayectoria se impone después de la simulación, igual que lo haría la entrada del usuario
+            solar_system.update(timestep, camera);
+            let (eye, target) = path.pose(frame, frames);
+            camera.look_at(eye, target);
+        }
+
+        solar_system.update_crosshair(timestep, camera);
+        clock = advance_clock(clock, solar_system.simulation_delta(timestep));
+        let image = renderer.render_scene(solar_system, camera, clock, timestep);
+
+        let file = output.join(format!("frame_{:05}.png", frame));
+        write_png(&file, image.width, image.height, &image.buffer)
+            .map_err(|err| format!("no se pudo escribir '{}': {}", file.display(), err))?;
+        renderer.stats.end_frame();
+    }
+
+    println!("{} frames escritos en {}", frames, output.display());
+    Ok(())
+}
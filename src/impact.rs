@@ -0,0 +1,65 @@
+// Choques de la nave contra un cuerpo: por encima de IMPACT_SPEED la nave rebota, un destello
+// cubre la pantalla y la cámara se sacude un momento. Todo depende solo del tiempo desde el choque,
+// así que una sesión reproducida da los mismos frames
+use nalgebra_glm::{Vec2, Vec3};
+
+// Velocidad contra la superficie (unidades por segundo) a partir de la cual hay impacto, y la que da
+// el efecto completo. Los roces lentos solo frenan la nave, como antes
+pub const IMPACT_SPEED: f32 = 1.0;
+const FULL_IMPACT_SPEED: f32 = 5.0;
+const MIN_STRENGTH: f32 = 0.3;
+
+// Duraciones en segundos reales; mientras dura la sacudida no se dispara otro impacto
+const FLASH_DURATION: f32 = 0.3;
+const SHAKE_DURATION: f32 = 0.5;
+
+// Sacudida máxima: desplazamiento del ojo (unidades) y giro de la mirada (radianes)
+const SHAKE_EYE: f32 = 0.04;
+const SHAKE_LOOK: f32 = 0.015;
+
+// Frecuencias (Hz) de las oscilaciones que forman la sacudida; sin múltiplos comunes para que no
+// se note el patrón
+const SHAKE_FREQUENCIES: [f32; 4] = [11.0, 13.7, 9.3, 15.1];
+
+pub struct Impact {
+    pub point: Vec3,      // Punto de contacto en el mundo, de donde sale el destello
+    strength: f32,        // En [MIN_STRENGTH, 1], según la velocidad del choque
+    age: f32,             // Segundos desde el choque
+    phases: [f32; 4],     // Desfases de la sacudida, distintos en cada choque
+}
+
+impl Impact {
+    // Choque en `point` a `speed` unidades por segundo contra la superficie
+    pub fn new(point: Vec3, speed: f32) -> Self {
+        let strength = (speed / FULL_IMPACT_SPEED).clamp(MIN_STRENGTH, 1.0);
+        let seed = (point.x * 12.9898 + point.y * 78.233 + point.z * 37.719).sin() * 43758.545;
+        let phases = [1.0, 2.0, 3.0, 4.0].map(|i: f32| (seed * i).fract() * std::f32::consts::TAU);
+        Impact { point, strength, age: 0.0, phases }
+    }
+
+    // Avanza el tiempo del impacto; devuelve false cuando ya no queda nada que mostrar
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.age += delta_time;
+        self.age < SHAKE_DURATION
+    }
+
+    // Intensidad del destello en [0, 1]: arranca al máximo y se apaga en FLASH_DURATION
+    pub fn flash(&self) -> f32 {
+        let remaining = (1.0 - self.age / FLASH_DURATION).max(0.0);
+        remaining * remaining * self.strength
+    }
+
+    // Sacudida de la cámara en este momento: desplazamiento del ojo y giro de la mirada, ambos sobre
+    // los ejes derecha y arriba de la cámara. Se aplica solo al armar la matriz de vista
+    pub fn shake(&self) -> Option<(Vec2, Vec2)> {
+        let remaining = 1.0 - self.age / SHAKE_DURATION;
+        if remaining <= 0.0 {
+            return None;
+        }
+        let amplitude = remaining * remaining * self.strength;
+        let wave = |i: usize| (self.age * SHAKE_FREQUENCIES[i] * std::f32::consts::TAU + self.phases[i]).sin();
+        let eye = Vec2::new(wave(0), wave(1)) * SHAKE_EYE * amplitude;
+        let look = Vec2::new(wave(2), wave(3)) * SHAKE_LOOK * amplitude;
+        Some((eye, look))
+    }
+}
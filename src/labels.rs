@@ -0,0 +1,55 @@
+// Etiquetas con el nombre de cada cuerpo, ancladas en el mundo justo encima de él y dibujadas
+// en pantalla con la fuente bitmap (siempre de frente a la cámara)
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::solar_system::SolarSystem;
+use crate::text::{draw_text_blended, text_width, GLYPH_HEIGHT};
+use crate::renderer::{projected_radius, world_to_screen, Uniforms};
+
+// Altura del ancla sobre el centro del cuerpo, en múltiplos de su escala, en la dirección "arriba" de la cámara
+const ANCHOR_HEIGHT: f32 = 1.3;
+
+// Radio en pantalla (pixeles) con el que la etiqueta desaparece y con el que ya se ve completa
+const FADE_OUT_RADIUS: f32 = 1.0;
+const FADE_IN_RADIUS: f32 = 3.0;
+
+// Algo más cerca que el ancla por más de esta fracción de su profundidad la tapa
+const OCCLUSION_BIAS: f32 = 0.01;
+
+const LABEL_COLOR: u32 = 0xE0E0E0;
+const LABEL_SCALE: usize = 1;
+const LABEL_GAP: usize = 2; // Separación entre el ancla y la base del texto
+
+pub fn draw(framebuffer: &mut Framebuffer, solar_system: &SolarSystem, uniforms: &Uniforms) {
+    // La segunda fila de la matriz de vista es el eje "arriba" de la cámara en coordenadas del mundo
+    let view = &uniforms.view_matrix;
+    let camera_up = Vec3::new(view[(1, 0)], view[(1, 1)], view[(1, 2)]);
+
+    for body in &solar_system.bodies {
+        let radius_px = projected_radius(body.position, body.scale, uniforms, framebuffer.height);
+        let opacity = ((radius_px - FADE_OUT_RADIUS) / (FADE_IN_RADIUS - FADE_OUT_RADIUS)).clamp(0.0, 1.0);
+        if opacity <= 0.0 {
+            continue;
+        }
+
+        let anchor = body.position + camera_up * (body.scale * ANCHOR_HEIGHT);
+        let screen = match world_to_screen(anchor, uniforms) {
+            Some(screen) => screen,
+            None => continue,
+        };
+        if screen.x < 0.0 || screen.y < 0.0 || screen.x >= framebuffer.width as f32 || screen.y >= framebuffer.height as f32 {
+            continue;
+        }
+
+        let index = screen.y as usize * framebuffer.width + screen.x as usize;
+        if framebuffer.zbuffer[index] < screen.z * (1.0 - OCCLUSION_BIAS) {
+            continue;
+        }
+
+        // Centrada sobre el ancla; cerca de los bordes se corre para no salirse de la pantalla
+        let width = text_width(&body.name, LABEL_SCALE);
+        let x = (screen.x as usize).saturating_sub(width / 2).min(framebuffer.width.saturating_sub(width));
+        let y = (screen.y as usize).saturating_sub(GLYPH_HEIGHT * LABEL_SCALE + LABEL_GAP);
+        draw_text_blended(framebuffer, x, y, &body.name, LABEL_COLOR, LABEL_SCALE, opacity);
+    }
+}
@@ -0,0 +1,93 @@
+// Destello de lente del Sol: un halo, una estela horizontal y "fantasmas" a lo largo de la línea
+// que une al Sol con el centro de la pantalla. Se suma sobre la imagen ya tonemapeada
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+
+// Fantasmas: posición sobre la línea Sol -> centro (0 = Sol, 1 = centro), radio relativo
+// al lado menor de la pantalla, color e intensidad
+const GHOSTS: [(f32, f32, [f32; 3], f32); 5] = [
+    (0.4, 0.035, [1.0, 0.6, 0.3], 0.25),
+    (0.7, 0.02, [0.5, 0.8, 1.0], 0.3),
+    (1.2, 0.06, [0.4, 1.0, 0.6], 0.15),
+    (1.6, 0.025, [1.0, 0.5, 0.8], 0.25),
+    (2.0, 0.09, [0.6, 0.7, 1.0], 0.12),
+];
+
+const HALO_RADIUS: f32 = 0.15;
+const HALO_COLOR: [f32; 3] = [1.0, 0.85, 0.6];
+const STREAK_LENGTH: f32 = 0.6;  // Semilongitud relativa al ancho de la pantalla
+const STREAK_THICKNESS: f32 = 2.5;
+const STREAK_COLOR: [f32; 3] = [1.0, 0.9, 0.75];
+
+// `sun` es la posición del Sol en pantalla y `visibility` la fracción de su disco que se ve
+// (ver `post::sun_visibility`); todo el destello se atenúa con ella
+pub fn draw(framebuffer: &mut Framebuffer, sun: Vec3, visibility: f32) {
+    if sun.z <= 0.0 || visibility <= 0.0 {
+        return;
+    }
+
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let center = (width / 2.0, height / 2.0);
+    let short_side = width.min(height);
+
+    add_soft_disc(framebuffer, sun.x, sun.y, HALO_RADIUS * short_side, HALO_COLOR, 0.35 * visibility);
+    add_streak(framebuffer, sun.x, sun.y, STREAK_LENGTH * width, 0.4 * visibility);
+
+    for (along, size, color, intensity) in GHOSTS {
+        let x = sun.x + (center.0 - sun.x) * along;
+        let y = sun.y + (center.1 - sun.y) * along;
+        add_soft_disc(framebuffer, x, y, size * short_side, color, intensity * visibility);
+    }
+}
+
+// Disco aditivo con caída cuadrática hacia el borde
+fn add_soft_disc(framebuffer: &mut Framebuffer, cx: f32, cy: f32, radius: f32, color: [f32; 3], intensity: f32) {
+    if radius < 0.5 || intensity <= 0.0 {
+        return;
+    }
+    let min_x = (cx - radius).floor().max(0.0) as usize;
+    let min_y = (cy - radius).floor().max(0.0) as usize;
+    let max_x = ((cx + radius).ceil().max(0.0) as usize).min(framebuffer.width);
+    let max_y = ((cy + radius).ceil().max(0.0) as usize).min(framebuffer.height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            let falloff = 1.0 - (dx * dx + dy * dy).sqrt() / radius;
+            if falloff > 0.0 {
+                add_light(framebuffer, x, y, color, intensity * falloff * falloff);
+            }
+        }
+    }
+}
+
+// Estela horizontal fina que se desvanece hacia los extremos y hacia arriba y abajo
+fn add_streak(framebuffer: &mut Framebuffer, cx: f32, cy: f32, half_length: f32, intensity: f32) {
+    let rows = (STREAK_THICKNESS * 3.0).ceil();
+    let min_y = (cy - rows).floor().max(0.0) as usize;
+    let max_y = ((cy + rows).ceil().max(0.0) as usize).min(framebuffer.height);
+    let min_x = (cx - half_length).floor().max(0.0) as usize;
+    let max_x = ((cx + half_length).ceil().max(0.0) as usize).min(framebuffer.width);
+
+    for y in min_y..max_y {
+        let dy = (y as f32 + 0.5 - cy) / STREAK_THICKNESS;
+        let vertical = (-dy * dy).exp();
+        for x in min_x..max_x {
+            let along = 1.0 - (x as f32 + 0.5 - cx).abs() / half_length;
+            if along > 0.0 {
+                add_light(framebuffer, x, y, STREAK_COLOR, intensity * vertical * along * along);
+            }
+        }
+    }
+}
+
+fn add_light(framebuffer: &mut Framebuffer, x: usize, y: usize, color: [f32; 3], amount: f32) {
+    let index = y * framebuffer.width + x;
+    let pixel = framebuffer.buffer[index];
+    let channel = |shift: u32, value: f32| {
+        let base = ((pixel >> shift) & 0xFF) as f32;
+        ((base + value * amount * 255.0).round() as u32).min(255)
+    };
+    framebuffer.buffer[index] = (channel(16, color[0]) << 16) | (channel(8, color[1]) << 8) | channel(0, color[2]);
+}
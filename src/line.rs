@@ -0,0 +1,61 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+
+// Rasteriza un segmento en coordenadas de pantalla (DDA) interpolando la profundidad,
+// de modo que cada pixel pasa por la prueba del z-buffer del framebuffer.
+// z es la profundidad en espacio de vista; se interpola 1/z, que es lineal en pantalla
+pub fn line(framebuffer: &mut Framebuffer, start: Vec3, end: Vec3) {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let steps = dx.abs().max(dy.abs()).ceil() as usize;
+
+    if steps == 0 {
+        plot(framebuffer, start.x, start.y, start.z);
+        return;
+    }
+
+    // Segmentos enormes vienen de puntos casi en el plano cercano; se limitan para no recorrer millones de pixeles
+    let max_steps = (framebuffer.width + framebuffer.height) * 4;
+    if steps > max_steps {
+        return;
+    }
+
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let point = start + (end - start) * t;
+        let depth = 1.0 / (1.0 / start.z + (1.0 / end.z - 1.0 / start.z) * t);
+        plot(framebuffer, point.x, point.y, depth);
+    }
+}
+
+fn plot(framebuffer: &mut Framebuffer, x: f32, y: f32, depth: f32) {
+    if x >= 0.0 && y >= 0.0 {
+        framebuffer.point(x as usize, y as usize, depth, 0);
+    }
+}
+
+// Como `line`, pero translúcido: la opacidad va de `start_alpha` a `end_alpha` a lo largo del segmento
+// y cada pixel además emite `emission` con esa opacidad, así el segmento brilla con el bloom.
+// Se mezcla con lo que hay detrás sin escribir la profundidad
+pub fn faded_line(framebuffer: &mut Framebuffer, start: Vec3, end: Vec3, color: u32, emission: u32, start_alpha: f32, end_alpha: f32) {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let steps = dx.abs().max(dy.abs()).ceil() as usize;
+    let max_steps = (framebuffer.width + framebuffer.height) * 4;
+    if steps > max_steps {
+        return;
+    }
+
+    for i in 0..=steps {
+        let t = if steps == 0 { 0.0 } else { i as f32 / steps as f32 };
+        let alpha = start_alpha + (end_alpha - start_alpha) * t;
+        let point = start + (end - start) * t;
+        if alpha <= 0.0 || point.x < 0.0 || point.y < 0.0 {
+            continue;
+        }
+        let depth = 1.0 / (1.0 / start.z + (1.0 / end.z - 1.0 / start.z) * t);
+        // Con opacidad 1 el framebuffer escribiría la profundidad; se deja apenas por debajo
+        framebuffer.set_current_color_alpha(color, alpha.min(0.999));
+        framebuffer.point(point.x as usize, point.y as usize, depth, emission);
+    }
+}
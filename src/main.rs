@@ -1,9 +1,11 @@
-use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
-use std::{f32::consts::PI, time::Instant};
+use nalgebra_glm::Vec3;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::time::Instant;
 
 mod framebuffer;
+mod generator;
 mod triangle;
+mod line;
 mod vertex;
 mod obj;
 mod color;
@@ -11,510 +13,541 @@ mod fragment;
 mod shaders;
 mod camera;
 mod solar_system;
+mod scene;
+mod text;
+mod stats;
+mod material;
+mod spaceship;
+mod starfield;
+mod png;
+mod headless;
+mod lens_flare;
+mod frustum;
+mod particles;
+mod shadows;
+mod debug_view;
+mod bookmarks;
+mod labels;
+mod minimap;
+mod session;
+mod haze;
+mod post;
+mod render_queue;
+mod bench;
+mod trail;
+mod renderer;
+mod surface_texture;
+mod tuning;
+mod targeting;
+mod snapshot;
+mod viewport;
+mod impact;
+mod texture;
 
 use solar_system::SolarSystem;
-use framebuffer::Framebuffer;
-use vertex::Vertex;
-use obj::Obj;
 use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader};
-use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
-
-pub struct Uniforms {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: FastNoiseLite,
-    cloud_noise: FastNoiseLite, 
-    band_noise: FastNoiseLite, 
-    current_shader: u8,
-}
-
-fn create_noise(current_shader: u8) -> FastNoiseLite {
-    match current_shader {
-        1 => create_earth_noise(),
-        2 => create_mars_noise(),
-        3 => create_mercury_noise(),
-        4 => FastNoiseLite::new(),
-        5 => create_jupiter_noise(),
-        6 => create_urano_noise(), 
-        8 => create_moon_noise(),
-        9 => FastNoiseLite::new(),
-        _ => create_earth_noise(),  
-    }
-}
-
-fn create_earth_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2S));
-    noise.set_fractal_type(Some(FractalType::Ridged));
-    noise.set_fractal_octaves(Some(5));
-    noise.set_fractal_lacunarity(Some(3.0));
-    noise.set_fractal_gain(Some(0.5));
-    noise.set_frequency(Some(0.5)); 
-    noise
-}
+use stats::Stage;
+use bookmarks::Bookmarks;
+use headless::HeadlessOptions;
+use obj::NormalMode;
+use session::{InputState, Session, SessionFrame, SessionRecorder};
+use renderer::{advance_clock, Assets, Renderer, MAX_EXPOSURE, MIN_EXPOSURE, RENDER_SCALES};
+use surface_texture::DEFAULT_BAKE_RESOLUTION;
 
-fn create_cloud_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(40);  
-    noise.set_noise_type(Some(NoiseType::Perlin)); 
-    noise.set_fractal_type(Some(FractalType::FBm));
-    noise.set_fractal_octaves(Some(2));
-    noise.set_fractal_lacunarity(Some(3.0));
-    noise.set_fractal_gain(Some(0.5));
-    noise.set_frequency(Some(0.01));
-    noise
-}
-
-fn create_mars_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1234);
-    noise.set_noise_type(Some(NoiseType::Perlin));
-    noise.set_fractal_type(Some(FractalType::Ridged));
-    noise.set_fractal_octaves(Some(4));
-    noise.set_fractal_lacunarity(Some(2.0));
-    noise.set_fractal_gain(Some(0.5));
-    noise.set_frequency(Some(1.5)); 
-    noise
-}
+const WINDOW_TITLE: &str = "Rust Graphics - Solar System - Use WASD to move, Mouse to look";
 
-fn create_moon_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(4321);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise.set_fractal_type(Some(FractalType::PingPong));
-    noise.set_fractal_octaves(Some(2));
-    noise.set_fractal_lacunarity(Some(2.0));
-    noise.set_fractal_gain(Some(0.5));
-    noise.set_frequency(Some(3.0));  
-    noise
-}
+// Grados por paso de la rueda al cambiar el campo de visión con Ctrl
+const FOV_SCROLL_STEP: f32 = 2.0;
 
-fn create_mercury_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(4321);
-    noise.set_noise_type(Some(NoiseType::Perlin));
-    noise.set_fractal_type(Some(FractalType::PingPong));
-    noise.set_fractal_octaves(Some(5));
-    noise.set_fractal_lacunarity(Some(2.0));
-    noise.set_fractal_gain(Some(1.0));
-    noise.set_frequency(Some(5.0));  
-    noise
-}
+// Factor por pulsación de coma y punto sobre la distancia de enfoque de la profundidad de campo
+const FOCUS_NUDGE_STEP: f32 = 1.1;
+const MIN_FOCUS_NUDGE: f32 = 0.1;
+const MAX_FOCUS_NUDGE: f32 = 10.0;
 
-fn create_jupiter_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(5678);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise.set_fractal_type(Some(FractalType::DomainWarpProgressive));
-    noise.set_fractal_octaves(Some(6));
-    noise.set_fractal_lacunarity(Some(2.0));
-    noise.set_fractal_gain(Some(0.5));
-    noise.set_frequency(Some(2.0));
-    noise
-}
-
-fn create_jupiter_band_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(7890);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise.set_frequency(Some(1.0));
-    noise.set_fractal_type(Some(FractalType::FBm));
-    noise
-}
-
-fn create_urano_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(2021);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise.set_fractal_type(Some(FractalType::Ridged));
-    noise.set_fractal_octaves(Some(4));
-    noise.set_fractal_lacunarity(Some(2.0));
-    noise.set_fractal_gain(Some(0.4));
-    noise.set_frequency(Some(0.2));
-    noise
-}
-
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
-
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
-
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
-
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
-    );
-
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let system_radius = 20.0;
+    let camera_distance = system_radius * 2.5;
+    let camera_height = system_radius * 1.0;
+    
+    let window_width = 680;
+    let window_height = 800;
 
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
+    // Inicializar la cámara en una posición elevada y alejada
+    let mut camera = Camera::new(
+        Vec3::new(camera_distance, camera_height, camera_distance),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
     );
 
-    transform_matrix * rotation_matrix
-}
-
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
-
-fn create_perspective_matrix(camera_distance: f32, window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 60.0 * PI / 180.0;
-    let aspect_ratio = window_width as f32 / window_height as f32;
-    let near = 0.1;
-    let far = camera_distance * 3.0;
-    perspective(fov, aspect_ratio, near, far)
-}
-
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0
-    )
-}
-
-fn gaussian_blur(buffer: &mut [u32], width: usize, height: usize, kernel_size: usize, sigma: f32) {
-    let gaussian_kernel = create_gaussian_kernel(kernel_size, sigma);
-    let kernel_sum: f32 = gaussian_kernel.iter().map(|&x| x as f32).sum();
-
-    for y in 0..height {
-        let mut temp_row = vec![0u32; width];
-        for x in 0..width {
-            let mut filtered_pixel = 0f32;
-            for k in 0..gaussian_kernel.len() {
-                let sample_x = x as i32 + k as i32 - (gaussian_kernel.len() / 2) as i32;
-                if sample_x >= 0 && sample_x < width as i32 {
-                    filtered_pixel += buffer[sample_x as usize + y * width] as f32 * gaussian_kernel[k] as f32;
-                }
-            }
-            temp_row[x] = (filtered_pixel / kernel_sum).round() as u32;
-        }
-        buffer[y * width..(y + 1) * width].copy_from_slice(&temp_row);
-    }
-
-    for x in 0..width {
-        let mut temp_col = vec![0u32; height];
-        for y in 0..height {
-            let mut filtered_pixel = 0f32;
-            for k in 0..gaussian_kernel.len() {
-                let sample_y = y as i32 + k as i32 - (gaussian_kernel.len() / 2) as i32;
-                if sample_y >= 0 && sample_y < height as i32 {
-                    filtered_pixel += buffer[x + sample_y as usize * width] as f32 * gaussian_kernel[k] as f32;
-                }
-            }
-            temp_col[y] = (filtered_pixel / kernel_sum).round() as u32;
-        }
-        for y in 0..height {
-            buffer[x + y * width] = temp_col[y];
-        }
-    }
-}
-
-fn create_gaussian_kernel(size: usize, sigma: f32) -> Vec<u32> {
-    let mut kernel = vec![0u32; size];
-    let mean = (size as f32 - 1.0) / 2.0;
-    let coefficient = 1.0 / (2.0 * std::f32::consts::PI * sigma * sigma).sqrt();
-
-    for x in 0..size {
-        let exp_numerator = -((x as f32 - mean) * (x as f32 - mean)) / (2.0 * sigma * sigma);
-        let exp_value = (-exp_numerator).exp();
-        kernel[x] = (coefficient * exp_value * 255.0) as u32;
-    }
-
-    kernel
-}
-
-fn apply_bloom(original: &mut [u32], bloom: &[u32], width: usize, height: usize) {
-    for i in 0..original.len() {
-        let original_color = original[i];
-        let bloom_intensity = bloom[i];
-        if bloom_intensity > 0 {
-            original[i] = blend_bloom(original_color, bloom_intensity);
-        }
-    }
-}
-
-fn blend_bloom(base_color: u32, bloom_intensity: u32) -> u32 {
-    let bloom_strength = 0.8;
-    let max_bloom_effect = 1.2;
-
-    let r = ((base_color >> 16) & 0xFF) as f32;
-    let g = ((base_color >> 8) & 0xFF) as f32;
-    let b = (base_color & 0xFF) as f32;
-    let bloom = bloom_intensity as f32 * bloom_strength;
-
-    let new_r = ((r + bloom).min(255.0 * max_bloom_effect)).min(255.0) as u32;
-    let new_g = ((g + bloom).min(255.0 * max_bloom_effect)).min(255.0) as u32;
-    let new_b = ((b + bloom).min(255.0 * max_bloom_effect)).min(255.0) as u32;
-
-    (new_r << 16) | (new_g << 8) | new_b
-}
+    // Esferas procedurales por defecto; `--sphere-obj` usa el modelo sphere.obj en un único nivel.
+    // `--bake-resolution N` fija el ancho de las texturas de superficie horneadas (0 usa el ruido en vivo).
+    // `--flat-normals` genera por cara, y no suavizadas, las normales que les falten a los modelos
+    let sphere_obj = args.iter().any(|arg| arg == "--sphere-obj");
+    let normal_mode = if args.iter().any(|arg| arg == "--flat-normals") { NormalMode::Flat } else { NormalMode::Smooth };
+    let bake_resolution = arg_value(&args, "--bake-resolution").map(|value| value.parse::<usize>().unwrap_or_else(|_| {
+        eprintln!("resolución de horneado inválida '{}': debe ser un entero sin signo", value);
+        std::process::exit(1);
+    }));
+    let mut renderer = if sphere_obj || bake_resolution.is_some() || normal_mode == NormalMode::Flat {
+        let assets = Assets::load(sphere_obj, bake_resolution.unwrap_or(DEFAULT_BAKE_RESOLUTION), normal_mode);
+        Renderer::with_assets(window_width, window_height, assets)
+    } else {
+        Renderer::new(window_width, window_height)
+    };
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], time: u32) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+    // `--scene archivo.toml` carga una escena y `--seed N` genera un sistema aleatorio
+    let seed = arg_value(&args, "--seed").map(|value| value.parse::<u64>().unwrap_or_else(|_| {
+        eprintln!("semilla inválida '{}': debe ser un entero sin signo", value);
+        std::process::exit(1);
+    }));
+    let mut solar_system = match (arg_value(&args, "--scene"), seed) {
+        (Some(path), _) => SolarSystem::from_file(&path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }),
+        (None, Some(seed)) => SolarSystem::generate(seed),
+        (None, None) => SolarSystem::new(),
+    };
+    solar_system.fit_collision_radii(renderer.mesh_radii());
+
+    // `--sky-clear` no limpia el color de la escena: el fondo de estrellas lo cubre después de lo opaco
+    renderer.settings.sky_clear = args.iter().any(|arg| arg == "--sky-clear");
+
+    // `--fixed-dt <ms>` avanza siempre el mismo paso de tiempo por frame en lugar del reloj real, para
+    // pruebas deterministas (con ventana la simulación va más lenta o más rápida que el tiempo real)
+    let fixed_dt = arg_value(&args, "--fixed-dt").map(|value| match value.parse::<f32>() {
+        Ok(ms) if ms > 0.0 && ms.is_finite() => ms / 1000.0,
+        _ => {
+            eprintln!("paso de tiempo inválido '{}': deben ser milisegundos mayores que cero", value);
+            std::process::exit(1);
         }
-    }
+    });
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+    // `--bench` recorre una trayectoria fija sin ventana e imprime los tiempos en JSON
+    if args.iter().any(|arg| arg == "--bench") {
+        bench::run(&mut renderer, &mut solar_system, &mut camera);
+        return;
     }
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            let (shaded_color, emission) = fragment_shader(&fragment, &uniforms, time);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth, emission);
+    // `--headless --frames N --output dir/ [--camera-path archivo | --replay sesion.bin]` renderiza a PNG sin abrir ventana
+    if args.iter().any(|arg| arg == "--headless") {
+        let options = HeadlessOptions {
+            frames: arg_value(&args, "--frames").and_then(|value| value.parse().ok()).unwrap_or(120),
+            output: arg_value(&args, "--output").unwrap_or_else(|| "frames".to_string()),
+            camera_path: arg_value(&args, "--camera-path"),
+            replay: arg_value(&args, "--replay"),
+            timestep: fixed_dt.unwrap_or(headless::TIMESTEP),
+        };
+        if let Err(err) = headless::run(&options, &mut renderer, &mut solar_system, &mut camera) {
+            eprintln!("{}", err);
+            std::process::exit(1);
         }
+        return;
     }
-}
-
-fn world_to_screen(point: Vec3, uniforms: &Uniforms) -> Vec3 {
-    let pos = Vec4::new(point.x, point.y, point.z, 1.0);
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * pos;
-    let w = transformed[3];
-    let ndc = Vec4::new(
-        transformed[0] / w,
-        transformed[1] / w,
-        transformed[2] / w,
-        1.0
-    );
-    let screen = uniforms.viewport_matrix * ndc;
-    Vec3::new(screen[0], screen[1], screen[2])
-}
-
-fn main() {
-    let mut last_bloom_update = 0;
-    let bloom_update_interval = 5;
-    let system_radius = 20.0;
-    let camera_distance = system_radius * 2.5;
-    let camera_height = system_radius * 1.0;
-    
-    let window_width = 680;
-    let window_height = 800;
-    let framebuffer_width = window_width; 
-    let framebuffer_height = window_height;
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
     let mut window = Window::new(
-        "Rust Graphics - Solar System - Use WASD to move, Mouse to look",
+        WINDOW_TITLE,
         window_width,
         window_height,
-        WindowOptions::default(),
+        WindowOptions {
+            resize: true,
+            ..WindowOptions::default()
+        },
     )
         .unwrap();
 
     window.set_position(500, 500);
     window.update();
 
-    framebuffer.set_background_color(0x000000); // Fondo negro para el espacio
-
-    // Inicializar la cámara en una posición elevada y alejada
-    let mut camera = Camera::new(
-        Vec3::new(camera_distance, camera_height, camera_distance),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
-    );
-
-    let obj = Obj::load("assets/models/sphere.obj").expect("Failed to load obj");
-    let moon = Obj::load("assets/models/moon.obj").expect("Failed to load obj");
-    let ring_obj = Obj::load("assets/models/ring.obj").expect("Failed to load ring model");
-    let spaceship = Obj::load("assets/models/Navesita.obj").expect("Failed to load spaceship");
-
-    let vertex_arrays = obj.get_vertex_array(); 
-    let ring_vertex_array = ring_obj.get_vertex_array();
-    let spaceship_vertex_array = spaceship.get_vertex_array();
-
     let mut last_frame_time = Instant::now();
-    let mut time = 0;
-
-    let projection_matrix = create_perspective_matrix(camera_distance, window_width as f32, window_height as f32);
-    let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-    
-    let mut uniforms = Uniforms { 
-        model_matrix: Mat4::identity(), 
-        view_matrix: Mat4::identity(), 
-        projection_matrix, 
-        viewport_matrix, 
-        time: 0, 
-        noise: create_noise(1),
-        cloud_noise: create_cloud_noise(),
-        band_noise: create_jupiter_band_noise(), 
-        current_shader: 1,
-    };
-
-    let mut solar_system = SolarSystem::new();
 
     // Tracking del mouse
     let mut last_mouse_pos: Option<(f32, f32)> = None;
-    window.set_cursor_visibility(false);
+    let mut mouse_capture = true;    // Lo que eligió el usuario con Tab
+    let mut mouse_captured = false;  // Si el mouse gira la cámara en este momento (además requiere el foco)
+    let mut last_title = String::new();
+    let mut clock = 0.0;
+    let mut left_was_down = false;
+    let mut last_click: Option<(Instant, usize)> = None;
+    let mut bookmarks = Bookmarks::load().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        Bookmarks::empty()
+    });
+
+    // `--record sesion.bin` graba la entrada de cada frame; `--replay sesion.bin` la reproduce en lugar de la real
+    let mut recorder = arg_value(&args, "--record").map(|path| SessionRecorder::create(&path).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }));
+    let replay = arg_value(&args, "--replay").map(|path| Session::load(&path).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }));
+    let mut replay_frames = replay.as_ref().map(|session| session.frames().iter());
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
-        let delta_time = last_frame_time.elapsed().as_secs_f32();
+        let real_delta_time = last_frame_time.elapsed().as_secs_f32();
         last_frame_time = Instant::now();
-        
+
+        // Al reproducir, el paso de tiempo y las teclas salen de la sesión y no del reloj ni del teclado
+        let replayed = match replay_frames.as_mut() {
+            Some(frames) => match frames.next() {
+                Some(frame) => Some(*frame),
+                None => break,
+            },
+            None => None,
+        };
+        let (delta_time, input) = match &replayed {
+            Some(frame) => (frame.timestep, frame.input),
+            None => (fixed_dt.unwrap_or(real_delta_time), InputState::from_window(&window)),
+        };
+
         // Actualizar el sistema solar con la cámara
         solar_system.update(delta_time, &mut camera);
         
         // Manejar input
-        handle_input(&window, &mut camera, &mut solar_system);
-        
-        // Manejar movimiento del mouse
-        if let Some((x, y)) = window.get_mouse_pos(minifb::MouseMode::Discard) {
-            if let Some((last_x, last_y)) = last_mouse_pos {
-                let delta_x = x - last_x;
-                let delta_y = y - last_y;
-                camera.handle_mouse_movement(delta_x, delta_y, 0.003);
-            }
-            last_mouse_pos = Some((x, y));
+        handle_input(&simulation_input(&input, &renderer), &mut camera, &mut solar_system, &mut bookmarks);
+        handle_view_input(&input, &mut renderer, &solar_system);
+        renderer.settings.update_fov(input.is_key_down(Key::C), delta_time);
+
+        // Si la ventana cambió de tamaño se recrean los framebuffers (el viewport y la proyección
+        // se recalculan en cada frame con su tamaño)
+        let (width, height) = window.get_size();
+        renderer.resize(width, height);
+
+        // Captura del mouse: Tab la alterna y se suspende mientras la ventana no tiene el foco.
+        // Al cambiar se olvida la última posición, así el primer movimiento tras recapturar no hace saltar la cámara
+        if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            mouse_capture = !mouse_capture;
+        }
+        let captured = mouse_capture && window.is_active();
+        if captured != mouse_captured {
+            window.set_cursor_visibility(!captured);
+            last_mouse_pos = None;
+            mouse_captured = captured;
         }
 
-        // Manejar scroll del mouse
-        if let Some(scroll) = window.get_scroll_wheel() {
-            camera.handle_mouse_scroll(scroll.1 * 0.1);
+        // Mostrar la velocidad de la simulación en el título, y si el mouse quedó libre
+        let time_status = if solar_system.paused {
+            "PAUSA".to_string()
+        } else {
+            format!("x{}", solar_system.time_scale)
+        };
+        let mut title = format!("{} - Tiempo: {}", WINDOW_TITLE, time_status);
+        if !mouse_captured {
+            title.push_str(" - Mouse libre (Tab)");
         }
-        if uniforms.current_shader == 7 && time - last_bloom_update >= bloom_update_interval {
-            gaussian_blur(&mut framebuffer.emissive_buffer, framebuffer.width, framebuffer.height, 10, 2.0); // Reduced kernel size
-            apply_bloom(&mut framebuffer.buffer, &framebuffer.emissive_buffer, framebuffer.width, framebuffer.height);
-            last_bloom_update = time;
+        if title != last_title {
+            window.set_title(&title);
+            last_title = title;
         }
+        
+        // La pose de la cámara y la selección grabadas reemplazan al mouse
+        if let Some(frame) = &replayed {
+            frame.apply_camera(&mut camera);
+            solar_system.selected_body = frame.selected_body;
+        } else {
+            // Manejar movimiento del mouse (solo con el mouse capturado)
+            if let Some((x, y)) = window.get_mouse_pos(MouseMode::Discard).filter(|_| mouse_captured) {
+                if let Some((last_x, last_y)) = last_mouse_pos {
+                    let delta_x = x - last_x;
+                    let delta_y = y - last_y;
+                    // En la vista de pájaro el mouse no gira la cámara
+                    if !solar_system.bird_eye_active() {
+                        camera.handle_mouse_movement(delta_x, delta_y, renderer.settings.mouse_sensitivity());
+                    }
+                }
+                last_mouse_pos = Some((x, y));
+            }
 
-        framebuffer.clear();
-
-        // Renderizar órbitas
-        for body in &solar_system.bodies {
-            if !body.orbit_points.is_empty() {
-                for point in &body.orbit_points {
-                    let screen_pos = world_to_screen(*point, &uniforms);
-                    if screen_pos.x >= 0.0 && screen_pos.x < framebuffer_width as f32 
-                       && screen_pos.y >= 0.0 && screen_pos.y < framebuffer_height as f32 {
-                        framebuffer.set_current_color(0x444444);
-                        framebuffer.point(screen_pos.x as usize, screen_pos.y as usize, screen_pos.z, 0);
+            // Selección de cuerpos con clic izquierdo; doble clic viaja al cuerpo
+            let left_down = window.get_mouse_down(MouseButton::Left);
+            if left_down && !left_was_down {
+                let picked = window.get_mouse_pos(MouseMode::Discard)
+                    .and_then(|(x, y)| renderer.screen_to_ray(x, y))
+                    .and_then(|(origin, direction)| solar_system.pick(&origin, &direction));
+                solar_system.selected_body = picked;
+
+                if let Some(index) = picked {
+                    let double_click = matches!(last_click, Some((at, previous)) if previous == index && at.elapsed().as_secs_f32() < 0.4);
+                    if double_click {
+                        solar_system.warp_to_planet(index);
+                        last_click = None;
+                    } else {
+                        last_click = Some((Instant::now(), index));
                     }
                 }
             }
-        }
-        
-        // Renderizar cuerpos celestes
-        for (i, body) in solar_system.bodies.iter().enumerate() {
-            uniforms.current_shader = body.shader_id;
-            uniforms.model_matrix = create_model_matrix(
-                body.position,
-                body.scale,
-                Vec3::new(0.0, body.rotation, 0.0)
-            );
-            uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-            
-            render(&mut framebuffer, &uniforms, &vertex_arrays, time as u32);
-            
-            // Renderizar anillos de Saturno
-            if i == 5 {
-                uniforms.current_shader = 9;
-                let ring_scale = body.scale * 1.5;
-                let ring_matrix = Mat4::new_scaling(ring_scale) * uniforms.model_matrix;
-                uniforms.model_matrix = ring_matrix;
-                render(&mut framebuffer, &uniforms, &ring_vertex_array, time as u32);
+            left_was_down = left_down;
+
+            // Manejar scroll del mouse; con Ctrl cambia el campo de visión en lugar de acercar la cámara
+            if let Some(scroll) = window.get_scroll_wheel() {
+                if input.is_key_down(Key::LeftCtrl) || input.is_key_down(Key::RightCtrl) {
+                    renderer.settings.adjust_fov(-scroll.1 * FOV_SCROLL_STEP);
+                } else if solar_system.bird_eye_active() {
+                    solar_system.adjust_bird_eye_height(scroll.1 * 0.5);
+                } else {
+                    camera.handle_mouse_scroll(scroll.1 * 0.1);
+                }
             }
         }
 
-        // Renderizar nave espacial
-        uniforms.current_shader = 8; // Shader específico para la nave
-        uniforms.model_matrix = create_model_matrix(
-            solar_system.spaceship_position,
-            0.02, // Escala de la nave
-            solar_system.spaceship_rotation
-        );
-        render(&mut framebuffer, &uniforms, &spaceship_vertex_array, time as u32);
-
-        // Efectos de post-procesamiento para el sol
-        if uniforms.current_shader == 7 {
-            gaussian_blur(&mut framebuffer.emissive_buffer, framebuffer.width, framebuffer.height, 20, 2.5);
-            apply_bloom(&mut framebuffer.buffer, &framebuffer.emissive_buffer, framebuffer.width, framebuffer.height);
+        if let Some(session) = recorder.as_mut() {
+            let frame = SessionFrame {
+                timestep: delta_time,
+                eye: camera.eye,
+                center: camera.center(),
+                up: camera.up,
+                selected_body: solar_system.selected_body,
+                input,
+            };
+            if let Err(err) = session.record(&frame) {
+                eprintln!("{}", err);
+                recorder = None;
+            }
         }
 
-        window.update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+        solar_system.update_crosshair(delta_time, &camera);
+        clock = advance_clock(clock, solar_system.simulation_delta(delta_time));
+        let frame = renderer.render_scene(&solar_system, &camera, clock, delta_time);
+
+        let present_start = Instant::now();
+        window.update_with_buffer(&frame.buffer, frame.width, frame.height)
             .unwrap();
+        renderer.stats.add(Stage::Present, present_start.elapsed());
+        renderer.stats.end_frame();
+    }
 
-        time += 1;
+    if let Some(session) = recorder {
+        if let Err(err) = session.finish() {
+            eprintln!("{}", err);
+        }
     }
 }
 
-fn handle_input(window: &Window, camera: &mut Camera, solar_system: &mut SolarSystem) {
-    let movement_speed = 0.5;
-    
-    // Movimiento básico
-    if window.is_key_down(Key::W) {
-        let new_pos = camera.eye + camera.get_forward() * movement_speed;
-        if !solar_system.check_collision(&new_pos) {
-            camera.eye = new_pos;
-            camera.center += camera.get_forward() * movement_speed;
+// Valor del argumento que sigue a `flag` en la línea de comandos, p. ej. `--scene assets/scene.toml`
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+// Entrada para la simulación: mientras se ajustan los shaders, sus teclas no llegan (ver tuning.rs)
+fn simulation_input(input: &InputState, renderer: &Renderer) -> InputState {
+    if renderer.settings.tuning.is_some() {
+        input.without(&tuning::KEYS)
+    } else {
+        *input
+    }
+}
+
+// Teclas que solo cambian cómo se ve el frame (no la simulación)
+fn handle_view_input(input: &InputState, renderer: &mut Renderer, solar_system: &SolarSystem) {
+    if input.is_key_pressed(Key::F3, KeyRepeat::No) {
+        renderer.stats.toggle();
+    }
+    if input.is_key_pressed(Key::F2, KeyRepeat::No) {
+        renderer.settings.show_axes = !renderer.settings.show_axes;
+    }
+    let shift = input.is_key_down(Key::LeftShift) || input.is_key_down(Key::RightShift);
+    if input.is_key_pressed(Key::G, KeyRepeat::No) && !shift {
+        renderer.settings.show_collisions = !renderer.settings.show_collisions;
+    }
+    if input.is_key_pressed(Key::L, KeyRepeat::No) && shift {
+        renderer.settings.show_labels = !renderer.settings.show_labels;
+    }
+    if input.is_key_pressed(Key::M, KeyRepeat::No) {
+        renderer.settings.show_minimap = !renderer.settings.show_minimap;
+    }
+    if input.is_key_pressed(Key::I, KeyRepeat::No) {
+        renderer.settings.show_overhead = !renderer.settings.show_overhead;
+    }
+    if input.is_key_pressed(Key::K, KeyRepeat::No) {
+        renderer.settings.show_trail = !renderer.settings.show_trail;
+    }
+    if input.is_key_pressed(Key::O, KeyRepeat::No) && shift {
+        renderer.settings.orbit_display = renderer.settings.orbit_display.next();
+    }
+    if input.is_key_pressed(Key::T, KeyRepeat::No) && shift {
+        renderer.settings.temporal_aa = !renderer.settings.temporal_aa;
+    }
+    if input.is_key_pressed(Key::N, KeyRepeat::No) {
+        renderer.settings.motion_blur = !renderer.settings.motion_blur;
+    }
+    // Profundidad de campo: J la alterna, coma y punto acercan o alejan el enfoque
+    if input.is_key_pressed(Key::J, KeyRepeat::No) {
+        renderer.settings.depth_of_field = !renderer.settings.depth_of_field;
+    }
+    if input.is_key_pressed(Key::Comma, KeyRepeat::Yes) {
+        renderer.settings.focus_nudge = (renderer.settings.focus_nudge / FOCUS_NUDGE_STEP).max(MIN_FOCUS_NUDGE);
+    }
+    if input.is_key_pressed(Key::Period, KeyRepeat::Yes) {
+        renderer.settings.focus_nudge = (renderer.settings.focus_nudge * FOCUS_NUDGE_STEP).min(MAX_FOCUS_NUDGE);
+    }
+    if input.is_key_pressed(Key::F4, KeyRepeat::No) {
+        renderer.debug_mode = renderer.debug_mode.next();
+    }
+    // Modo de dibujo (relleno, alambre, plano)
+    if input.is_key_pressed(Key::F5, KeyRepeat::No) {
+        renderer.render_mode = renderer.render_mode.next();
+    }
+    if input.is_key_pressed(Key::F12, KeyRepeat::No) {
+        renderer.post_mut().cycle();
+    }
+    // Ajuste en vivo de los parámetros del material del cuerpo seleccionado
+    if input.is_key_pressed(Key::F9, KeyRepeat::No) {
+        renderer.toggle_tuning(solar_system.selected_body.map(|index| solar_system.bodies[index].shader));
+    }
+    renderer.handle_tuning_input(input);
+    // Escala de render: F6 baja la resolución interna (más rápido), F7 la sube
+    if input.is_key_pressed(Key::F6, KeyRepeat::No) {
+        renderer.settings.render_scale = renderer.settings.render_scale.saturating_sub(1);
+    }
+    if input.is_key_pressed(Key::F7, KeyRepeat::No) {
+        renderer.settings.render_scale = (renderer.settings.render_scale + 1).min(RENDER_SCALES.len() - 1);
+    }
+    // Exposición: RePág aclara (útil para los planetas exteriores), AvPág oscurece
+    if input.is_key_pressed(Key::PageUp, KeyRepeat::Yes) {
+        renderer.settings.exposure = (renderer.settings.exposure * 1.25).min(MAX_EXPOSURE);
+    }
+    if input.is_key_pressed(Key::PageDown, KeyRepeat::Yes) {
+        renderer.settings.exposure = (renderer.settings.exposure / 1.25).max(MIN_EXPOSURE);
+    }
+}
+
+fn handle_input(input: &InputState, camera: &mut Camera, solar_system: &mut SolarSystem, bookmarks: &mut Bookmarks) {
+    // Modo órbita alrededor del cuerpo seleccionado (Shift + O cambia cómo se dibujan las órbitas)
+    let shift = input.is_key_down(Key::LeftShift) || input.is_key_down(Key::RightShift);
+    if input.is_key_pressed(Key::O, KeyRepeat::No) && !shift {
+        solar_system.toggle_orbit_mode(camera);
+    }
+
+    // Modo seguimiento del cuerpo seleccionado; cualquier tecla de vuelo lo interrumpe
+    if input.is_key_pressed(Key::F, KeyRepeat::No) {
+        solar_system.toggle_follow_mode(camera);
+    }
+    let flight_keys = [Key::W, Key::A, Key::S, Key::D, Key::E, Key::Q];
+    if solar_system.following && flight_keys.iter().any(|&key| input.is_key_down(key)) {
+        solar_system.stop_orbit(camera);
+    }
+
+    // Piloto automático hacia el cuerpo seleccionado; lo apaga cualquier tecla de vuelo o el freno
+    if input.is_key_pressed(Key::P, KeyRepeat::No) {
+        solar_system.toggle_autopilot(camera);
+    }
+    if solar_system.autopilot_target().is_some() && (flight_keys.iter().any(|&key| input.is_key_down(key)) || input.is_key_down(Key::X)) {
+        solar_system.disengage_autopilot();
+    }
+
+    // Controles de vuelo: W/S empuje frontal, A/D lateral, E/Q sube y baja en el eje Y del mundo, X frena
+    // (sin efecto mientras la cámara orbita un cuerpo)
+    let axis = |positive: Key, negative: Key| {
+        (input.is_key_down(positive) as i32 - input.is_key_down(negative) as i32) as f32
+    };
+    if camera.orbit_mode {
+        solar_system.spaceship.set_controls(0.0, 0.0, 0.0, false);
+    } else {
+        solar_system.spaceship.set_controls(axis(Key::W, Key::S), axis(Key::D, Key::A), axis(Key::E, Key::Q), input.is_key_down(Key::X));
+    }
+
+    // Shift multiplica la velocidad; Ctrl o Alt la reducen para maniobrar cerca de una superficie
+    let boost = shift;
+    let precision = [Key::LeftCtrl, Key::RightCtrl, Key::LeftAlt, Key::RightAlt].iter().any(|&key| input.is_key_down(key));
+    let modifier = match (boost, precision) {
+        (true, false) => spaceship::BOOST_MULTIPLIER,
+        (false, true) => spaceship::PRECISION_MULTIPLIER,
+        _ => 1.0,
+    };
+    solar_system.spaceship.set_speed_modifier(modifier);
+
+    // Marcadores de cámara: Ctrl+1..4 guarda la pose en un espacio, Alt+1..4 vuelve a ella (si está vacío no pasa nada)
+    let ctrl = input.is_key_down(Key::LeftCtrl) || input.is_key_down(Key::RightCtrl);
+    let alt = input.is_key_down(Key::LeftAlt) || input.is_key_down(Key::RightAlt);
+    let slot_keys: [Key; bookmarks::SLOTS] = [Key::Key1, Key::Key2, Key::Key3, Key::Key4];
+    for (slot, &key) in slot_keys.iter().enumerate() {
+        if ctrl && input.is_key_pressed(key, KeyRepeat::No) {
+            if let Err(err) = bookmarks.store(slot, camera) {
+                eprintln!("{}", err);
+            }
+        }
+        if alt && !ctrl && input.is_key_pressed(key, KeyRepeat::No) {
+            if let Some(bookmark) = bookmarks.get(slot) {
+                solar_system.fly_to_bookmark(bookmark, camera);
+            }
         }
     }
-    if window.is_key_down(Key::S) {
-        let new_pos = camera.eye - camera.get_forward() * movement_speed;
-        if !solar_system.check_collision(&new_pos) {
-            camera.eye = new_pos;
-            camera.center -= camera.get_forward() * movement_speed;
+
+    // Instantáneas de la simulación: F10 guarda y F11 vuelve a ella
+    if input.is_key_pressed(Key::F10, KeyRepeat::No) {
+        match solar_system.save_snapshot(snapshot::DEFAULT_PATH, camera) {
+            Ok(()) => println!("instantánea guardada en {}", snapshot::DEFAULT_PATH),
+            Err(err) => eprintln!("{}", err),
         }
     }
-    if window.is_key_down(Key::A) {
-        let new_pos = camera.eye - camera.get_right() * movement_speed;
-        if !solar_system.check_collision(&new_pos) {
-            camera.eye = new_pos;
-            camera.center -= camera.get_right() * movement_speed;
+    if input.is_key_pressed(Key::F11, KeyRepeat::No) {
+        if let Err(err) = solar_system.load_snapshot(snapshot::DEFAULT_PATH, camera) {
+            eprintln!("{}", err);
         }
     }
-    if window.is_key_down(Key::D) {
-        let new_pos = camera.eye + camera.get_right() * movement_speed;
-        if !solar_system.check_collision(&new_pos) {
-            camera.eye = new_pos;
-            camera.center += camera.get_right() * movement_speed;
+
+    // Warping a planetas: las teclas 1-9 recorren los cuerpos que orbitan al Sol (el Sol no tiene tecla);
+    // con Ctrl o Alt son los marcadores
+    let warp_keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+    for (key, index) in warp_keys.iter().zip(solar_system.planet_indices()) {
+        if !ctrl && !alt && input.is_key_pressed(*key, KeyRepeat::No) {
+            solar_system.warp_to_planet(index);
         }
     }
 
-    // Warping a planetas
-    if window.is_key_down(Key::Key1) { solar_system.warp_to_planet(0); }
-    if window.is_key_down(Key::Key2) { solar_system.warp_to_planet(1); }
-    if window.is_key_down(Key::Key3) { solar_system.warp_to_planet(2); }
-    if window.is_key_down(Key::Key4) { solar_system.warp_to_planet(3); }
-    if window.is_key_down(Key::Key5) { solar_system.warp_to_planet(4); }
+    // Viajar al cuerpo seleccionado
+    if input.is_key_pressed(Key::Enter, KeyRepeat::No) {
+        solar_system.warp_to_selected();
+    }
+
+    // Control del tiempo
+    if input.is_key_pressed(Key::Space, KeyRepeat::No) {
+        solar_system.toggle_pause();
+    }
+    if input.is_key_pressed(Key::RightBracket, KeyRepeat::No) || input.is_key_pressed(Key::Equal, KeyRepeat::No) {
+        solar_system.scale_time(2.0);
+    }
+    if input.is_key_pressed(Key::LeftBracket, KeyRepeat::No) || input.is_key_pressed(Key::Minus, KeyRepeat::No) {
+        solar_system.scale_time(0.5);
+    }
+    if input.is_key_pressed(Key::Key0, KeyRepeat::No) {
+        solar_system.reset_time_scale();
+    }
+
+    // T fija como selección el cuerpo en la mira (Shift + T alterna el anti-aliasing temporal)
+    if input.is_key_pressed(Key::T, KeyRepeat::No) && !boost {
+        solar_system.lock_crosshair_target();
+    }
+
+    // Shift + G activa la gravedad de los cuerpos sobre la nave (G sola muestra las esferas de colisión)
+    if input.is_key_pressed(Key::G, KeyRepeat::No) && boost {
+        solar_system.toggle_gravity();
+    }
+
+    // Sistema aleatorio: R genera otro con la semilla siguiente (se ve en el HUD para repetirlo con `--seed`)
+    if input.is_key_pressed(Key::R, KeyRepeat::No) {
+        let seed = solar_system.seed.map_or(1, |seed| seed.wrapping_add(1));
+        solar_system.regenerate(seed, camera);
+    }
 
-    // Vista de pájaro
-    if window.is_key_down(Key::B) {
-        solar_system.toggle_bird_eye_view();
+    // L nivela la cámara: quita el alabeo y la inclinación excesiva sin cambiar el rumbo (Shift + L alterna las etiquetas)
+    if input.is_key_pressed(Key::L, KeyRepeat::No) && !boost {
+        solar_system.level_camera();
     }
-}
\ No newline at end of file
+
+    // Modo de cámara: libre, persecución con resorte o cabina
+    if input.is_key_pressed(Key::V, KeyRepeat::No) {
+        solar_system.cycle_camera_mode();
+    }
+
+    // Vista de pájaro (solo en el flanco de la tecla, no mientras se mantiene presionada)
+    if input.is_key_pressed(Key::B, KeyRepeat::No) {
+        solar_system.toggle_bird_eye_view(camera);
+    }
+}
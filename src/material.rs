@@ -0,0 +1,481 @@
+// Materiales: cada tipo de shader junto con su ruido y parámetros propios
+use fastnoise_lite::{CellularDistanceFunction, CellularReturnType, FastNoiseLite, FractalType, NoiseType};
+use serde::Deserialize;
+use crate::color::Color;
+use crate::shaders::static_surface;
+use crate::surface_texture::SurfaceTexture;
+
+// Tipo de superficie de cada cuerpo; en las escenas TOML se escribe por nombre (shader = "Earth")
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ShaderKind {
+    Earth,
+    Mars,
+    Mercury,
+    Venus,
+    Saturn,
+    Jupiter,
+    Uranus,
+    Neptune,
+    Sun,
+    Moon,
+    Ring,
+    UranusRing,
+    Spaceship,
+    CometTail,
+    Clouds,        // Capa de nubes de la Tierra; no se usa en las escenas, la agrega el render
+    // Estilos de los planetas generados (`--seed`); su ruido y paleta salen de la semilla de cada planeta
+    Rocky,
+    GasGiant,
+    Icy,
+}
+
+impl ShaderKind {
+    pub const ALL: [ShaderKind; 18] = [
+        ShaderKind::Earth,
+        ShaderKind::Mars,
+        ShaderKind::Mercury,
+        ShaderKind::Venus,
+        ShaderKind::Saturn,
+        ShaderKind::Jupiter,
+        ShaderKind::Uranus,
+        ShaderKind::Neptune,
+        ShaderKind::Sun,
+        ShaderKind::Moon,
+        ShaderKind::Ring,
+        ShaderKind::UranusRing,
+        ShaderKind::Spaceship,
+        ShaderKind::CometTail,
+        ShaderKind::Clouds,
+        ShaderKind::Rocky,
+        ShaderKind::GasGiant,
+        ShaderKind::Icy,
+    ];
+}
+
+// Halo atmosférico: color del borde y su intensidad
+#[derive(Clone, Copy, Debug)]
+pub struct Atmosphere {
+    pub color: Color,
+    pub strength: f32,
+}
+
+// Parámetros de los shaders que se pueden ajustar en vivo (ver tuning.rs). Cada uno lo leen uno o
+// más materiales; los demás materiales lo tienen con su valor por defecto y lo ignoran
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShaderParam {
+    LandThreshold,      // Tierra: ruido a partir del cual hay continentes
+    CityThreshold,      // Tierra: ruido a partir del cual hay luces de ciudades
+    CloudCover,         // Tierra y su capa de nubes: corre el umbral de las nubes (positivo = más nubes)
+    DuneScale,          // Júpiter (desierto): frecuencia del ruido del terreno
+    SandThreshold,      // Júpiter (desierto): ruido a partir del cual hay arena en lugar de roca
+    BandScale,          // Saturno: multiplica la frecuencia de las zonas y las bandas
+    HexagonRadius,      // Saturno: distancia del eje a las esquinas del hexágono polar
+    EmissionGain,       // Sol: multiplica la emisión
+    SunspotThreshold,   // Sol: temperatura fría a partir de la cual aparecen manchas
+    LavaThreshold,      // Urano (Tierra primordial): ruido a partir del cual hay lava
+}
+
+impl ShaderParam {
+    pub const ALL: [ShaderParam; 10] = [
+        ShaderParam::LandThreshold,
+        ShaderParam::CityThreshold,
+        ShaderParam::CloudCover,
+        ShaderParam::DuneScale,
+        ShaderParam::SandThreshold,
+        ShaderParam::BandScale,
+        ShaderParam::HexagonRadius,
+        ShaderParam::EmissionGain,
+        ShaderParam::SunspotThreshold,
+        ShaderParam::LavaThreshold,
+    ];
+
+    // Valor con que arranca cada parámetro; el modo de ajuste imprime estas mismas líneas
+    pub fn default_value(self) -> f32 {
+        match self {
+            ShaderParam::LandThreshold => 0.4,
+            ShaderParam::CityThreshold => 0.55,
+            ShaderParam::CloudCover => 0.0,
+            ShaderParam::DuneScale => 3.0,
+            ShaderParam::SandThreshold => 0.5,
+            ShaderParam::BandScale => 1.0,
+            ShaderParam::HexagonRadius => 0.32,
+            ShaderParam::EmissionGain => 1.0,
+            ShaderParam::SunspotThreshold => 0.45,
+            ShaderParam::LavaThreshold => 0.5,
+        }
+    }
+
+    // Paso de cada pulsación y rango permitido (mínimo, máximo)
+    pub fn step(self) -> (f32, f32, f32) {
+        match self {
+            ShaderParam::LandThreshold | ShaderParam::SandThreshold | ShaderParam::LavaThreshold => (0.02, 0.05, 0.75),
+            ShaderParam::CityThreshold => (0.02, 0.2, 0.95),
+            ShaderParam::CloudCover => (0.02, -0.4, 0.4),
+            ShaderParam::DuneScale => (0.1, 0.5, 8.0),
+            ShaderParam::BandScale => (0.05, 0.25, 3.0),
+            ShaderParam::HexagonRadius => (0.01, 0.1, 0.6),
+            ShaderParam::EmissionGain => (0.05, 0.0, 3.0),
+            ShaderParam::SunspotThreshold => (0.02, 0.1, 0.9),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderParam::LandThreshold => "UMBRAL CONTINENTES",
+            ShaderParam::CityThreshold => "UMBRAL CIUDADES",
+            ShaderParam::CloudCover => "COBERTURA NUBES",
+            ShaderParam::DuneScale => "ESCALA TERRENO",
+            ShaderParam::SandThreshold => "UMBRAL ARENA",
+            ShaderParam::BandScale => "ESCALA BANDAS",
+            ShaderParam::HexagonRadius => "RADIO HEXAGONO",
+            ShaderParam::EmissionGain => "GANANCIA EMISION",
+            ShaderParam::SunspotThreshold => "UMBRAL MANCHAS",
+            ShaderParam::LavaThreshold => "UMBRAL LAVA",
+        }
+    }
+
+    // Materiales que leen el parámetro; al ajustarlo cambia en todos
+    pub fn materials(self) -> &'static [ShaderKind] {
+        match self {
+            ShaderParam::LandThreshold | ShaderParam::CityThreshold => &[ShaderKind::Earth],
+            ShaderParam::CloudCover => &[ShaderKind::Earth, ShaderKind::Clouds],
+            ShaderParam::DuneScale | ShaderParam::SandThreshold => &[ShaderKind::Jupiter],
+            ShaderParam::BandScale | ShaderParam::HexagonRadius => &[ShaderKind::Saturn],
+            ShaderParam::EmissionGain | ShaderParam::SunspotThreshold => &[ShaderKind::Sun],
+            ShaderParam::LavaThreshold => &[ShaderKind::Uranus],
+        }
+    }
+}
+
+// Valores de todos los parámetros ajustables de un material
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderParams {
+    values: [f32; ShaderParam::ALL.len()],
+}
+
+impl ShaderParams {
+    pub fn get(&self, param: ShaderParam) -> f32 {
+        self.values[param as usize]
+    }
+
+    fn set(&mut self, param: ShaderParam, value: f32) {
+        self.values[param as usize] = value;
+    }
+}
+
+impl Default for ShaderParams {
+    fn default() -> Self {
+        ShaderParams { values: ShaderParam::ALL.map(ShaderParam::default_value) }
+    }
+}
+
+pub struct Material {
+    pub kind: ShaderKind,
+    pub noise: FastNoiseLite,
+    pub detail_noise: Option<FastNoiseLite>,   // Nubes de la Tierra (para su sombra), bandas de Júpiter
+    pub atmosphere: Option<Atmosphere>,
+    pub craters: Option<FastNoiseLite>,        // Campo celular de cráteres de Mercurio y las lunas
+    pub lighting: Lighting,
+    pub params: ShaderParams,
+    pub baked: Option<SurfaceTexture>,         // Superficie estática horneada (ver `MaterialRegistry::new`)
+}
+
+// Cómo recibe la luz del Sol: `ambient` es el piso de luz del lado nocturno y `wrap` cuánto pasa la
+// luz más allá del terminador (0 es un corte lambertiano; las atmósferas densas lo suavizan más)
+#[derive(Clone, Copy, Debug)]
+pub struct Lighting {
+    pub ambient: f32,
+    pub wrap: f32,
+}
+
+impl Material {
+    pub fn new(kind: ShaderKind) -> Self {
+        let (noise, detail_noise, atmosphere) = match kind {
+            ShaderKind::Earth => (create_earth_noise(), Some(create_cloud_noise()), atmosphere(0.35, 0.6, 1.0, 0.8)),
+            ShaderKind::Mars => (create_mars_noise(), None, None),
+            ShaderKind::Mercury => (create_mercury_noise(), None, None),
+            ShaderKind::Venus => (create_venus_noise(), None, atmosphere(1.0, 0.9, 0.6, 0.7)),
+            ShaderKind::Saturn => (create_saturn_noise(), None, atmosphere(0.95, 0.85, 0.6, 0.4)),
+            ShaderKind::Jupiter => (create_jupiter_noise(), Some(create_jupiter_band_noise()), atmosphere(0.85, 0.55, 0.3, 0.5)),
+            ShaderKind::Uranus => (create_urano_noise(), None, atmosphere(0.45, 0.9, 0.95, 0.6)),
+            ShaderKind::Neptune => (create_neptune_noise(), None, atmosphere(0.3, 0.5, 1.0, 0.6)),
+            ShaderKind::Sun => (create_sun_noise(), None, None),
+            ShaderKind::Moon => (create_moon_noise(), None, None),
+            ShaderKind::Clouds => (create_cloud_noise(), None, None),
+            ShaderKind::Rocky | ShaderKind::GasGiant | ShaderKind::Icy => {
+                let atmosphere = match kind {
+                    ShaderKind::GasGiant => atmosphere(0.9, 0.85, 0.75, 0.4),
+                    ShaderKind::Icy => atmosphere(0.7, 0.85, 1.0, 0.5),
+                    _ => None,
+                };
+                (create_generated_noise(kind, DEFAULT_GENERATED_SEED, 1.0), None, atmosphere)
+            }
+            ShaderKind::Ring | ShaderKind::UranusRing | ShaderKind::Spaceship | ShaderKind::CometTail => (FastNoiseLite::new(), None, None),
+        };
+        let craters = matches!(kind, ShaderKind::Mercury | ShaderKind::Moon | ShaderKind::Rocky).then(|| create_crater_noise(2718));
+        Material { kind, noise, detail_noise, atmosphere, craters, lighting: lighting(kind), params: ShaderParams::default(), baked: None }
+    }
+}
+
+fn lighting(kind: ShaderKind) -> Lighting {
+    let (ambient, wrap) = match kind {
+        // Roca desnuda: terminador marcado
+        ShaderKind::Mercury | ShaderKind::Moon | ShaderKind::Rocky => (0.05, 0.05),
+        ShaderKind::Earth | ShaderKind::Mars => (0.08, 0.2),
+        // Las nubes casi desaparecen de noche; el terminador se difumina un poco más que en el suelo
+        ShaderKind::Clouds => (0.03, 0.3),
+        ShaderKind::Icy => (0.08, 0.25),
+        // Nubes y gigantes gaseosos: la luz se difunde en la atmósfera
+        ShaderKind::Venus | ShaderKind::Jupiter | ShaderKind::Saturn | ShaderKind::Uranus | ShaderKind::Neptune | ShaderKind::GasGiant => (0.1, 0.45),
+        // Los anillos se iluminan por ambas caras y dispersan algo de luz hacia atrás
+        ShaderKind::Ring | ShaderKind::UranusRing => (0.3, 0.0),
+        ShaderKind::Spaceship => (0.2, 0.2),
+        // Emiten su propia luz
+        ShaderKind::Sun | ShaderKind::CometTail => (1.0, 0.0),
+    };
+    Lighting { ambient, wrap }
+}
+
+// Superficie de un planeta generado: semilla y frecuencia de su ruido y una paleta de tres colores
+// (oscuro, base y acento). Son datos simples para que el generador los guarde en cada cuerpo
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceParams {
+    pub seed: i32,
+    pub frequency: f32,
+    pub palette: [Color; 3],
+}
+
+// Ruido y paleta de un planeta generado, armados a partir de sus parámetros al dibujarlo.
+// Los shaders de Rocky, GasGiant e Icy los usan en lugar del ruido compartido del material
+pub struct Surface {
+    pub noise: FastNoiseLite,
+    pub craters: Option<FastNoiseLite>,
+    pub palette: [Color; 3],
+}
+
+impl Surface {
+    pub fn new(kind: ShaderKind, params: &SurfaceParams) -> Self {
+        Surface {
+            noise: create_generated_noise(kind, params.seed, params.frequency),
+            craters: (kind == ShaderKind::Rocky).then(|| create_crater_noise(params.seed.wrapping_add(1))),
+            palette: params.palette,
+        }
+    }
+}
+
+// Semilla del ruido compartido de los estilos generados, para cuerpos de una escena sin parámetros propios
+const DEFAULT_GENERATED_SEED: i32 = 9001;
+
+fn atmosphere(r: f32, g: f32, b: f32, strength: f32) -> Option<Atmosphere> {
+    Some(Atmosphere { color: Color::from_float(r, g, b), strength })
+}
+
+// Un material por tipo, creado una sola vez al iniciar
+pub struct MaterialRegistry {
+    materials: Vec<Material>,
+    bake_resolution: usize,
+}
+
+impl MaterialRegistry {
+    // `bake_resolution` es el ancho de las texturas de superficie; con 0 todo se calcula con ruido en vivo
+    pub fn new(bake_resolution: usize) -> Self {
+        let materials = ShaderKind::ALL.iter().map(|&kind| Material::new(kind)).collect();
+        let mut registry = MaterialRegistry { materials, bake_resolution };
+        registry.bake_missing();
+        registry
+    }
+
+    pub fn get(&self, kind: ShaderKind) -> &Material {
+        &self.materials[kind as usize]
+    }
+
+    // Suma `delta` a un parámetro en todos los materiales que lo leen, dentro de su rango. La textura
+    // horneada de esos materiales deja de corresponder: se usa el ruido en vivo hasta `bake_missing`
+    pub fn adjust(&mut self, param: ShaderParam, delta: f32) {
+        let (_, min, max) = param.step();
+        for &kind in param.materials() {
+            let material = &mut self.materials[kind as usize];
+            material.params.set(param, (material.params.get(param) + delta).clamp(min, max));
+            material.baked = None;
+        }
+    }
+
+    // Hornea la superficie de los materiales estáticos que no tienen textura (todos al iniciar, los
+    // ajustados al salir del modo de ajuste). Cada uno en su propio hilo, así tarda lo que el más lento
+    pub fn bake_missing(&mut self) {
+        if self.bake_resolution == 0 {
+            return;
+        }
+        let resolution = self.bake_resolution;
+        std::thread::scope(|scope| {
+            for material in self.materials.iter_mut().filter(|material| material.baked.is_none()) {
+                if let Some(surface) = static_surface(material.kind) {
+                    scope.spawn(move || {
+                        material.baked = Some(SurfaceTexture::bake(surface, &material.noise, &material.params, resolution));
+                    });
+                }
+            }
+        });
+    }
+}
+
+fn create_earth_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(1337);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2S));
+    noise.set_fractal_type(Some(FractalType::Ridged));
+    noise.set_fractal_octaves(Some(5));
+    noise.set_fractal_lacunarity(Some(3.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(0.5)); 
+    noise
+}
+
+// Se muestrea sobre la dirección (radio 1), así que la frecuencia fija el tamaño de los frentes nubosos
+fn create_cloud_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(40);  
+    noise.set_noise_type(Some(NoiseType::Perlin)); 
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(2));
+    noise.set_fractal_lacunarity(Some(3.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(2.5));
+    noise
+}
+
+fn create_mars_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(1234);
+    noise.set_noise_type(Some(NoiseType::Perlin));
+    noise.set_fractal_type(Some(FractalType::Ridged));
+    noise.set_fractal_octaves(Some(4));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(1.5)); 
+    noise
+}
+
+fn create_moon_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(4321);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::PingPong));
+    noise.set_fractal_octaves(Some(2));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(3.0));  
+    noise
+}
+
+// Distancia al punto celular más cercano: cada celda aporta un cráter centrado en su punto.
+// Frecuencia 1 para que la escala de cada capa se elija al muestrear
+fn create_crater_noise(seed: i32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    noise.set_noise_type(Some(NoiseType::Cellular));
+    noise.set_cellular_distance_function(Some(CellularDistanceFunction::Euclidean));
+    noise.set_cellular_return_type(Some(CellularReturnType::Distance));
+    noise.set_cellular_jitter(Some(0.8));
+    noise.set_frequency(Some(1.0));
+    noise
+}
+
+fn create_mercury_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(4321);
+    noise.set_noise_type(Some(NoiseType::Perlin));
+    noise.set_fractal_type(Some(FractalType::PingPong));
+    noise.set_fractal_octaves(Some(5));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(1.0));
+    noise.set_frequency(Some(5.0));  
+    noise
+}
+
+fn create_jupiter_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(5678);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::DomainWarpProgressive));
+    noise.set_fractal_octaves(Some(6));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(2.0));
+    noise
+}
+
+fn create_jupiter_band_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(7890);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_frequency(Some(1.0));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise
+}
+
+// Turbulencia suave que dobla las bandas de Saturno; se muestrea estirada a lo largo de la longitud
+fn create_saturn_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(6262);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(3));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(1.0));
+    noise
+}
+
+fn create_urano_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(2021);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::Ridged));
+    noise.set_fractal_octaves(Some(4));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.4));
+    noise.set_frequency(Some(0.2));
+    noise
+}
+
+fn create_neptune_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(3030);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(3));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(1.0));
+    noise
+}
+
+fn create_sun_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(777);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(3));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(1.0));
+    noise
+}
+
+fn create_venus_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(4242);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(2));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(1.2));
+    noise
+}
+
+// Ruido de los estilos generados: relieve suave para los rocosos, turbulencia de bandas para los
+// gigantes gaseosos y grietas (ruido con crestas) para los helados
+fn create_generated_noise(kind: ShaderKind, seed: i32, frequency: f32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    let (noise_type, fractal_type, octaves) = match kind {
+        ShaderKind::GasGiant => (NoiseType::OpenSimplex2, FractalType::FBm, 3),
+        ShaderKind::Icy => (NoiseType::Perlin, FractalType::Ridged, 4),
+        _ => (NoiseType::OpenSimplex2S, FractalType::FBm, 5),
+    };
+    noise.set_noise_type(Some(noise_type));
+    noise.set_fractal_type(Some(fractal_type));
+    noise.set_fractal_octaves(Some(octaves));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(frequency));
+    noise
+}
@@ -0,0 +1,113 @@
+// Minimapa cenital en la esquina superior derecha: órbitas, un punto por cuerpo y una flecha
+// con la posición y el rumbo de la cámara. Se dibuja sobre la imagen final, sin profundidad
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::material::ShaderKind;
+use crate::solar_system::{CelestialBody, SolarSystem};
+use crate::renderer::Uniforms;
+
+const MAP_SIZE: usize = 160;
+const MAP_MARGIN: usize = 8;
+const MAP_PADDING: f32 = 6.0;   // Espacio libre entre la órbita más externa y el borde
+
+const BACKGROUND_COLOR: u32 = 0x000814;
+const BACKGROUND_ALPHA: f32 = 0.7;
+const BORDER_COLOR: u32 = 0x4A5A70;
+const ORBIT_COLOR: u32 = 0x2E3A4A;
+const CAMERA_COLOR: u32 = 0xFFFFFF;
+const ARROW_LENGTH: f32 = 7.0;
+const ARROW_WIDTH: f32 = 4.0;
+
+// Color del punto de cada cuerpo (y de su estela orbital): los planetas generados usan el color
+// base de su paleta y los demás uno parecido al tono dominante de su shader
+pub fn body_color(body: &CelestialBody) -> u32 {
+    body.surface.map_or(shader_color(body.shader), |surface| surface.palette[1].to_hex())
+}
+
+fn shader_color(shader: ShaderKind) -> u32 {
+    match shader {
+        ShaderKind::Sun => 0xFFD040,
+        ShaderKind::Mercury => 0xB0B8C0,
+        ShaderKind::Venus => 0xE0C080,
+        ShaderKind::Earth => 0x40A0E0,
+        ShaderKind::Mars => 0xD05030,
+        ShaderKind::Jupiter => 0xD8B080,
+        ShaderKind::Saturn => 0xE8D090,
+        ShaderKind::Uranus => 0x90E0E8,
+        ShaderKind::Neptune => 0x4060E0,
+        _ => 0xA0A0A0,
+    }
+}
+
+pub fn draw(framebuffer: &mut Framebuffer, solar_system: &SolarSystem, uniforms: &Uniforms) {
+    if framebuffer.width < MAP_SIZE + 2 * MAP_MARGIN || framebuffer.height < MAP_SIZE + 2 * MAP_MARGIN {
+        return;
+    }
+
+    let left = framebuffer.width - MAP_SIZE - MAP_MARGIN;
+    let top = MAP_MARGIN;
+    let (min_x, max_x) = (left as f32, (left + MAP_SIZE - 1) as f32);
+    let (min_y, max_y) = (top as f32, (top + MAP_SIZE - 1) as f32);
+    framebuffer.fill_rect(left, top, MAP_SIZE, MAP_SIZE, BACKGROUND_COLOR, BACKGROUND_ALPHA);
+    framebuffer.draw_line(min_x, min_y, max_x, min_y, BORDER_COLOR);
+    framebuffer.draw_line(max_x, min_y, max_x, max_y, BORDER_COLOR);
+    framebuffer.draw_line(max_x, max_y, min_x, max_y, BORDER_COLOR);
+    framebuffer.draw_line(min_x, max_y, min_x, min_y, BORDER_COLOR);
+
+    // La escala se ajusta para que la órbita más externa siempre entre en el mapa. Vista desde arriba:
+    // x del mundo hacia la derecha y z del mundo hacia abajo
+    let center = (left as f32 + MAP_SIZE as f32 / 2.0, top as f32 + MAP_SIZE as f32 / 2.0);
+    let scale = (MAP_SIZE as f32 / 2.0 - MAP_PADDING) / solar_system.system_radius().max(1.0);
+    let to_map = |point: Vec3| (center.0 + point.x * scale, center.1 + point.z * scale);
+
+    // Órbitas de los cuerpos que giran alrededor del Sol; las de las lunas no se distinguirían a esta escala
+    for body in solar_system.bodies.iter().filter(|body| body.parent.is_none()) {
+        if body.kepler.is_some() {
+            let count = body.orbit_points.len();
+            for i in 0..count {
+                let (x0, y0) = to_map(body.orbit_points[i]);
+                let (x1, y1) = to_map(body.orbit_points[(i + 1) % count]);
+                framebuffer.draw_line(x0, y0, x1, y1, ORBIT_COLOR);
+            }
+        } else if body.orbital_radius > 0.0 {
+            framebuffer.draw_circle(center.0, center.1, body.orbital_radius * scale, ORBIT_COLOR);
+        }
+    }
+
+    // Un punto por cuerpo: el Sol más grande, las lunas de un solo pixel
+    for body in &solar_system.bodies {
+        let (x, y) = to_map(body.position);
+        let size = match (body.shader, body.parent) {
+            (ShaderKind::Sun, _) => 5,
+            (_, Some(_)) => 1,
+            _ => 3,
+        };
+        let half = size as f32 / 2.0;
+        if x - half >= min_x && y - half >= min_y {
+            let color = body_color(body);
+            framebuffer.fill_rect((x - half) as usize, (y - half) as usize, size, size, color, 1.0);
+        }
+    }
+
+    // Cámara: flecha en la dirección en que mira, pegada al borde si está fuera del mapa.
+    // La tercera fila de la matriz de vista es el eje "atrás" de la cámara
+    let view = &uniforms.view_matrix;
+    let forward = Vec3::new(-view[(2, 0)], 0.0, -view[(2, 2)]);
+    let (x, y) = to_map(uniforms.camera_position);
+    let (x, y) = (x.clamp(min_x + 2.0, max_x - 2.0), y.clamp(min_y + 2.0, max_y - 2.0));
+    if forward.magnitude() < 0.1 {
+        // Mirando casi en vertical (vista de pájaro) el rumbo no se distingue: solo un círculo
+        framebuffer.draw_circle(x, y, ARROW_WIDTH / 2.0, CAMERA_COLOR);
+        return;
+    }
+    let direction = forward.normalize();
+    let (dx, dy) = (direction.x, direction.z);
+    let tip = (x + dx * ARROW_LENGTH, y + dy * ARROW_LENGTH);
+    let back = (x - dx * ARROW_WIDTH, y - dy * ARROW_WIDTH);
+    let wing_left = (back.0 - dy * ARROW_WIDTH, back.1 + dx * ARROW_WIDTH);
+    let wing_right = (back.0 + dy * ARROW_WIDTH, back.1 - dx * ARROW_WIDTH);
+    framebuffer.draw_line(tip.0, tip.1, wing_left.0, wing_left.1, CAMERA_COLOR);
+    framebuffer.draw_line(wing_left.0, wing_left.1, x, y, CAMERA_COLOR);
+    framebuffer.draw_line(x, y, wing_right.0, wing_right.1, CAMERA_COLOR);
+    framebuffer.draw_line(wing_right.0, wing_right.1, tip.0, tip.1, CAMERA_COLOR);
+}
@@ -1,9 +1,40 @@
-use tobj;
 use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fmt;
+use std::fs;
 use crate::vertex::Vertex;
 
 pub struct Obj {
-    meshes: Vec<Mesh>,
+    vertex_buffer: Vec<Vertex>,   // Vértices únicos (posición, normal, uv)
+    index_buffer: Vec<u32>,       // Tres índices por triángulo dentro de vertex_buffer
+    radius: f32,                  // Distancia máxima de un vértice al origen del modelo
+}
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(String, std::io::Error),
+    Parse { path: String, line: usize, text: String, reason: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(path, err) => write!(f, "no se pudo leer el modelo '{}': {}", path, err),
+            ObjError::Parse { path, line, text, reason } => {
+                write!(f, "{}:{}: {} en '{}'", path, line, reason, text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+// Cómo generar las normales que faltan en el archivo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NormalMode {
+    Smooth,   // Promedio de las caras vecinas ponderado por área
+    Flat,     // Normal de la cara, duplicando los vértices de cada triángulo
 }
 
 struct Mesh {
@@ -14,49 +45,510 @@ struct Mesh {
 }
 
 impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
+    // `normal_mode` elige cómo se generan las normales de los vértices que no las traen
+    pub fn load(filename: &str, normal_mode: NormalMode) -> Result<Self, ObjError> {
+        Ok(Obj::from_mesh(read_mesh(filename, normal_mode)?))
+    }
+
+    // Como `load` con normales suavizadas, para modelos con UV de proyección esférica (sphere.obj): corrige la costura y los polos
+    pub fn load_spherical(filename: &str) -> Result<Self, ObjError> {
+        Ok(Obj::from_mesh(fix_spherical_uvs(read_mesh(filename, NormalMode::Smooth)?)))
+    }
+
+    // Esfera UV unitaria. `stacks` divide la latitud y `slices` la longitud; las UV van de 0 a 1 en ambos ejes
+    pub fn sphere(stacks: usize, slices: usize) -> Self {
+        let stacks = stacks.max(2);
+        let slices = slices.max(3);
+
+        let mut vertices = Vec::with_capacity((stacks + 1) * (slices + 1));
+        let mut texcoords = Vec::with_capacity((stacks + 1) * (slices + 1));
+        for stack in 0..=stacks {
+            for slice in 0..=slices {
+                let v = stack as f32 / stacks as f32;
+                let u = slice as f32 / slices as f32;
+                let theta = v * PI;         // 0 en el polo norte, PI en el polo sur
+                let phi = u * 2.0 * PI;
+                vertices.push(Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                ));
+                texcoords.push(Vec2::new(u, v));
+            }
+        }
 
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
+        let index = |stack: usize, slice: usize| (stack * (slices + 1) + slice) as u32;
+        let mut indices = Vec::with_capacity(stacks * slices * 6);
+        for stack in 0..stacks {
+            for slice in 0..slices {
+                // En los polos un lado del cuadrilátero colapsa, así que solo hay un triángulo
+                if stack != 0 {
+                    indices.extend([index(stack, slice), index(stack + 1, slice), index(stack, slice + 1)]);
+                }
+                if stack != stacks - 1 {
+                    indices.extend([index(stack, slice + 1), index(stack + 1, slice), index(stack + 1, slice + 1)]);
+                }
             }
-        }).collect();
+        }
+
+        // En una esfera unitaria la normal hacia afuera es la misma posición
+        let normals = vertices.clone();
+        Obj::from_mesh(fix_spherical_uvs(Mesh { vertices, normals, texcoords, indices }))
+    }
+
+    fn from_mesh(mesh: Mesh) -> Self {
+        let (vertex_buffer, index_buffer) = build_index_buffer(&mesh);
+        let radius = mesh.vertices.iter().map(|vertex| vertex.magnitude()).fold(0.0, f32::max);
+        Obj { vertex_buffer, index_buffer, radius }
+    }
+
+    // Radio de la esfera centrada en el origen que contiene al modelo, en unidades del modelo
+    pub fn bounding_radius(&self) -> f32 {
+        self.radius
+    }
 
-        Ok(Obj { meshes })
+    // Vértices únicos e índices de triángulos: cada vértice se transforma una sola vez por frame
+    pub fn get_indexed(&self) -> (&[Vertex], &[u32]) {
+        (&self.vertex_buffer, &self.index_buffer)
     }
+}
+
+// Malla del archivo con las normales que faltan ya generadas
+fn read_mesh(filename: &str, normal_mode: NormalMode) -> Result<Mesh, ObjError> {
+    let contents = fs::read_to_string(filename).map_err(|err| ObjError::Io(filename.to_string(), err))?;
+    Ok(generate_missing_normals(parse_mesh(&contents, filename)?, normal_mode))
+}
+
+// Malla del contenido de un archivo; `path` solo se usa en el error
+fn parse_mesh(contents: &str, path: &str) -> Result<Mesh, ObjError> {
+    parse_obj(contents).map_err(|(line, text, reason)| ObjError::Parse {
+        path: path.to_string(),
+        line,
+        text,
+        reason,
+    })
+}
+
+fn mesh_vertex(mesh: &Mesh, index: u32) -> Vertex {
+    let position = mesh.vertices[index as usize];
+    let normal = mesh.normals.get(index as usize)
+        .cloned()
+        .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+    let tex_coords = mesh.texcoords.get(index as usize)
+        .cloned()
+        .unwrap_or(Vec2::new(0.0, 0.0));
+    Vertex::new(position, normal, tex_coords)
+}
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
+// Buffer de vértices únicos, compartiendo los que tienen la misma posición, normal y uv
+fn build_index_buffer(mesh: &Mesh) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut unique: HashMap<[u32; 8], u32> = HashMap::new();
 
-        for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
+    for &index in &mesh.indices {
+        let vertex = mesh_vertex(mesh, index);
+        let key = [
+            vertex.position.x.to_bits(), vertex.position.y.to_bits(), vertex.position.z.to_bits(),
+            vertex.normal.x.to_bits(), vertex.normal.y.to_bits(), vertex.normal.z.to_bits(),
+            vertex.tex_coords.x.to_bits(), vertex.tex_coords.y.to_bits(),
+        ];
+        let shared = *unique.entry(key).or_insert_with(|| {
+            vertices.push(vertex);
+            (vertices.len() - 1) as u32
+        });
+        indices.push(shared);
+    }
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+    (vertices, indices)
+}
+
+fn has_normal(mesh: &Mesh, index: usize) -> bool {
+    mesh.normals.get(index).is_some_and(|normal| normal.magnitude_squared() > f32::EPSILON)
+}
+
+// Normal de un triángulo sin normalizar: su longitud es el doble del área, lo que sirve de peso
+fn face_normal(mesh: &Mesh, triangle: &[u32]) -> Vec3 {
+    let a = mesh.vertices[triangle[0] as usize];
+    let b = mesh.vertices[triangle[1] as usize];
+    let c = mesh.vertices[triangle[2] as usize];
+    (b - a).cross(&(c - a))
+}
+
+fn normalize_or_up(normal: Vec3) -> Vec3 {
+    if normal.magnitude_squared() > f32::EPSILON { normal.normalize() } else { Vec3::new(0.0, 1.0, 0.0) }
+}
+
+// Completa las normales ausentes (archivo sin `vn` o caras que no las referencian);
+// las normales que sí vienen en el archivo se conservan
+fn generate_missing_normals(mut mesh: Mesh, mode: NormalMode) -> Mesh {
+    let vertex_count = mesh.vertices.len();
+    if (0..vertex_count).all(|i| has_normal(&mesh, i)) {
+        return mesh;
+    }
+
+    match mode {
+        NormalMode::Smooth => {
+            let mut accumulated = vec![Vec3::new(0.0, 0.0, 0.0); vertex_count];
+            for triangle in mesh.indices.chunks_exact(3) {
+                let normal = face_normal(&mesh, triangle);
+                for &index in triangle {
+                    accumulated[index as usize] += normal;
+                }
+            }
+            mesh.normals = (0..vertex_count)
+                .map(|i| if has_normal(&mesh, i) { mesh.normals[i] } else { normalize_or_up(accumulated[i]) })
+                .collect();
+            mesh
+        }
+        NormalMode::Flat => {
+            // Cada triángulo recibe vértices propios para que la normal de la cara no se mezcle
+            let mut flat = Mesh { vertices: Vec::new(), normals: Vec::new(), texcoords: Vec::new(), indices: Vec::new() };
+            for triangle in mesh.indices.chunks_exact(3) {
+                let normal = normalize_or_up(face_normal(&mesh, triangle));
+                for &index in triangle {
+                    let index = index as usize;
+                    flat.indices.push(flat.vertices.len() as u32);
+                    flat.vertices.push(mesh.vertices[index]);
+                    flat.normals.push(if has_normal(&mesh, index) { mesh.normals[index] } else { normal });
+                    flat.texcoords.push(mesh.texcoords.get(index).cloned().unwrap_or(Vec2::new(0.0, 0.0)));
+                }
             }
+            flat
         }
+    }
+}
+
+// Un vértice está en un polo si su distancia al eje y es despreciable frente a su distancia al origen
+const POLE_EPSILON: f32 = 1e-6;
+
+// Ajusta las UV de una malla con u = longitud para que la interpolación entre vértices no las rompa.
+// Un triángulo que cruza la costura (u salta de casi 1 a casi 0) haría un barrido por toda la
+// textura: sus vértices de u chica pasan a u + 1. En los polos la longitud no está definida y un
+// único u tuerce las caras vecinas en espiral: cada cara recibe su propio vértice de polo con el
+// promedio de u de sus otros vértices. Los vértices cambiados se duplican, así no afectan a otras caras
+fn fix_spherical_uvs(mut mesh: Mesh) -> Mesh {
+    let is_pole = |position: Vec3| position.x * position.x + position.z * position.z <= POLE_EPSILON * position.magnitude_squared();
+    let mut duplicates: HashMap<(u32, u32), u32> = HashMap::new();
+    let mut indices = Vec::with_capacity(mesh.indices.len());
 
-        vertices
+    for triangle in mesh.indices.chunks_exact(3) {
+        let mut u: Vec<f32> = triangle.iter().map(|&index| mesh.texcoords[index as usize].x).collect();
+        let span = u.iter().copied().fold(f32::MIN, f32::max) - u.iter().copied().fold(f32::MAX, f32::min);
+        if span > 0.5 {
+            u.iter_mut().filter(|u| **u < 0.5).for_each(|u| *u += 1.0);
+        }
+        let poles: Vec<bool> = triangle.iter().map(|&index| is_pole(mesh.vertices[index as usize])).collect();
+        let others: Vec<f32> = (0..3).filter(|&i| !poles[i]).map(|i| u[i]).collect();
+        if !others.is_empty() {
+            let average = others.iter().sum::<f32>() / others.len() as f32;
+            (0..3).filter(|&i| poles[i]).for_each(|i| u[i] = average);
+        }
+
+        for (corner, &index) in triangle.iter().enumerate() {
+            let original = mesh.texcoords[index as usize];
+            if u[corner] == original.x {
+                indices.push(index);
+                continue;
+            }
+            let duplicate = *duplicates.entry((index, u[corner].to_bits())).or_insert_with(|| {
+                mesh.vertices.push(mesh.vertices[index as usize]);
+                mesh.normals.push(mesh.normals[index as usize]);
+                mesh.texcoords.push(Vec2::new(u[corner], original.y));
+                (mesh.vertices.len() - 1) as u32
+            });
+            indices.push(duplicate);
+        }
     }
-}
\ No newline at end of file
+
+    mesh.indices = indices;
+    mesh
+}
+
+// Error de una línea: número (desde 1), texto de la línea y motivo
+type LineError = (usize, String, String);
+
+// Lee las sentencias v, vt, vn, f y l; el resto (o, g, s, usemtl, mtllib, ...) se ignora.
+// Cada combinación distinta posición/uv/normal de las caras se convierte en un vértice de la malla
+fn parse_obj(contents: &str) -> Result<Mesh, LineError> {
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut texcoords: Vec<Vec2> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+
+    let mut mesh = Mesh { vertices: Vec::new(), normals: Vec::new(), texcoords: Vec::new(), indices: Vec::new() };
+    let mut unique: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+
+    for (number, raw_line) in contents.lines().enumerate() {
+        let line = raw_line.trim();
+        let error = |reason: &str| (number + 1, line.to_string(), reason.to_string());
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else { continue };
+        let values: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let v = parse_floats(&values, 3).ok_or_else(|| error("posición inválida"))?;
+                positions.push(Vec3::new(v[0], v[1], v[2]));
+            }
+            "vt" => {
+                let t = parse_floats(&values, 1).ok_or_else(|| error("coordenada de textura inválida"))?;
+                texcoords.push(Vec2::new(t[0], 1.0 - t.get(1).copied().unwrap_or(0.0)));
+            }
+            "vn" => {
+                let n = parse_floats(&values, 3).ok_or_else(|| error("normal inválida"))?;
+                normals.push(Vec3::new(n[0], n[1], n[2]));
+            }
+            "f" | "l" => {
+                let minimum = if keyword == "f" { 3 } else { 2 };
+                if values.len() < minimum {
+                    return Err(error(&format!("se necesitan al menos {} vértices", minimum)));
+                }
+                let mut corners = Vec::with_capacity(values.len());
+                for corner in &values {
+                    let key = parse_corner(corner, positions.len(), texcoords.len(), normals.len())
+                        .map_err(|reason| error(&reason))?;
+                    let index = *unique.entry(key).or_insert_with(|| {
+                        let (position, texcoord, normal) = key;
+                        mesh.vertices.push(positions[position]);
+                        mesh.texcoords.push(texcoord.map_or(Vec2::new(0.0, 0.0), |t| texcoords[t]));
+                        // Sin normal: vector nulo, que generate_missing_normals completa después
+                        mesh.normals.push(normal.map_or(Vec3::new(0.0, 0.0, 0.0), |n| normals[n]));
+                        (mesh.vertices.len() - 1) as u32
+                    });
+                    corners.push(index);
+                }
+                if keyword == "f" {
+                    // Cuadriláteros y n-gonos se triangulan en abanico desde el primer vértice
+                    for k in 1..corners.len() - 1 {
+                        mesh.indices.extend([corners[0], corners[k], corners[k + 1]]);
+                    }
+                } else {
+                    // Cada segmento de una línea queda como triángulo degenerado: no genera fragmentos
+                    // pero mantiene la lista de vértices de modelos como Navesita.obj sin cambios
+                    for pair in corners.windows(2) {
+                        mesh.indices.extend([pair[0], pair[1], pair[1]]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+fn parse_floats(values: &[&str], required: usize) -> Option<Vec<f32>> {
+    let parsed: Vec<f32> = values.iter().map(|value| value.parse().ok()).collect::<Option<_>>()?;
+    if parsed.len() < required { None } else { Some(parsed) }
+}
+
+// Un vértice de cara: "v", "v/vt", "v//vn" o "v/vt/vn"; los índices negativos cuentan desde el final
+fn parse_corner(corner: &str, positions: usize, texcoords: usize, normals: usize) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let mut parts = corner.split('/');
+    let position = resolve_index(parts.next(), positions, "posición")?
+        .ok_or_else(|| format!("falta el índice de posición en '{}'", corner))?;
+    let texcoord = resolve_index(parts.next(), texcoords, "coordenada de textura")?;
+    let normal = resolve_index(parts.next(), normals, "normal")?;
+    Ok((position, texcoord, normal))
+}
+
+fn resolve_index(part: Option<&str>, count: usize, kind: &str) -> Result<Option<usize>, String> {
+    let part = match part {
+        Some(part) if !part.is_empty() => part,
+        _ => return Ok(None),
+    };
+    let index: i64 = part.parse().map_err(|_| format!("índice de {} inválido '{}'", kind, part))?;
+    let resolved = if index > 0 { index - 1 } else { count as i64 + index };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("índice de {} fuera de rango ({})", kind, index));
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Malla de un OBJ en memoria, con las normales que faltan generadas como al cargar un archivo
+    fn parse(contents: &str) -> Obj {
+        parse_with_normals(contents, NormalMode::Smooth)
+    }
+
+    // Modelo de tests/fixtures cargado con `Obj::load`, como lo hace el programa
+    fn fixture(name: &str) -> Obj {
+        Obj::load(&format!("tests/fixtures/{}", name), NormalMode::Smooth).unwrap()
+    }
+
+    const SQUARE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+";
+
+    // Tetraedro sin `vn`, con las caras en sentido antihorario vistas desde afuera
+    const TETRAHEDRON: &str = "tests/fixtures/tetrahedron.obj";
+
+    fn parse_with_normals(contents: &str, mode: NormalMode) -> Obj {
+        Obj::from_mesh(generate_missing_normals(parse_mesh(contents, "prueba.obj").unwrap(), mode))
+    }
+
+    #[test]
+    fn smooth_normals_are_unit_and_point_outward() {
+        let obj = Obj::load(TETRAHEDRON, NormalMode::Smooth).unwrap();
+        let (vertices, indices) = obj.get_indexed();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 12);
+        for vertex in vertices {
+            assert!((vertex.normal.magnitude() - 1.0).abs() < 1e-5);
+            assert!(vertex.normal.dot(&vertex.position) > 0.0);
+        }
+    }
+
+    #[test]
+    fn flat_normals_equal_the_face_normal() {
+        let obj = Obj::load(TETRAHEDRON, NormalMode::Flat).unwrap();
+        let (vertices, indices) = obj.get_indexed();
+        // Cada cara tiene sus propios vértices
+        assert_eq!(vertices.len(), 12);
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| &vertices[triangle[corner] as usize]);
+            let face = (b.position - a.position).cross(&(c.position - a.position)).normalize();
+            for vertex in [a, b, c] {
+                assert!((vertex.normal - face).magnitude() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        match Obj::load("tests/fixtures/no_existe.obj", NormalMode::Flat) {
+            Err(ObjError::Io(path, err)) => {
+                assert_eq!(path, "tests/fixtures/no_existe.obj");
+                assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+            }
+            _ => panic!("se esperaba un error de lectura"),
+        }
+    }
+
+    #[test]
+    fn normals_from_the_file_are_kept() {
+        let obj = parse_with_normals(&format!("{}vn 0 0 -1\nf 1//1 2//1 3//1\n", SQUARE), NormalMode::Flat);
+        assert!(obj.get_indexed().0.iter().all(|vertex| vertex.normal == Vec3::new(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn sphere_counts_follow_stacks_and_slices() {
+        for (stacks, slices) in [(2, 3), (12, 16), (48, 64)] {
+            let sphere = Obj::sphere(stacks, slices);
+            let (vertices, indices) = sphere.get_indexed();
+            // Un triángulo por división en cada casquete polar y dos en las bandas intermedias
+            assert_eq!(indices.len(), 6 * slices * (stacks - 1));
+            // Los anillos interiores repiten el vértice de la costura; cada cara polar tiene su propio polo
+            assert_eq!(vertices.len(), (stacks - 1) * (slices + 1) + 2 * slices);
+            assert!(indices.iter().all(|&index| (index as usize) < vertices.len()));
+        }
+    }
+
+    #[test]
+    fn sphere_vertices_are_on_the_unit_sphere() {
+        let sphere = Obj::sphere(24, 32);
+        assert!((sphere.bounding_radius() - 1.0).abs() < 1e-5);
+        for vertex in sphere.get_indexed().0 {
+            assert!((vertex.position.magnitude() - 1.0).abs() < 1e-5);
+            assert_eq!(vertex.normal, vertex.position);
+            assert!((0.0..=1.0).contains(&vertex.tex_coords.x) && (0.0..=1.0).contains(&vertex.tex_coords.y));
+        }
+    }
+
+    #[test]
+    fn sphere_uvs_cover_the_texture_across_the_seam() {
+        let (stacks, slices) = (24, 32);
+        let sphere = Obj::sphere(stacks, slices);
+        let vertices = sphere.get_indexed().0;
+        let range = |coordinate: fn(&Vertex) -> f32| {
+            vertices.iter().map(coordinate).fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)))
+        };
+        // u da la vuelta completa y v va de un polo al otro
+        assert_eq!(range(|vertex| vertex.tex_coords.x), (0.0, 1.0));
+        assert_eq!(range(|vertex| vertex.tex_coords.y), (0.0, 1.0));
+
+        // La costura repite el vértice de cada anillo interior, una vez con u = 0 y otra con u = 1
+        let seam: Vec<&Vertex> = vertices.iter().filter(|vertex| vertex.tex_coords.y > 0.0 && vertex.tex_coords.y < 1.0).collect();
+        for stack in 1..stacks {
+            let v = stack as f32 / stacks as f32;
+            let at = |u: f32| seam.iter().find(|vertex| vertex.tex_coords == Vec2::new(u, v)).map(|vertex| vertex.position);
+            let (start, end) = (at(0.0).expect("falta u = 0 en la costura"), at(1.0).expect("falta u = 1 en la costura"));
+            assert!((start - end).magnitude() < 1e-5, "anillo {}", stack);
+        }
+    }
+
+    #[test]
+    fn quad_is_split_in_two_triangles() {
+        let obj = fixture("quad.obj");
+        let (vertices, indices) = obj.get_indexed();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, &[0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn ngon_is_fanned_from_its_first_corner() {
+        let obj = fixture("ngon.obj");
+        let (vertices, indices) = obj.get_indexed();
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(indices, &[0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    }
+
+    #[test]
+    fn negative_indices_count_from_the_end() {
+        let relative = fixture("negative_indices.obj");
+        let absolute = parse(&format!("{}vt 0 0\nvt 1 0\nvt 1 1\nf 1/1 2/2 3/3\n", SQUARE));
+        let (relative_vertices, relative_indices) = relative.get_indexed();
+        let (absolute_vertices, absolute_indices) = absolute.get_indexed();
+        assert_eq!(relative_indices, absolute_indices);
+        assert_eq!(relative_vertices.len(), 3);
+        for (a, b) in relative_vertices.iter().zip(absolute_vertices) {
+            assert_eq!(a.position, b.position);
+            assert_eq!(a.tex_coords, b.tex_coords);
+        }
+    }
+
+    #[test]
+    fn faces_without_texcoords_get_zero_uv() {
+        let obj = fixture("no_texcoords.obj");
+        let (vertices, indices) = obj.get_indexed();
+        assert_eq!(indices.len(), 6);
+        // Los vértices 1 y 3 aparecen con y sin normal del archivo, pero las generadas coinciden
+        assert_eq!(vertices.len(), 4);
+        assert!(vertices.iter().all(|vertex| vertex.tex_coords == Vec2::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn unknown_statements_are_skipped() {
+        let obj = parse(&format!("mtllib nave.mtl\no Cuadrado\n{}usemtl Metal\ns off\ng lado\nf 1 2 3 4\n", SQUARE));
+        assert_eq!(obj.get_indexed().1.len(), 6);
+    }
+
+    #[test]
+    fn out_of_range_index_reports_its_line() {
+        match Obj::load("tests/fixtures/out_of_range.obj", NormalMode::Smooth) {
+            Err(ObjError::Parse { path, line, text, .. }) => {
+                assert_eq!(path, "tests/fixtures/out_of_range.obj");
+                assert_eq!(line, 6);
+                assert_eq!(text, "f 1 2 5");
+            }
+            _ => panic!("se esperaba un error de índice fuera de rango"),
+        }
+        assert!(matches!(parse_mesh("v 0 0 0\nf 1 1 -2\n", "prueba.obj"), Err(ObjError::Parse { line: 2, .. })));
+    }
+
+    // La nave se cargaba con tobj; el cargador propio da los mismos vértices únicos, índices y radio
+    #[test]
+    fn spaceship_model_loads_as_before() {
+        let obj = Obj::load("assets/models/Navesita.obj", NormalMode::Smooth).unwrap();
+        let (vertices, indices) = obj.get_indexed();
+        assert_eq!(vertices.len(), 998);
+        assert_eq!(indices.len(), 1977);
+        assert!((obj.bounding_radius() - 8.179415).abs() < 1e-5);
+    }
+}
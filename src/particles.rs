@@ -0,0 +1,118 @@
+// Escape de la nave: partículas que nacen en la tobera mientras hay empuje hacia adelante,
+// salen hacia atrás y se apagan y achican con la edad. Se suman a la imagen con emisión para el bloom
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Uniforms;
+
+const MAX_PARTICLES: usize = 512;
+const EMISSION_RATE: f32 = 240.0;    // Partículas por segundo con empuje máximo
+const LIFETIME: f32 = 0.6;           // Segundos
+const EJECT_SPEED: f32 = 3.0;        // Velocidad de salida hacia atrás, en unidades por segundo
+const SPREAD: f32 = 0.3;             // Dispersión aleatoria de la velocidad de salida
+const START_SIZE: f32 = 0.015;       // Radio inicial en unidades del mundo
+const EMISSION_COLOR: [f32; 3] = [0.45, 0.75, 1.0];
+const EMISSION_STRENGTH: f32 = 0.8;  // Fracción del color que además va al buffer emisivo
+const SEED: u64 = 7;
+
+// Tobera en espacio del modelo de la nave (la nave mira hacia +z), justo detrás del casco
+const NOZZLE: [f32; 3] = [0.0, 0.3, -6.5];
+
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    spawn_budget: f32,   // Fracción de partícula acumulada entre frames
+    rng: StdRng,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            spawn_budget: 0.0,
+            rng: StdRng::seed_from_u64(SEED),
+        }
+    }
+
+    // Envejece y mueve las partículas; con `thrust` positivo emite nuevas desde la tobera de la nave
+    // descrita por `model` (su matriz de modelo)
+    pub fn update(&mut self, delta_time: f32, model: &Mat4, thrust: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.age += delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+        self.particles.retain(|particle| particle.age < LIFETIME);
+
+        if thrust <= 0.0 {
+            self.spawn_budget = 0.0;
+            return;
+        }
+        self.spawn_budget += EMISSION_RATE * thrust * delta_time;
+
+        let nozzle = (model * Vec4::new(NOZZLE[0], NOZZLE[1], NOZZLE[2], 1.0)).xyz();
+        let backward = (model * Vec4::new(0.0, 0.0, -1.0, 0.0)).xyz().normalize();
+        while self.spawn_budget >= 1.0 && self.particles.len() < MAX_PARTICLES {
+            self.spawn_budget -= 1.0;
+            let jitter = Vec3::new(
+                self.rng.gen_range(-1.0..1.0),
+                self.rng.gen_range(-1.0..1.0),
+                self.rng.gen_range(-1.0..1.0),
+            ) * SPREAD;
+            let velocity = backward * EJECT_SPEED + jitter;
+            // Repartidas dentro del paso para que no salgan en grupos, un frame a la vez
+            let age = self.rng.gen_range(0.0..delta_time.max(f32::EPSILON));
+            self.particles.push(Particle { position: nozzle + velocity * age, velocity, age });
+        }
+        // Con el máximo alcanzado no se acumulan partículas pendientes
+        self.spawn_budget = self.spawn_budget.min(1.0);
+    }
+
+    // Discos aditivos de borde suave con prueba de profundidad (sin escribirla), así los planetas tapan la estela
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let focal = uniforms.projection_matrix[(1, 1)] * framebuffer.height as f32 / 2.0;
+
+        for particle in &self.particles {
+            let clip = view_projection * Vec4::new(particle.position.x, particle.position.y, particle.position.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = uniforms.viewport_matrix * ndc;
+
+            let life = 1.0 - particle.age / LIFETIME;
+            let radius = (START_SIZE * life * focal / clip.w).max(0.5);
+            let brightness = life * life;
+
+            let min_x = (screen.x - radius).floor().max(0.0) as usize;
+            let min_y = (screen.y - radius).floor().max(0.0) as usize;
+            let max_x = ((screen.x + radius).ceil().max(0.0) as usize).min(framebuffer.width);
+            let max_y = ((screen.y + radius).ceil().max(0.0) as usize).min(framebuffer.height);
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let dx = x as f32 + 0.5 - screen.x;
+                    let dy = y as f32 + 0.5 - screen.y;
+                    // Borde suave: más brillo en el centro del disco
+                    let falloff = 1.0 - (dx * dx + dy * dy) / (radius * radius);
+                    if falloff > 0.0 {
+                        let intensity = brightness * falloff;
+                        let color = rgb(EMISSION_COLOR, intensity);
+                        let emission = rgb(EMISSION_COLOR, intensity * EMISSION_STRENGTH);
+                        framebuffer.add_point(x, y, clip.w, color, emission);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn rgb(color: [f32; 3], intensity: f32) -> u32 {
+    let channel = |value: f32| ((value * intensity).clamp(0.0, 1.0) * 255.0).round() as u32;
+    (channel(color[0]) << 16) | (channel(color[1]) << 8) | channel(color[2])
+}
@@ -0,0 +1,797 @@
+// Post-procesamiento: cada efecto trabaja sobre el frame ya rasterizado y el pipeline los aplica
+// en orden una vez por frame. Cada uno puede desactivarse en tiempo de ejecución (F12 recorre las combinaciones)
+use crate::color::{srgb_to_linear, linear_to_srgb, tone_map_aces};
+use crate::framebuffer::Framebuffer;
+use crate::haze;
+use crate::lens_flare;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::material::ShaderKind;
+use crate::solar_system::{CelestialBody, SolarSystem};
+use crate::renderer::{projected_radius, world_to_screen, Uniforms};
+
+// Lo que un efecto puede leer del frame actual
+pub struct FrameContext<'a> {
+    pub uniforms: &'a Uniforms<'a>,
+    pub solar_system: &'a SolarSystem,
+    pub exposure: f32,         // Compensación manual (RePág / AvPág) sobre la exposición automática
+    pub delta_time: f32,       // Segundos reales desde el frame anterior, para la adaptación de la exposición
+    pub sun_visibility: f32,   // Fracción del disco del Sol que se ve según el z-buffer (solo lo opaco escribe profundidad)
+    pub motion_blur: bool,     // Desenfoque de movimiento activado con N
+    pub focus: Option<(f32, f32)>, // Profundidad de campo (J): distancia de enfoque y medio ancho de la zona nítida
+    pub ship_radius: f32,      // Radio de la nave en el mundo: sus pixeles se mueven con ella y no con el fondo
+}
+
+// Muestras por lado de la grilla con que se estima qué fracción del Sol se ve
+const VISIBILITY_SAMPLES: usize = 7;
+
+
+pub trait PostEffect {
+    // Nombre corto para el HUD
+    fn name(&self) -> &'static str;
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext);
+
+    // Se llama en lugar de `apply` cuando el efecto está desactivado
+    fn disabled(&mut self, _framebuffer: &mut Framebuffer) {}
+}
+
+pub struct PostPipeline {
+    effects: Vec<Box<dyn PostEffect>>,
+    step: usize,   // Posición en el recorrido de F12: 0 todos activos, 1..=n uno apagado, n + 1 ninguno
+}
+
+impl PostPipeline {
+    pub fn new(effects: Vec<Box<dyn PostEffect>>) -> Self {
+        PostPipeline { effects, step: 0 }
+    }
+
+    // Bit i encendido = efecto i activo
+    fn enabled_mask(&self) -> u32 {
+        let all = (1u32 << self.effects.len()) - 1;
+        match self.step {
+            0 => all,
+            step if step <= self.effects.len() => all & !(1 << (step - 1)),
+            _ => 0,
+        }
+    }
+
+    // Todos activos, después cada efecto apagado por separado, después ninguno y de vuelta al principio
+    pub fn cycle(&mut self) {
+        self.step = (self.step + 1) % (self.effects.len() + 2);
+    }
+
+    // Nombres de los efectos apagados, para avisar en el HUD
+    pub fn disabled_names(&self) -> Vec<&'static str> {
+        let mask = self.enabled_mask();
+        self.effects.iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) == 0)
+            .map(|(_, effect)| effect.name())
+            .collect()
+    }
+
+    pub fn run(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let mask = self.enabled_mask();
+        for (i, effect) in self.effects.iter_mut().enumerate() {
+            if mask & (1 << i) != 0 {
+                effect.apply(framebuffer, context);
+            } else {
+                effect.disabled(framebuffer);
+            }
+        }
+    }
+}
+
+// Resplandor del polvo cerca del Sol, antes del tone mapping para que se comprima con el resto
+pub struct SunHaze;
+
+impl PostEffect for SunHaze {
+    fn name(&self) -> &'static str {
+        "BRUMA"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        if let Some(sun) = find_sun(context.solar_system) {
+            haze::apply(framebuffer, context.uniforms, sun.position, sun.scale);
+        }
+    }
+}
+
+// Destello de lente del Sol, sumado después del tone mapping para que no lo apague
+pub struct LensFlare;
+
+impl PostEffect for LensFlare {
+    fn name(&self) -> &'static str {
+        "DESTELLO"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let Some(sun) = find_sun(context.solar_system) else { return };
+        if let Some(screen) = world_to_screen(sun.position, context.uniforms) {
+            lens_flare::draw(framebuffer, screen, context.sun_visibility);
+        }
+    }
+}
+
+// Fracción de una grilla de muestras sobre el disco proyectado del Sol que está en pantalla y sin nada
+// delante en el z-buffer. Varía de a poco cuando el Sol sale del cuadro o un planeta pasa por delante
+pub fn sun_visibility(framebuffer: &Framebuffer, uniforms: &Uniforms, solar_system: &SolarSystem) -> f32 {
+    let Some(sun) = find_sun(solar_system) else { return 0.0 };
+    let Some(screen) = world_to_screen(sun.position, uniforms) else { return 0.0 };
+    if screen.z <= 0.0 {
+        return 0.0;
+    }
+    let radius_px = projected_radius(sun.position, sun.scale, uniforms, framebuffer.height);
+    let spread = (radius_px * 0.9).max(1.0);
+    // Cualquier cosa más cerca que la superficie frontal del Sol lo tapa
+    let front = screen.z - sun.scale * 1.05;
+
+    let (mut samples, mut visible) = (0, 0);
+    for i in 0..VISIBILITY_SAMPLES {
+        for j in 0..VISIBILITY_SAMPLES {
+            let u = i as f32 / (VISIBILITY_SAMPLES - 1) as f32 * 2.0 - 1.0;
+            let v = j as f32 / (VISIBILITY_SAMPLES - 1) as f32 * 2.0 - 1.0;
+            if u * u + v * v > 1.0 {
+                continue;
+            }
+            samples += 1;
+            let x = screen.x + u * spread;
+            let y = screen.y + v * spread;
+            if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32 {
+                continue;
+            }
+            if framebuffer.zbuffer[y as usize * framebuffer.width + x as usize] >= front {
+                visible += 1;
+            }
+        }
+    }
+    visible as f32 / samples as f32
+}
+
+fn find_sun(solar_system: &SolarSystem) -> Option<&CelestialBody> {
+    solar_system.bodies.iter().find(|body| body.shader == ShaderKind::Sun)
+}
+
+// El buffer emisivo guarda colores empaquetados 0xRRGGBB, así que el desenfoque
+// separa cada pixel en sus canales R, G y B y filtra cada uno por separado
+fn gaussian_blur(buffer: &mut [u32], width: usize, height: usize, kernel_size: usize, sigma: f32) {
+    let gaussian_kernel = create_gaussian_kernel(kernel_size, sigma);
+    let half = (gaussian_kernel.len() / 2) as i32;
+
+    let mut channels: Vec<[f32; 3]> = buffer.iter().map(|&pixel| unpack_rgb(pixel)).collect();
+    let mut temp = vec![[0.0f32; 3]; width * height];
+
+    // Pasada horizontal
+    for y in 0..height {
+        for x in 0..width {
+            let mut filtered = [0.0f32; 3];
+            for (k, weight) in gaussian_kernel.iter().enumerate() {
+                let sample_x = x as i32 + k as i32 - half;
+                if sample_x >= 0 && sample_x < width as i32 {
+                    let sample = channels[sample_x as usize + y * width];
+                    for c in 0..3 {
+                        filtered[c] += sample[c] * weight;
+                    }
+                }
+            }
+            temp[x + y * width] = filtered;
+        }
+    }
+
+    // Pasada vertical
+    for x in 0..width {
+        for y in 0..height {
+            let mut filtered = [0.0f32; 3];
+            for (k, weight) in gaussian_kernel.iter().enumerate() {
+                let sample_y = y as i32 + k as i32 - half;
+                if sample_y >= 0 && sample_y < height as i32 {
+                    let sample = temp[x + sample_y as usize * width];
+                    for c in 0..3 {
+                        filtered[c] += sample[c] * weight;
+                    }
+                }
+            }
+            channels[x + y * width] = filtered;
+        }
+    }
+
+    for (pixel, rgb) in buffer.iter_mut().zip(channels.iter()) {
+        *pixel = pack_rgb(*rgb);
+    }
+}
+
+// El bloom se desenfoca a menor resolución: BLOOM_DOWNSAMPLE pixeles por lado forman uno del buffer
+// reducido, y el kernel se achica en la misma proporción para que el halo conserve su tamaño en pantalla
+const BLOOM_DOWNSAMPLE: usize = 4;
+const BLOOM_KERNEL_SIZE: usize = 6;
+const BLOOM_SIGMA: f32 = 1.0;
+
+// Bloom: los pixeles emisivos se desenfocan y el tone mapping los suma como halo. Si no se
+// escribió ninguno en el frame no hay nada que desenfocar
+pub struct Bloom;
+
+impl PostEffect for Bloom {
+    fn name(&self) -> &'static str {
+        "BLOOM"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &FrameContext) {
+        if framebuffer.has_emission() {
+            apply_bloom(&mut framebuffer.emissive_buffer, framebuffer.width, framebuffer.height);
+        }
+    }
+
+    // Sin bloom el tone mapping no debe sumar la emisión cruda
+    fn disabled(&mut self, framebuffer: &mut Framebuffer) {
+        framebuffer.emissive_count = 0;
+    }
+}
+
+// Reduce el buffer emisivo, lo desenfoca y lo vuelve a ampliar sobre el mismo buffer, listo para
+// sumarse en el tone mapping
+fn apply_bloom(emissive: &mut [u32], width: usize, height: usize) {
+    let (mut small, small_width, small_height) = downsample(emissive, width, height, BLOOM_DOWNSAMPLE);
+    gaussian_blur(&mut small, small_width, small_height, BLOOM_KERNEL_SIZE, BLOOM_SIGMA);
+    upsample(&small, small_width, small_height, emissive, width, height, BLOOM_DOWNSAMPLE);
+}
+
+// Promedia bloques de `factor` x `factor` pixeles; devuelve el buffer reducido y su tamaño
+fn downsample(buffer: &[u32], width: usize, height: usize, factor: usize) -> (Vec<u32>, usize, usize) {
+    let small_width = width.div_ceil(factor);
+    let small_height = height.div_ceil(factor);
+
+    let mut small = vec![0u32; small_width * small_height];
+    for sy in 0..small_height {
+        for sx in 0..small_width {
+            let mut sum = [0.0f32; 3];
+            let mut count = 0.0;
+            for y in (sy * factor)..((sy + 1) * factor).min(height) {
+                for x in (sx * factor)..((sx + 1) * factor).min(width) {
+                    let rgb = unpack_rgb(buffer[x + y * width]);
+                    for c in 0..3 {
+                        sum[c] += rgb[c];
+                    }
+                    count += 1.0;
+                }
+            }
+            small[sx + sy * small_width] = pack_rgb(sum.map(|value| value / count));
+        }
+    }
+    (small, small_width, small_height)
+}
+
+// Amplía un buffer reducido con `downsample` a `output` con interpolación bilineal
+fn upsample(small: &[u32], small_width: usize, small_height: usize, output: &mut [u32], width: usize, height: usize, factor: usize) {
+    let small: Vec<[f32; 3]> = small.iter().map(|&pixel| unpack_rgb(pixel)).collect();
+
+    // Cada pixel de salida toma los cuatro pixeles reducidos más cercanos a su centro;
+    // las columnas de origen y sus pesos son iguales en todas las filas
+    let scale = 1.0 / factor as f32;
+    let source = |position: usize, size: usize| {
+        let f = ((position as f32 + 0.5) * scale - 0.5).clamp(0.0, (size - 1) as f32);
+        let low = f as usize;
+        (low, (low + 1).min(size - 1), f - low as f32)
+    };
+    let columns: Vec<(usize, usize, f32)> = (0..width).map(|x| source(x, small_width)).collect();
+    for y in 0..height {
+        let (y0, y1, ty) = source(y, small_height);
+        for (x, &(x0, x1, tx)) in columns.iter().enumerate() {
+            let [a, b, c, d] = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(sx, sy)| small[sx + sy * small_width]);
+            // La mayor parte del buffer emisivo está en negro: se evita el empaquetado
+            if a == [0.0; 3] && b == [0.0; 3] && c == [0.0; 3] && d == [0.0; 3] {
+                output[x + y * width] = 0;
+                continue;
+            }
+            let mut rgb = [0.0f32; 3];
+            for i in 0..3 {
+                let top = a[i] + (b[i] - a[i]) * tx;
+                let bottom = c[i] + (d[i] - c[i]) * tx;
+                rgb[i] = top + (bottom - top) * ty;
+            }
+            output[x + y * width] = pack_rgb(rgb);
+        }
+    }
+}
+
+// Kernel gaussiano normalizado (suma 1.0), en punto flotante para no perder precisión con sigma pequeño
+fn create_gaussian_kernel(size: usize, sigma: f32) -> Vec<f32> {
+    let mean = (size as f32 - 1.0) / 2.0;
+    let mut kernel: Vec<f32> = (0..size)
+        .map(|x| {
+            let offset = x as f32 - mean;
+            (-(offset * offset) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let sum: f32 = kernel.iter().sum();
+    for weight in kernel.iter_mut() {
+        *weight /= sum;
+    }
+
+    kernel
+}
+
+fn unpack_rgb(color: u32) -> [f32; 3] {
+    [
+        ((color >> 16) & 0xFF) as f32,
+        ((color >> 8) & 0xFF) as f32,
+        (color & 0xFF) as f32,
+    ]
+}
+
+fn pack_rgb(rgb: [f32; 3]) -> u32 {
+    let r = rgb[0].round().clamp(0.0, 255.0) as u32;
+    let g = rgb[1].round().clamp(0.0, 255.0) as u32;
+    let b = rgb[2].round().clamp(0.0, 255.0) as u32;
+    (r << 16) | (g << 8) | b
+}
+
+// Resolución de la tabla de codificación gamma para los valores ya tonemapeados
+const GAMMA_LUT_SIZE: usize = 4096;
+
+// Exposición automática, como una cámara: la luminancia media logarítmica del frame (medida en una
+// grilla de muestras) se lleva hacia la luminancia objetivo, solo en parte (exponente de respuesta)
+// para que un frame casi vacío y uno con el Sol no terminen igual de brillantes. La exposición se
+// adapta de forma exponencial con esta constante de tiempo (en ~1 segundo queda casi asentada) y se
+// acota para que el cielo negro no la suba hasta volver visible el ruido, ni el Sol la baje hasta apagar los planetas
+const AUTO_EXPOSURE_TARGET: f32 = 0.004;
+const AUTO_EXPOSURE_RESPONSE: f32 = 0.5;
+const AUTO_EXPOSURE_ADAPTATION: f32 = 0.3;
+const AUTO_EXPOSURE_MIN: f32 = 0.4;
+const AUTO_EXPOSURE_MAX: f32 = 1.6;
+const AUTO_EXPOSURE_GRID: usize = 32;          // Muestras por fila de la grilla de medición
+const AUTO_EXPOSURE_FLOOR: f32 = 0.001;        // Luminancia mínima de una muestra en el promedio (el negro no cuenta como 0)
+
+// Composición final en espacio lineal: suma el bloom sin recortar, aplica la exposición,
+// el tone mapping ACES y vuelve a codificar en gamma 2.2 al escribir cada pixel.
+// Las conversiones se tabulan una sola vez para no evaluar powf por canal y pixel
+pub struct ToneMap {
+    to_linear: Vec<f32>,
+    to_srgb: Vec<f32>,
+    adapted_exposure: Option<f32>, // Exposición automática actual; None hasta el primer frame, que la toma directo
+}
+
+impl ToneMap {
+    pub fn new() -> Self {
+        ToneMap {
+            to_linear: (0..256).map(|v| srgb_to_linear(v as f32 / 255.0)).collect(),
+            to_srgb: (0..GAMMA_LUT_SIZE)
+                .map(|i| linear_to_srgb(i as f32 / (GAMMA_LUT_SIZE - 1) as f32) * 255.0)
+                .collect(),
+            adapted_exposure: None,
+        }
+    }
+
+    // Color lineal de un pixel con su bloom sumado, antes de la exposición
+    fn linear(&self, base: u32, glow: u32, bloom_strength: f32) -> [f32; 3] {
+        let (base, glow) = (unpack_rgb(base), unpack_rgb(glow));
+        [0, 1, 2].map(|c| self.to_linear[base[c] as usize] + self.to_linear[glow[c] as usize] * bloom_strength)
+    }
+
+    // Media geométrica de la luminancia sobre una grilla de muestras repartida en el frame
+    fn log_average_luminance(&self, framebuffer: &Framebuffer, bloom: Option<&Vec<u32>>, bloom_strength: f32) -> f32 {
+        let step = (framebuffer.width / AUTO_EXPOSURE_GRID).max(1);
+        let mut sum = 0.0;
+        let mut count = 0;
+        for y in (step / 2..framebuffer.height).step_by(step) {
+            for x in (step / 2..framebuffer.width).step_by(step) {
+                let index = y * framebuffer.width + x;
+                let glow = bloom.map_or(0, |bloom| bloom[index]);
+                let [r, g, b] = self.linear(framebuffer.buffer[index], glow, bloom_strength);
+                let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                sum += luminance.max(AUTO_EXPOSURE_FLOOR).ln();
+                count += 1;
+            }
+        }
+        if count == 0 { AUTO_EXPOSURE_TARGET } else { (sum / count as f32).exp() }
+    }
+
+    // Lleva la exposición automática hacia la que dejaría el frame en la luminancia objetivo
+    fn adapt_exposure(&mut self, luminance: f32, delta_time: f32) -> f32 {
+        let target = (AUTO_EXPOSURE_TARGET / luminance).powf(AUTO_EXPOSURE_RESPONSE).clamp(AUTO_EXPOSURE_MIN, AUTO_EXPOSURE_MAX);
+        let exposure = match self.adapted_exposure {
+            // En escala logarítmica, así aclarar y oscurecer llevan el mismo tiempo
+            Some(current) => {
+                let blend = 1.0 - (-delta_time / AUTO_EXPOSURE_ADAPTATION).exp();
+                (current.ln() + (target.ln() - current.ln()) * blend).exp()
+            }
+            None => target,
+        };
+        self.adapted_exposure = Some(exposure);
+        exposure
+    }
+}
+
+impl PostEffect for ToneMap {
+    fn name(&self) -> &'static str {
+        "TONEMAP"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let bloom_strength = 0.8;
+        let bloom = framebuffer.has_emission().then_some(&framebuffer.emissive_buffer);
+        let luminance = self.log_average_luminance(framebuffer, bloom, bloom_strength);
+        let exposure = context.exposure * self.adapt_exposure(luminance, context.delta_time);
+
+        for (i, pixel) in framebuffer.buffer.iter_mut().enumerate() {
+            let glow = bloom.map_or(0, |bloom| bloom[i]);
+            let output = self.linear(*pixel, glow, bloom_strength).map(|linear| {
+                let mapped = tone_map_aces(linear * exposure);
+                self.to_srgb[(mapped * (GAMMA_LUT_SIZE - 1) as f32) as usize]
+            });
+            *pixel = pack_rgb(output);
+        }
+    }
+
+    // Al volver a activarlo la exposición arranca desde el frame de ese momento
+    fn disabled(&mut self, _framebuffer: &mut Framebuffer) {
+        self.adapted_exposure = None;
+    }
+}
+
+// Desenfoque de movimiento: cada pixel se lleva al mundo con su profundidad, se reproyecta con la
+// vista y proyección del frame anterior y se promedia a lo largo del desplazamiento resultante
+const MOTION_BLUR_STRENGTH: f32 = 0.5;      // Fracción del movimiento entre frames que abarca la estela (obturador de 180°)
+const MOTION_BLUR_MAX_LENGTH: f32 = 24.0;   // Largo máximo de la estela en pixeles
+const MOTION_BLUR_MIN_LENGTH: f32 = 0.5;    // Debajo de este largo el pixel queda igual
+const MOTION_BLUR_SAMPLES: usize = 8;
+
+// Guarda la vista-proyección y la posición de la nave de cada frame para el siguiente. Como el
+// efecto corre antes del destello y del texto, esos quedan nítidos
+pub struct MotionBlur {
+    previous: Option<(Mat4, Vec3)>,
+    source: Vec<u32>,
+}
+
+impl MotionBlur {
+    pub fn new() -> Self {
+        MotionBlur { previous: None, source: Vec::new() }
+    }
+}
+
+impl PostEffect for MotionBlur {
+    fn name(&self) -> &'static str {
+        "MOVIMIENTO"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let uniforms = context.uniforms;
+        let ship = context.solar_system.spaceship.position;
+        let previous = self.previous.replace((uniforms.projection_matrix * uniforms.view_matrix, ship));
+        if !context.motion_blur {
+            return;
+        }
+        if let Some((previous_view_projection, previous_ship)) = previous {
+            let ship_motion = (ship, previous_ship - ship, context.ship_radius);
+            apply_motion_blur(framebuffer, &mut self.source, uniforms, &previous_view_projection, ship_motion);
+        }
+    }
+
+    // Sin frames seguidos no hay un frame anterior válido con qué comparar
+    fn disabled(&mut self, _framebuffer: &mut Framebuffer) {
+        self.previous = None;
+    }
+}
+
+// `ship_motion` es la posición actual de la nave, cuánto se movió desde el frame anterior y su radio
+fn apply_motion_blur(
+    framebuffer: &mut Framebuffer,
+    source: &mut Vec<u32>,
+    uniforms: &Uniforms,
+    previous_view_projection: &Mat4,
+    (ship, ship_offset, ship_radius): (Vec3, Vec3, f32),
+) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    source.clear();
+    source.extend_from_slice(&framebuffer.buffer);
+
+    // Igual que en la bruma: el rayo en el mundo es afín en (x, y) de pantalla y, con z = -1 en
+    // espacio de vista, ojo + rayo * profundidad es el punto que guarda el z-buffer
+    let viewport = &uniforms.viewport_matrix;
+    let direction = |x: f32, y: f32| {
+        let ndc_x = (x - viewport[(0, 3)]) / viewport[(0, 0)];
+        let ndc_y = (y - viewport[(1, 3)]) / viewport[(1, 1)];
+        let point = uniforms.inverse_projection * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+        let view = Vec4::new(point.x, point.y, point.z, 0.0) / -point.z;
+        let world = uniforms.inverse_view * view;
+        Vec3::new(world.x, world.y, world.z)
+    };
+    let origin = direction(0.5, 0.5);
+    let step_x = direction(1.5, 0.5) - origin;
+    let step_y = direction(0.5, 1.5) - origin;
+    let eye = uniforms.camera_position;
+    let (max_x, max_y) = ((width - 1) as f32, (height - 1) as f32);
+
+    for y in 0..height {
+        let row = origin + step_y * y as f32;
+        for x in 0..width {
+            let index = y * width + x;
+            let ray = row + step_x * x as f32;
+            let depth = framebuffer.zbuffer[index];
+
+            // Las estrellas y el fondo están en el infinito: solo cuenta la dirección (w = 0)
+            let point = if depth.is_finite() && depth < f32::MAX {
+                let mut world = eye + ray * depth;
+                if (world - ship).magnitude() < ship_radius {
+                    world += ship_offset;
+                }
+                Vec4::new(world.x, world.y, world.z, 1.0)
+            } else {
+                Vec4::new(ray.x, ray.y, ray.z, 0.0)
+            };
+            let clip = previous_view_projection * point;
+            if clip.w <= f32::EPSILON {
+                continue;
+            }
+            let previous_x = viewport[(0, 0)] * clip.x / clip.w + viewport[(0, 3)];
+            let previous_y = viewport[(1, 1)] * clip.y / clip.w + viewport[(1, 3)];
+
+            // Estela centrada en el pixel, con el largo limitado para movimientos bruscos (warps, teletransportes)
+            let mut dx = (x as f32 + 0.5 - previous_x) * MOTION_BLUR_STRENGTH;
+            let mut dy = (y as f32 + 0.5 - previous_y) * MOTION_BLUR_STRENGTH;
+            let length = (dx * dx + dy * dy).sqrt();
+            if length < MOTION_BLUR_MIN_LENGTH {
+                continue;
+            }
+            if length > MOTION_BLUR_MAX_LENGTH {
+                dx *= MOTION_BLUR_MAX_LENGTH / length;
+                dy *= MOTION_BLUR_MAX_LENGTH / length;
+            }
+
+            let mut sum = [0.0f32; 3];
+            for i in 0..MOTION_BLUR_SAMPLES {
+                let t = i as f32 / (MOTION_BLUR_SAMPLES - 1) as f32 - 0.5;
+                let sample_x = (x as f32 + dx * t).round().clamp(0.0, max_x) as usize;
+                let sample_y = (y as f32 + dy * t).round().clamp(0.0, max_y) as usize;
+                for (total, channel) in sum.iter_mut().zip(unpack_rgb(source[sample_y * width + sample_x])) {
+                    *total += channel;
+                }
+            }
+            framebuffer.buffer[index] = pack_rgb(sum.map(|channel| channel / MOTION_BLUR_SAMPLES as f32));
+        }
+    }
+}
+
+// Profundidad de campo: cada pixel se mezcla con una copia desenfocada del frame según su círculo de
+// confusión, que crece con la distancia relativa a la zona enfocada. El desenfoque es el mismo gaussiano
+// separable del bloom sobre el frame reducido
+const DOF_DOWNSAMPLE: usize = 4;
+const DOF_KERNEL_SIZE: usize = 7;
+const DOF_SIGMA: f32 = 1.5;
+const DOF_APERTURE: f32 = 2.0;       // Círculo de confusión (1 = desenfoque completo) por unidad de distancia relativa
+const DOF_NEIGHBORHOOD: isize = 2;   // Bloques reducidos alrededor de cada pixel donde se buscan bordes más cercanos
+
+pub struct DepthOfField {
+    blurred: Vec<u32>,
+}
+
+impl DepthOfField {
+    pub fn new() -> Self {
+        DepthOfField { blurred: Vec::new() }
+    }
+}
+
+impl PostEffect for DepthOfField {
+    fn name(&self) -> &'static str {
+        "PROFUNDIDAD"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        if let Some((focus, range)) = context.focus {
+            apply_depth_of_field(framebuffer, &mut self.blurred, focus, range);
+        }
+    }
+}
+
+// Distancia de enfoque y medio ancho de la zona nítida, en profundidad de vista (la del z-buffer):
+// con un cuerpo seleccionado delante de la cámara, todo el cuerpo; si no, lo que hay bajo la mira.
+// `nudge` multiplica la distancia (se ajusta con , y .)
+pub fn depth_of_field_focus(framebuffer: &Framebuffer, uniforms: &Uniforms, solar_system: &SolarSystem, nudge: f32) -> (f32, f32) {
+    let selected = solar_system.selected_body.map(|index| &solar_system.bodies[index]).and_then(|body| {
+        let view = uniforms.view_matrix * Vec4::new(body.position.x, body.position.y, body.position.z, 1.0);
+        (-view.z > 0.0).then_some((-view.z, body.collision_radius))
+    });
+    let (focus, range) = selected.unwrap_or_else(|| {
+        let center = framebuffer.zbuffer[framebuffer.height / 2 * framebuffer.width + framebuffer.width / 2];
+        (center, 0.0)
+    });
+    (focus * nudge, range)
+}
+
+// 0 dentro de la zona nítida y hasta 1 lejos de ella. El fondo (profundidad infinita) solo queda
+// nítido si el enfoque también está en el infinito
+fn circle_of_confusion(depth: f32, focus: f32, range: f32) -> f32 {
+    let relative = if !depth.is_finite() || depth >= f32::MAX {
+        if focus.is_finite() && focus < f32::MAX { 1.0 } else { 0.0 }
+    } else {
+        ((depth - focus).abs() - range).max(0.0) / depth
+    };
+    (relative * DOF_APERTURE).min(1.0)
+}
+
+fn apply_depth_of_field(framebuffer: &mut Framebuffer, blurred: &mut Vec<u32>, focus: f32, range: f32) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let (mut small, small_width, small_height) = downsample(&framebuffer.buffer, width, height, DOF_DOWNSAMPLE);
+    gaussian_blur(&mut small, small_width, small_height, DOF_KERNEL_SIZE, DOF_SIGMA);
+    blurred.resize(width * height, 0);
+    upsample(&small, small_width, small_height, blurred, width, height, DOF_DOWNSAMPLE);
+
+    // Lo más cercano de cada bloque reducido y su círculo de confusión. Junto a un borde de algo más
+    // cercano la copia desenfocada trae el color de ese objeto, así que el pixel no se desenfoca más
+    // que él: un planeta enfocado no deja un halo sobre el fondo borroso
+    let mut nearest = vec![(f32::INFINITY, 0.0f32); small_width * small_height];
+    for y in 0..height {
+        for x in 0..width {
+            let depth = framebuffer.zbuffer[y * width + x];
+            let block = &mut nearest[y / DOF_DOWNSAMPLE * small_width + x / DOF_DOWNSAMPLE];
+            if depth < block.0 {
+                *block = (depth, circle_of_confusion(depth, focus, range));
+            }
+        }
+    }
+
+    for y in 0..height {
+        let block_y = (y / DOF_DOWNSAMPLE) as isize;
+        for x in 0..width {
+            let index = y * width + x;
+            let depth = framebuffer.zbuffer[index];
+            let mut coc = circle_of_confusion(depth, focus, range);
+            if coc <= 0.0 {
+                continue;
+            }
+            let block_x = (x / DOF_DOWNSAMPLE) as isize;
+            for ny in (block_y - DOF_NEIGHBORHOOD).max(0)..=(block_y + DOF_NEIGHBORHOOD).min(small_height as isize - 1) {
+                for nx in (block_x - DOF_NEIGHBORHOOD).max(0)..=(block_x + DOF_NEIGHBORHOOD).min(small_width as isize - 1) {
+                    let (near_depth, near_coc) = nearest[ny as usize * small_width + nx as usize];
+                    if near_depth < depth {
+                        coc = coc.min(near_coc);
+                    }
+                }
+            }
+
+            let sharp = unpack_rgb(framebuffer.buffer[index]);
+            let soft = unpack_rgb(blurred[index]);
+            framebuffer.buffer[index] = pack_rgb([0, 1, 2].map(|c| sharp[c] + (soft[c] - sharp[c]) * coc));
+        }
+    }
+}
+
+// Efecto de warp: aberración cromática radial, distorsión de barril y estelas hacia el destino.
+// Todos crecen con el avance del warp y desaparecen al llegar
+const WARP_ABERRATION: f32 = 0.015;      // Separación radial entre canales en las esquinas
+const WARP_BARREL: f32 = 0.08;           // Contracción de la imagen en las esquinas
+const WARP_STREAK_SECTORS: u32 = 360;    // Sectores angulares alrededor del destino que pueden tener estela
+const WARP_STREAK_DENSITY: f32 = 0.2;    // Fracción de sectores con estela
+const WARP_STREAK_COLOR: [f32; 3] = [0.55, 0.65, 0.8];
+
+// Solo actúa durante el warp, con una curva suave que se corta de golpe al llegar. Guarda una
+// copia del frame, así cada pixel se muestrea sin importar lo que ya se escribió
+pub struct WarpDistortion {
+    source: Vec<u32>,
+}
+
+impl WarpDistortion {
+    pub fn new() -> Self {
+        WarpDistortion { source: Vec::new() }
+    }
+}
+
+impl PostEffect for WarpDistortion {
+    fn name(&self) -> &'static str {
+        "WARP"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let solar_system = context.solar_system;
+        let Some(target) = solar_system.warp_target else { return };
+        let t = solar_system.warp_animation.clamp(0.0, 1.0);
+        let strength = t * t * (3.0 - 2.0 * t);
+        let focus = world_to_screen(solar_system.bodies[target].position, context.uniforms)
+            .map_or((framebuffer.width as f32 / 2.0, framebuffer.height as f32 / 2.0), |screen| (screen.x, screen.y));
+        apply_warp_effect(framebuffer, &mut self.source, strength, focus);
+    }
+}
+
+// `strength` en [0, 1]; `focus` es la posición en pantalla del destino, de donde salen las estelas
+fn apply_warp_effect(framebuffer: &mut Framebuffer, source: &mut Vec<u32>, strength: f32, focus: (f32, f32)) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    source.clear();
+    source.extend_from_slice(&framebuffer.buffer);
+
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let inverse_radius = 1.0 / (cx * cx + cy * cy).sqrt();
+    let (max_x, max_y) = ((width - 1) as f32, (height - 1) as f32);
+
+    // Muestra más cercana a lo largo del rayo desde el centro, recortada al borde de la pantalla
+    let sample = |dx: f32, dy: f32, scale: f32| {
+        let x = (cx + dx * scale).clamp(0.0, max_x) as usize;
+        let y = (cy + dy * scale).clamp(0.0, max_y) as usize;
+        source[y * width + x]
+    };
+
+    for y in 0..height {
+        let dy = y as f32 + 0.5 - cy;
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let radius_sq = (dx * dx + dy * dy) * inverse_radius * inverse_radius;
+
+            let distortion = 1.0 - WARP_BARREL * strength * radius_sq;
+            let aberration = WARP_ABERRATION * strength * radius_sq;
+            let red = (sample(dx, dy, distortion * (1.0 + aberration)) >> 16) & 0xFF;
+            let green = (sample(dx, dy, distortion) >> 8) & 0xFF;
+            let blue = sample(dx, dy, distortion * (1.0 - aberration)) & 0xFF;
+            let mut color = [red, green, blue];
+
+            // Estelas: algunos sectores angulares alrededor del destino se iluminan, desde el borde
+            // de la pantalla hacia adentro a medida que avanza el warp
+            let (fx, fy) = (x as f32 + 0.5 - focus.0, y as f32 + 0.5 - focus.1);
+            let along = ((fx * fx + fy * fy).sqrt() * inverse_radius).min(1.0);
+            let reach = along - (1.0 - strength);
+            if reach > 0.0 {
+                let sector = (diamond_angle(fx, fy) / 4.0 * WARP_STREAK_SECTORS as f32) as u32 % WARP_STREAK_SECTORS;
+                let hash = (sector.wrapping_mul(2654435761) >> 16 & 0xFFFF) as f32 / 65535.0;
+                if hash < WARP_STREAK_DENSITY {
+                    let amount = reach * strength * (hash / WARP_STREAK_DENSITY);
+                    for (channel, streak) in color.iter_mut().zip(WARP_STREAK_COLOR) {
+                        *channel = (*channel + (streak * amount * 255.0) as u32).min(255);
+                    }
+                }
+            }
+
+            framebuffer.buffer[y * width + x] = (color[0] << 16) | (color[1] << 8) | color[2];
+        }
+    }
+}
+
+// Sustituto barato de atan2 para repartir sectores: crece con el ángulo pero recorre [0, 4) en lugar de
+// [-PI, PI). Los sectores no quedan exactamente iguales, lo que no se nota en las estelas
+fn diamond_angle(x: f32, y: f32) -> f32 {
+    match (x >= 0.0, y >= 0.0) {
+        (true, true) => y / (x + y),
+        (false, true) => 1.0 - x / (y - x),
+        (false, false) => 2.0 - y / (-x - y),
+        (true, false) => 3.0 + x / (x - y),
+    }
+}
+
+// Destello de un impacto: blanco en el punto de contacto y anaranjado hacia los bordes, sumado
+// sobre la imagen final mientras se apaga
+const IMPACT_CORE: [f32; 3] = [1.0, 1.0, 0.95];
+const IMPACT_EDGE: [f32; 3] = [1.0, 0.55, 0.15];
+const IMPACT_EDGE_STRENGTH: f32 = 0.35;   // Intensidad en el borde de la pantalla respecto del centro
+
+pub struct ImpactFlash;
+
+impl PostEffect for ImpactFlash {
+    fn name(&self) -> &'static str {
+        "IMPACT"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &FrameContext) {
+        let Some(impact) = context.solar_system.impact() else { return };
+        let flash = impact.flash();
+        if flash <= 0.0 {
+            return;
+        }
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let focus = world_to_screen(impact.point, context.uniforms)
+            .map_or((width as f32 / 2.0, height as f32 / 2.0), |screen| (screen.x, screen.y));
+        let inverse_radius = 1.0 / ((width * width + height * height) as f32).sqrt();
+
+        for y in 0..height {
+            let dy = y as f32 + 0.5 - focus.1;
+            for x in 0..width {
+                let dx = x as f32 + 0.5 - focus.0;
+                let t = ((dx * dx + dy * dy).sqrt() * inverse_radius).min(1.0);
+                let amount = flash * (1.0 - t * (1.0 - IMPACT_EDGE_STRENGTH));
+                let pixel = &mut framebuffer.buffer[y * width + x];
+                let mut color = [(*pixel >> 16) & 0xFF, (*pixel >> 8) & 0xFF, *pixel & 0xFF];
+                for (channel, (core, edge)) in color.iter_mut().zip(IMPACT_CORE.iter().zip(IMPACT_EDGE)) {
+                    let tint = core + (edge - core) * t;
+                    *channel = (*channel as f32 + (255.0 - *channel as f32) * tint * amount) as u32;
+                }
+                *pixel = (color[0] << 16) | (color[1] << 8) | color[2];
+            }
+        }
+    }
+}
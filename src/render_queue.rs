@@ -0,0 +1,154 @@
+// Cola de dibujo del frame: cada objeto visible se agrega como un comando con su malla, matriz de
+// modelo y material. Al ejecutarla, lo opaco va de adelante hacia atrás (la prueba de profundidad
+// descarta temprano lo que queda tapado) y lo translúcido de atrás hacia adelante, para que cada
+// capa se mezcle sobre la anterior
+use nalgebra_glm::{Mat4, Vec3};
+use crate::framebuffer::Framebuffer;
+use crate::material::{ShaderKind, Surface, SurfaceParams};
+use crate::solar_system::Ring;
+use crate::stats::FrameStats;
+use crate::texture::Texture;
+use crate::renderer::{build_comet_tail, build_ring, render, render_additive, render_sphere, Assets, RenderScratch, Uniforms};
+
+// Malla de un comando; los anillos y las colas se arman al ejecutarlo
+#[derive(Clone, Copy)]
+pub enum Mesh {
+    Sphere(usize),                         // Nivel de detalle de la esfera procedural
+    CloudShell(usize),                     // La misma esfera, translúcida, para la capa de nubes
+    Moon,
+    Spaceship,
+    Ring(Ring),
+    CometTail { position: Vec3, length: f32 }, // En espacio mundial, con la matriz identidad
+}
+
+impl Mesh {
+    fn transparent(&self) -> bool {
+        matches!(self, Mesh::CloudShell(_) | Mesh::Ring(_) | Mesh::CometTail { .. })
+    }
+}
+
+pub struct DrawCommand<'a> {
+    pub mesh: Mesh,
+    pub model_matrix: Mat4,
+    pub material: ShaderKind,
+    pub surface: Option<&'a SurfaceParams>,
+    pub texture: Option<&'a Texture>,
+    pub transparent: bool,
+    pub camera_distance: f32,
+}
+
+pub struct RenderQueue<'a> {
+    commands: Vec<DrawCommand<'a>>,
+}
+
+impl<'a> RenderQueue<'a> {
+    pub fn new() -> Self {
+        RenderQueue { commands: Vec::new() }
+    }
+
+    pub fn push(
+        &mut self,
+        mesh: Mesh,
+        model_matrix: Mat4,
+        material: ShaderKind,
+        surface: Option<&'a SurfaceParams>,
+        texture: Option<&'a Texture>,
+        camera_distance: f32,
+    ) {
+        let transparent = mesh.transparent();
+        self.commands.push(DrawCommand { mesh, model_matrix, material, surface, texture, transparent, camera_distance });
+    }
+
+    // Ordena y dibuja todo. El orden es estable: a igual distancia se respeta el orden de llegada
+    pub fn execute<'b>(
+        mut self,
+        framebuffer: &mut Framebuffer,
+        uniforms: &mut Uniforms<'b>,
+        assets: &'b Assets,
+        scratch: &mut RenderScratch,
+        stats: &mut FrameStats,
+    ) where
+        'a: 'b,
+    {
+        self.sort();
+        self.draw(framebuffer, uniforms, assets, scratch, stats);
+    }
+
+    // Ordena y dibuja solo lo opaco; devuelve lo translúcido para dibujarlo después con `execute`,
+    // así entre ambas pasadas se puede dibujar lo que tiene que quedar detrás de lo translúcido
+    pub fn execute_opaque<'b>(
+        mut self,
+        framebuffer: &mut Framebuffer,
+        uniforms: &mut Uniforms<'b>,
+        assets: &'b Assets,
+        scratch: &mut RenderScratch,
+        stats: &mut FrameStats,
+    ) -> Self
+    where
+        'a: 'b,
+    {
+        self.sort();
+        let first_transparent = self.commands.iter().position(|command| command.transparent).unwrap_or(self.commands.len());
+        let transparent = self.commands.split_off(first_transparent);
+        self.draw(framebuffer, uniforms, assets, scratch, stats);
+        RenderQueue { commands: transparent }
+    }
+
+    fn sort(&mut self) {
+        self.commands.sort_by(|a, b| match (a.transparent, b.transparent) {
+            (false, false) => a.camera_distance.total_cmp(&b.camera_distance),
+            (true, true) => b.camera_distance.total_cmp(&a.camera_distance),
+            (transparent_a, transparent_b) => transparent_a.cmp(&transparent_b),
+        });
+    }
+
+    fn draw<'b>(
+        &self,
+        framebuffer: &mut Framebuffer,
+        uniforms: &mut Uniforms<'b>,
+        assets: &'b Assets,
+        scratch: &mut RenderScratch,
+        stats: &mut FrameStats,
+    ) where
+        'a: 'b,
+    {
+        for command in &self.commands {
+            uniforms.material = assets.materials.get(command.material);
+            uniforms.surface = command.surface.map(|params| Surface::new(command.material, params));
+            // Las texturas horneadas siguen las UV de la esfera unitaria y el ruido compartido del material
+            uniforms.surface_texture = match command.mesh {
+                Mesh::Sphere(_) if command.surface.is_none() => uniforms.material.baked.as_ref(),
+                _ => None,
+            };
+            // La imagen solo va en la malla del cuerpo, no en su capa de nubes
+            uniforms.texture = match command.mesh {
+                Mesh::Sphere(_) | Mesh::Moon => command.texture,
+                _ => None,
+            };
+            uniforms.model_matrix = command.model_matrix;
+
+            match command.mesh {
+                Mesh::Sphere(level) | Mesh::CloudShell(level) => {
+                    let (vertices, indices) = assets.spheres[level].get_indexed();
+                    render_sphere(framebuffer, uniforms, vertices, indices, scratch, stats);
+                }
+                Mesh::Moon => {
+                    let (vertices, indices) = assets.moon.get_indexed();
+                    render(framebuffer, uniforms, vertices, indices, scratch, stats);
+                }
+                Mesh::Spaceship => {
+                    let (vertices, indices) = assets.spaceship.get_indexed();
+                    render(framebuffer, uniforms, vertices, indices, scratch, stats);
+                }
+                Mesh::Ring(ring) => {
+                    let (vertices, indices) = build_ring(&ring);
+                    render(framebuffer, uniforms, &vertices, &indices, scratch, stats);
+                }
+                Mesh::CometTail { position, length } => {
+                    let (vertices, indices) = build_comet_tail(position, length);
+                    render_additive(framebuffer, uniforms, &vertices, &indices, scratch, stats);
+                }
+            }
+        }
+    }
+}
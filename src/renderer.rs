@@ -0,0 +1,1477 @@
+// Renderizador por software del sistema solar, separado de la ventana: recibe el estado de la
+// simulación y la cámara y devuelve el frame terminado (escena, post-procesamiento y HUD).
+// Lo usan el modo con ventana, el modo sin ventana y el benchmark
+use nalgebra_glm::{mat4_to_mat3, Vec2, Vec3, Vec4, Mat4, perspective};
+use std::{f32::consts::PI, time::Instant};
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::debug_view::{self, DebugMode, RenderMode};
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::frustum::Frustum;
+use crate::impact::Impact;
+use crate::labels;
+use crate::line::line;
+use crate::material::{Material, MaterialRegistry, ShaderKind, Surface};
+use crate::minimap;
+use crate::targeting;
+use crate::obj::{NormalMode, Obj, ObjError};
+use crate::post::{self, Bloom, DepthOfField, FrameContext, ImpactFlash, LensFlare, MotionBlur, PostPipeline, SunHaze, ToneMap, WarpDistortion};
+use crate::render_queue::{Mesh, RenderQueue};
+use crate::session::InputState;
+use crate::shaders::{vertex_shader, fragment_shader, CLOCK_PERIOD, CLOUD_SHELL_SCALE};
+use crate::shadows::{Occluder, RingOccluder};
+use crate::solar_system::{CelestialBody, MeshRadii, Ring, SolarSystem};
+use crate::spaceship;
+use crate::starfield::Starfield;
+use crate::stats::{FrameStats, Stage};
+use crate::surface_texture::{SurfaceTexture, DEFAULT_BAKE_RESOLUTION};
+use crate::texture::Texture;
+use crate::text::{draw_text, GLYPH_HEIGHT};
+use crate::trail::OrbitDisplay;
+use crate::triangle::triangle;
+use crate::tuning::Tuning;
+use crate::vertex::Vertex;
+use crate::viewport::{self, Viewport};
+
+pub struct Uniforms<'a> {
+    pub model_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub inverse_view: Mat4,         // Inversas de la vista y la proyección, para reconstruir rayos por pixel
+    pub inverse_projection: Mat4,
+    pub time: f32,                  // Reloj de los shaders: segundos de simulación, acotado a CLOCK_PERIOD
+    pub material: &'a Material,
+    pub surface: Option<Surface>,   // Ruido y paleta del planeta generado que se está dibujando
+    pub surface_texture: Option<&'a SurfaceTexture>, // Superficie horneada del cuerpo, si es una esfera que la tiene
+    pub texture: Option<&'a Texture>, // Imagen del cuerpo (`texture` en la escena); tiene prioridad sobre lo horneado
+    pub camera_position: Vec3,
+    pub occluders: Vec<Occluder>,   // Cuerpos que proyectan sombra en el frame actual (todos menos el Sol)
+    pub rings: Vec<RingOccluder>,   // Anillos que proyectan sombra en el frame actual
+    pub sun_radius: f32,
+    pub debug_mode: DebugMode,
+    pub render_mode: RenderMode,
+}
+
+// Avanza el reloj de los shaders; se da la vuelta cada CLOCK_PERIOD segundos para no perder
+// precisión en sesiones largas (las animaciones se repiten en períodos que lo dividen)
+pub fn advance_clock(time: f32, seconds: f32) -> f32 {
+    (time + seconds) % CLOCK_PERIOD
+}
+
+impl Uniforms<'_> {
+    // Vista y posición del ojo para el frame actual
+    pub fn set_camera(&mut self, camera: &Camera) {
+        self.view_matrix = camera.view_matrix();
+        self.inverse_view = self.view_matrix.try_inverse().unwrap_or(Mat4::identity());
+        self.camera_position = camera.eye;
+    }
+
+    // Como `set_camera`, con la sacudida de un impacto aplicada solo a la vista de este frame
+    pub fn set_shaken_camera(&mut self, camera: &Camera, (eye_offset, look_offset): (Vec2, Vec2)) {
+        self.view_matrix = camera.shaken_view_matrix(eye_offset, look_offset);
+        self.inverse_view = self.view_matrix.try_inverse().unwrap_or(Mat4::identity());
+        self.camera_position = (self.inverse_view * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+    }
+
+    // Proyección del frame actual y su inversa
+    pub fn set_projection(&mut self, projection_matrix: Mat4) {
+        self.projection_matrix = projection_matrix;
+        self.inverse_projection = projection_matrix.try_inverse().unwrap_or(Mat4::identity());
+    }
+}
+
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale, 0.0,   0.0,   translation.x,
+        0.0,   scale, 0.0,   translation.y,
+        0.0,   0.0,   scale, translation.z,
+        0.0,   0.0,   0.0,   1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+// Igual, con una deformación por eje (`CelestialBody::shape`) aplicada en espacio del modelo antes de rotar
+pub fn create_shaped_model_matrix(translation: Vec3, scale: f32, shape: Vec3, rotation: Vec3) -> Mat4 {
+    create_model_matrix(translation, scale, rotation) * Mat4::new_nonuniform_scaling(&shape)
+}
+
+// Plano cercano fijo; el lejano se calcula por frame con `clip_planes`
+pub const NEAR_PLANE: f32 = 0.1;
+
+fn create_perspective_matrix(fov_degrees: f32, near: f32, far: f32, window_width: f32, window_height: f32) -> Mat4 {
+    let fov = fov_degrees * PI / 180.0;
+    let aspect_ratio = window_width / window_height;
+    // nalgebra-glm recibe primero la relación de aspecto y luego el campo de visión vertical
+    perspective(aspect_ratio, fov, near, far)
+}
+
+// Planos de recorte que abarcan todo el sistema desde la posición actual de la cámara:
+// el plano lejano llega al punto más alejado de la esfera que contiene todas las órbitas
+fn clip_planes(camera_eye: Vec3, system_radius: f32) -> (f32, f32) {
+    let far = (camera_eye.magnitude() + system_radius) * 1.1;
+    (NEAR_PLANE, far.max(NEAR_PLANE * 2.0))
+}
+
+fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+// Exposición inicial y límites para ajustarla con el teclado
+pub const DEFAULT_EXPOSURE: f32 = 1.0;
+pub const MIN_EXPOSURE: f32 = 0.125;
+pub const MAX_EXPOSURE: f32 = 8.0;
+
+// Campo de visión vertical en grados: el inicial, los límites para Ctrl + rueda y el de la tecla C
+const DEFAULT_FOV: f32 = 60.0;
+const MIN_FOV: f32 = 20.0;
+const MAX_FOV: f32 = 100.0;
+const ZOOM_FOV: f32 = 15.0;
+const FOV_SMOOTHING: f32 = 10.0;    // Rapidez con que el campo de visión llega al objetivo (1/s)
+const FOV_HUD_SECONDS: f32 = 1.5;   // Tiempo que el HUD sigue mostrando el campo de visión después de cambiarlo
+
+// Radianes por pixel de movimiento del mouse con el campo de visión inicial
+const MOUSE_SENSITIVITY: f32 = 0.003;
+
+// Buffers de trabajo del pipeline, reutilizados entre llamadas a `render` para no pedir memoria en cada frame
+pub struct RenderScratch {
+    vertices: Vec<Vertex>,
+    fragments: Vec<Fragment>,
+    post: PostPipeline, // Efectos de post-procesamiento, con su estado entre frames
+    previous_camera: Option<(Mat4, Vec3, f32)>, // Vista, posición y distancia focal de la cámara en el frame anterior (anti-aliasing temporal)
+    delta_time: f32,    // Segundos reales desde el frame anterior (adaptación de la exposición)
+}
+
+impl RenderScratch {
+    fn new(post: PostPipeline) -> Self {
+        RenderScratch { vertices: Vec::new(), fragments: Vec::new(), post, previous_camera: None, delta_time: 0.0 }
+    }
+}
+
+// Escalas de render internas que se pueden elegir (F6 baja, F7 sube)
+pub const RENDER_SCALES: [f32; 3] = [0.5, 0.75, 1.0];
+
+// La escena y el post-procesamiento se dibujan en `scene`, a la escala de render; `display` tiene el
+// tamaño de la ventana, recibe la escena ampliada y encima el texto, que así queda nítido
+pub struct RenderTargets {
+    scene: Framebuffer,
+    display: Framebuffer,
+    scale: f32,
+    insets: Vec<InsetTarget>, // Una por cada vista adicional, en el mismo orden
+}
+
+// Vista adicional: su rectángulo en la ventana y su campo de visión, y un framebuffer a la escala de
+// render con su propio bloom y tone mapping (la exposición automática se adapta a lo que ve esa vista)
+struct InsetTarget {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    fov: f32,
+    scene: Framebuffer,
+    post: PostPipeline,
+}
+
+impl InsetTarget {
+    fn new(width: usize, height: usize) -> Self {
+        InsetTarget {
+            x: 0,
+            y: 0,
+            width,
+            height,
+            fov: DEFAULT_FOV,
+            scene: scene_framebuffer(width, height),
+            post: PostPipeline::new(vec![Box::new(Bloom), Box::new(ToneMap::new())]),
+        }
+    }
+}
+
+impl RenderTargets {
+    fn new(width: usize, height: usize) -> Self {
+        RenderTargets { scene: scene_framebuffer(width, height), display: Framebuffer::new(width, height), scale: 1.0, insets: Vec::new() }
+    }
+
+    // Un framebuffer por vista adicional con el tamaño de su rectángulo a la escala actual; los que ya
+    // tienen ese tamaño se conservan, junto con el estado de su post-procesamiento
+    fn prepare_insets(&mut self, viewports: &[Viewport]) {
+        self.insets.truncate(viewports.len());
+        for (index, viewport) in viewports.iter().enumerate() {
+            let (width, height) = (scaled_size(viewport.width, self.scale), scaled_size(viewport.height, self.scale));
+            if self.insets.len() == index {
+                self.insets.push(InsetTarget::new(width, height));
+            }
+            let inset = &mut self.insets[index];
+            if inset.scene.width != width || inset.scene.height != height {
+                inset.scene = scene_framebuffer(width, height);
+            }
+            (inset.x, inset.y, inset.width, inset.height, inset.fov) = (viewport.x, viewport.y, viewport.width, viewport.height, viewport.fov);
+        }
+    }
+
+    // Tamaño de la ventana; la escena se recrea con la escala actual
+    fn resize(&mut self, width: usize, height: usize) {
+        self.display = Framebuffer::new(width, height);
+        self.scene = scene_framebuffer(scaled_size(width, self.scale), scaled_size(height, self.scale));
+    }
+
+    // Framebuffer con el frame terminado: la escena misma si tiene el tamaño de la ventana
+    fn output(&self) -> &Framebuffer {
+        if targets_match(&self.scene, &self.display) { &self.scene } else { &self.display }
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.resize(self.display.width, self.display.height);
+        }
+    }
+}
+
+fn targets_match(scene: &Framebuffer, display: &Framebuffer) -> bool {
+    scene.width == display.width && scene.height == display.height
+}
+
+fn scene_framebuffer(width: usize, height: usize) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.set_background_color(0x000000); // Fondo negro para el espacio
+    framebuffer
+}
+
+fn scaled_size(size: usize, scale: f32) -> usize {
+    ((size as f32 * scale).round() as usize).max(1)
+}
+
+// Anti-aliasing temporal: la proyección se desplaza una fracción de pixel distinta en cada frame
+// (secuencia de Halton 2, 3) y los frames se promedian mientras la cámara está quieta
+const TAA_SAMPLES: u32 = 16;          // Largo del ciclo de desplazamientos
+const TAA_MIN_WEIGHT: f32 = 0.1;      // Peso mínimo del frame nuevo una vez lleno el promedio
+const TAA_RESET_MOTION: f32 = 0.5;    // Movimiento de cámara (pixeles) que descarta lo acumulado
+
+// Elemento `index` (desde 1) de la secuencia de Halton en la base dada, en [0, 1)
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+// Desplazamiento en pixeles, en [-0.5, 0.5), para la muestra `sample`
+fn taa_jitter(sample: u32) -> (f32, f32) {
+    let index = sample % TAA_SAMPLES + 1;
+    (halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+}
+
+// Cuánto se movió la imagen por la cámara desde el frame anterior, en pixeles: el giro más el
+// paralaje de la traslación respecto del cuerpo más cercano. Guarda la cámara actual para el próximo frame
+fn camera_motion(uniforms: &Uniforms, solar_system: &SolarSystem, height: usize, scratch: &mut RenderScratch) -> f32 {
+    // Pixeles por radian en el centro de la pantalla
+    let focal = uniforms.projection_matrix[(1, 1)] * height as f32 / 2.0;
+    let current = (uniforms.view_matrix, uniforms.camera_position, focal);
+    let Some((previous_view, previous_eye, previous_focal)) = scratch.previous_camera.replace(current) else {
+        return f32::INFINITY;
+    };
+
+    let rotation = (0..3)
+        .flat_map(|row| (0..3).map(move |column| (row, column)))
+        .map(|index| (uniforms.view_matrix[index] - previous_view[index]).abs())
+        .fold(0.0, f32::max);
+    let nearest = solar_system.nearest_body(&uniforms.camera_position).map_or(f32::INFINITY, |(_, distance)| distance);
+    let translation = (uniforms.camera_position - previous_eye).magnitude() / nearest.max(NEAR_PLANE);
+    // Un cambio del campo de visión desplaza más los bordes: medio alto de pantalla por el cambio relativo
+    let zoom = (focal / previous_focal - 1.0).abs() * height as f32 / 2.0;
+    (rotation + translation) * focal + zoom
+}
+
+// Transforma cada vértice único una vez y rasteriza los triángulos formados por los índices;
+// los resultados quedan en `scratch`, que se vacía sin liberar su capacidad
+fn rasterize(uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], width: usize, height: usize, scratch: &mut RenderScratch, stats: &mut FrameStats) {
+    let vertex_start = Instant::now();
+    scratch.vertices.clear();
+    scratch.vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)));
+    stats.add(Stage::Vertex, vertex_start.elapsed());
+
+    let raster_start = Instant::now();
+    scratch.fragments.clear();
+    stats.record_triangles(indices.len() / 3);
+    let flat = uniforms.render_mode == RenderMode::Flat;
+    let transformed = &scratch.vertices;
+    for tri in indices.chunks_exact(3) {
+        let (v1, v2, v3) = (&transformed[tri[0] as usize], &transformed[tri[1] as usize], &transformed[tri[2] as usize]);
+        let first = scratch.fragments.len();
+        triangle(v1, v2, v3, width, height, &mut scratch.fragments);
+        if flat {
+            let normal = face_normal(v1, v2, v3, uniforms);
+            scratch.fragments[first..].iter_mut().for_each(|fragment| fragment.normal = normal);
+        }
+    }
+    stats.add(Stage::Raster, raster_start.elapsed());
+}
+
+// Normal de la cara en el mundo (el producto cruz de las aristas ya transformadas por el modelo),
+// orientada hacia el mismo lado que las normales de sus vértices
+fn face_normal(v1: &Vertex, v2: &Vertex, v3: &Vertex, uniforms: &Uniforms) -> Vec3 {
+    let model = mat4_to_mat3(&uniforms.model_matrix);
+    let average = v1.transformed_normal + v2.transformed_normal + v3.transformed_normal;
+    let normal = (model * (v2.position - v1.position)).cross(&(model * (v3.position - v1.position)));
+    match normal.try_normalize(f32::EPSILON) {
+        Some(normal) if normal.dot(&average) < 0.0 => -normal,
+        Some(normal) => normal,
+        None => average.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 1.0, 0.0)),
+    }
+}
+
+// Modo alambre: las aristas se colorean según su profundidad en escala logarítmica, de claro
+// (cerca) a oscuro (lejos), y se dibujan apenas por delante de su superficie para no pelear con ella
+const WIREFRAME_NEAR: f32 = 1.0;
+const WIREFRAME_FAR: f32 = 100.0;
+const WIREFRAME_DEPTH_BIAS: f32 = 0.005;
+
+fn wireframe_color(depth: f32) -> u32 {
+    let t = ((depth / WIREFRAME_NEAR).ln() / (WIREFRAME_FAR / WIREFRAME_NEAR).ln()).clamp(0.0, 1.0);
+    Color::from_float(0.6, 1.0, 1.0).lerp(&Color::from_float(0.1, 0.15, 0.5), t).to_hex()
+}
+
+// Primero se escribe solo la profundidad de los triángulos, así las aristas de atrás y las que tapa
+// otro objeto no se ven; después cada arista pasa por el rasterizador de líneas con prueba de profundidad
+fn render_wireframe(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], scratch: &mut RenderScratch, stats: &mut FrameStats) {
+    rasterize(uniforms, vertex_array, indices, framebuffer.width, framebuffer.height, scratch, stats);
+
+    let shading_start = Instant::now();
+    for fragment in &scratch.fragments {
+        let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+        if x < framebuffer.width && y < framebuffer.height {
+            let depth = &mut framebuffer.zbuffer[y * framebuffer.width + x];
+            *depth = depth.min(fragment.depth);
+        }
+    }
+
+    let transformed = &scratch.vertices;
+    for tri in indices.chunks_exact(3) {
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            let start = transformed[tri[a] as usize].transformed_position;
+            let end = transformed[tri[b] as usize].transformed_position;
+            // Igual que `triangle`: un vértice detrás de la cámara se proyectaría reflejado
+            if start.z <= 0.0 || end.z <= 0.0 {
+                continue;
+            }
+            framebuffer.set_current_color(wireframe_color((start.z + end.z) * 0.5));
+            let bias = 1.0 - WIREFRAME_DEPTH_BIAS;
+            line(framebuffer, Vec3::new(start.x, start.y, start.z * bias), Vec3::new(end.x, end.y, end.z * bias));
+        }
+    }
+    stats.add(Stage::Shading, shading_start.elapsed());
+}
+
+pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], scratch: &mut RenderScratch, stats: &mut FrameStats) {
+    if uniforms.render_mode == RenderMode::Wireframe {
+        return render_wireframe(framebuffer, uniforms, vertex_array, indices, scratch, stats);
+    }
+    rasterize(uniforms, vertex_array, indices, framebuffer.width, framebuffer.height, scratch, stats);
+    shade(framebuffer, uniforms, &scratch.fragments, stats);
+}
+
+// Igual que `render` para las esferas unitarias con nivel de detalle: la posición interpolada se
+// proyecta sobre la esfera, así el ruido de la superficie no cambia al cambiar la teselación
+pub fn render_sphere(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], scratch: &mut RenderScratch, stats: &mut FrameStats) {
+    if uniforms.render_mode == RenderMode::Wireframe {
+        return render_wireframe(framebuffer, uniforms, vertex_array, indices, scratch, stats);
+    }
+    rasterize(uniforms, vertex_array, indices, framebuffer.width, framebuffer.height, scratch, stats);
+    for fragment in scratch.fragments.iter_mut() {
+        fragment.vertex_position = fragment.vertex_position.normalize();
+    }
+    shade(framebuffer, uniforms, &scratch.fragments, stats);
+}
+
+fn shade(framebuffer: &mut Framebuffer, uniforms: &Uniforms, fragments: &[Fragment], stats: &mut FrameStats) {
+    let shading_start = Instant::now();
+    let mut shaded = 0;
+    for fragment in fragments {
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+        if x < framebuffer.width && y < framebuffer.height {
+            // Prueba de profundidad temprana: lo que ya está tapado no pasa por el shader
+            if framebuffer.zbuffer[y * framebuffer.width + x] <= fragment.depth {
+                continue;
+            }
+            shaded += 1;
+            // Opacidad 0 descarta el fragmento; menor que 1 lo mezcla con lo que hay detrás
+            let (shaded_color, emission) = fragment_shader(fragment, uniforms, uniforms.time);
+            let alpha = shaded_color.alpha();
+            if alpha <= 0.0 {
+                continue;
+            }
+            framebuffer.set_current_color_alpha(shaded_color.to_hex(), alpha);
+            framebuffer.point(x, y, fragment.depth, emission.to_hex());
+        }
+    }
+    stats.record_fragments(shaded);
+    stats.add(Stage::Shading, shading_start.elapsed());
+}
+
+// Igual que `render` pero sumando el color sobre lo ya dibujado, para geometría translúcida
+pub fn render_additive(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], scratch: &mut RenderScratch, stats: &mut FrameStats) {
+    if uniforms.render_mode == RenderMode::Wireframe {
+        return render_wireframe(framebuffer, uniforms, vertex_array, indices, scratch, stats);
+    }
+    rasterize(uniforms, vertex_array, indices, framebuffer.width, framebuffer.height, scratch, stats);
+
+    let shading_start = Instant::now();
+    for fragment in &scratch.fragments {
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+        let (shaded_color, emission) = fragment_shader(fragment, uniforms, uniforms.time);
+        framebuffer.add_point(x, y, fragment.depth, shaded_color.to_hex(), emission.to_hex());
+    }
+    stats.record_fragments(scratch.fragments.len());
+    stats.add(Stage::Shading, shading_start.elapsed());
+}
+
+// Cola de cometa en espacio mundial: un abanico de triángulos en forma de cono que apunta
+// en dirección opuesta al Sol. uv.x va de 0 en el núcleo a 1 en la punta
+pub fn build_comet_tail(position: Vec3, radius: f32) -> (Vec<Vertex>, Vec<u32>) {
+    let distance_to_sun = position.magnitude().max(0.1);
+    let away = position / distance_to_sun;
+
+    // Más larga y brillante cerca del perihelio
+    let length = (40.0 / distance_to_sun).clamp(0.5, 10.0);
+    let brightness = (8.0 / distance_to_sun).clamp(0.2, 1.0);
+    let color = Color::from_float(0.6 * brightness, 0.8 * brightness, brightness);
+
+    let helper = if away.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let side = away.cross(&helper).normalize();
+    let up = side.cross(&away).normalize();
+    let tip = position + away * length;
+
+    let segments = 8;
+    let make_vertex = |point: Vec3, along: f32| {
+        let mut vertex = Vertex::new(point, away, Vec2::new(along, 0.0));
+        vertex.color = color;
+        vertex
+    };
+
+    // Anillo de la base alrededor del núcleo y la punta como último vértice
+    let mut vertices: Vec<Vertex> = (0..segments)
+        .map(|k| {
+            let angle = k as f32 / segments as f32 * 2.0 * PI;
+            make_vertex(position + (side * angle.cos() + up * angle.sin()) * radius, 0.0)
+        })
+        .collect();
+    vertices.push(make_vertex(tip, 1.0));
+
+    let tip_index = segments as u32;
+    let indices = (0..segments as u32)
+        .flat_map(|k| [k, (k + 1) % tip_index, tip_index])
+        .collect();
+
+    (vertices, indices)
+}
+
+// En el plano del ecuador: la inclinación propia del anillo y después la axial del cuerpo.
+// El giro del cuerpo no se aplica, el anillo es simétrico
+fn ring_model_matrix(body: &CelestialBody, ring: &Ring) -> Mat4 {
+    create_model_matrix(body.position, body.scale, Vec3::new(ring.tilt, 0.0, body.axial_tilt))
+}
+
+// Corona plana en y = 0 entre los radios interior y exterior del anillo (en unidades del cuerpo).
+// La coordenada u va de 0 en el borde interior a 1 en el exterior, para las bandas del shader
+pub fn build_ring(ring: &Ring) -> (Vec<Vertex>, Vec<u32>) {
+    let segments = 96;
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let vertices = (0..segments)
+        .flat_map(|k| {
+            let angle = k as f32 / segments as f32 * 2.0 * PI;
+            let direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+            [
+                Vertex::new(direction * ring.inner_scale, normal, Vec2::new(0.0, 0.0)),
+                Vertex::new(direction * ring.outer_scale, normal, Vec2::new(1.0, 0.0)),
+            ]
+        })
+        .collect();
+
+    let count = 2 * segments as u32;
+    let indices = (0..segments as u32)
+        .flat_map(|k| {
+            let (inner, outer) = (2 * k, 2 * k + 1);
+            let (next_inner, next_outer) = ((2 * k + 2) % count, (2 * k + 3) % count);
+            [inner, outer, next_outer, inner, next_outer, next_inner]
+        })
+        .collect();
+
+    (vertices, indices)
+}
+
+// Dibuja el FPS y los tiempos por etapa en la esquina superior izquierda
+fn draw_stats_overlay(framebuffer: &mut Framebuffer, stats: &FrameStats) {
+    let scale = 2;
+    for (i, text) in stats.lines().iter().enumerate() {
+        let y = 8 + i * (GLYPH_HEIGHT + 2) * scale;
+        draw_text(framebuffer, 8, y, text, 0x00FF00, scale);
+    }
+}
+
+// HUD en la esquina inferior izquierda: parámetros en ajuste, cuerpo seleccionado o destino del warp o del piloto automático,
+// velocidad de la simulación y distancia al cuerpo más cercano
+fn draw_hud(framebuffer: &mut Framebuffer, solar_system: &SolarSystem, uniforms: &Uniforms, post: &PostPipeline, settings: &RenderSettings, materials: &MaterialRegistry) {
+    let scale = 2;
+    let mut lines = settings.tuning.as_ref().map_or_else(Vec::new, |tuning| tuning.hud_lines(materials));
+
+    if uniforms.debug_mode != DebugMode::Off {
+        lines.push(format!("VISTA: {}", uniforms.debug_mode.name()));
+    }
+    if uniforms.render_mode != RenderMode::Filled {
+        lines.push(format!("DIBUJO: {}", uniforms.render_mode.name()));
+    }
+    if settings.orbit_display != OrbitDisplay::Circle {
+        lines.push(format!("ÓRBITAS: {}", settings.orbit_display.name()));
+    }
+    let disabled_effects = post.disabled_names();
+    if !disabled_effects.is_empty() {
+        lines.push(format!("SIN: {}", disabled_effects.join(" ")));
+    }
+    let render_scale = RENDER_SCALES[settings.render_scale];
+    if render_scale < 1.0 {
+        lines.push(format!("ESCALA: {:.0}%", render_scale * 100.0));
+    }
+    if settings.depth_of_field {
+        lines.push(format!("ENFOQUE: x{:.2}", settings.focus_nudge));
+    }
+    if settings.fov_hud_timer > 0.0 {
+        lines.push(format!("CAMPO VISUAL: {:.0}°", settings.current_fov));
+    }
+
+    if let Some(target) = solar_system.warp_target {
+        lines.push(format!("VIAJANDO A: {}", solar_system.bodies[target].name));
+    } else if let Some(target) = solar_system.autopilot_target() {
+        lines.push(format!("PILOTO AUTOMÁTICO: {}", solar_system.bodies[target].name));
+    } else if let Some(target) = solar_system.arrival_notice() {
+        lines.push(format!("LLEGADA: {}", solar_system.bodies[target].name));
+    } else if let Some(selected) = solar_system.selected_body {
+        lines.push(format!("SELECCIÓN: {}", solar_system.bodies[selected].name));
+    }
+
+    if let Some(seed) = solar_system.seed {
+        lines.push(format!("SEMILLA: {}", seed));
+    }
+
+    if solar_system.paused {
+        lines.push("TIEMPO: PAUSA".to_string());
+    } else {
+        lines.push(format!("TIEMPO: x{}", solar_system.time_scale));
+    }
+
+    let ship = &solar_system.spaceship;
+    lines.push(format!("VELOCIDAD: {:.1} / {:.1} U/S", ship.speed(), ship.max_speed()));
+    if solar_system.gravity {
+        lines.push("GRAVEDAD: ACTIVADA".to_string());
+    }
+    lines.push(format!("CÁMARA: {}", solar_system.camera_mode.name()));
+
+    if let Some((nearest, distance)) = solar_system.nearest_body(&uniforms.camera_position) {
+        lines.push(format!("CERCANO: {} ({:.1})", solar_system.bodies[nearest].name, distance));
+    }
+
+    let line_height = (GLYPH_HEIGHT + 2) * scale;
+    let top = framebuffer.height.saturating_sub(8 + lines.len() * line_height);
+    for (i, text) in lines.iter().enumerate() {
+        draw_text(framebuffer, 8, top + i * line_height, text, 0xFFFFFF, scale);
+    }
+}
+
+// Borde inferior de la cabina en primera persona: un panel oscuro con el canto superior curvo
+// y una línea más clara en el borde, para dar referencia de hacia dónde apunta la nave
+fn draw_cockpit_rim(framebuffer: &mut Framebuffer) {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height;
+    let base = height as f32 * 0.06;
+    let rise = height as f32 * 0.08;
+    for x in 0..framebuffer.width {
+        let u = (x as f32 / width) * 2.0 - 1.0;
+        let top = height.saturating_sub((base + rise * u * u) as usize);
+        for y in top..height {
+            let color = if y < top + 2 { 0x5A6270 } else { 0x1A1D23 };
+            framebuffer.buffer[y * framebuffer.width + x] = color;
+        }
+    }
+}
+
+// Margen alrededor de la pantalla, en unidades NDC (la pantalla va de -1 a 1), dentro del cual
+// todavía se proyectan puntos: lo que está cerca del borde sigue contando (el Sol que sale del cuadro)
+// y lo que está muy afuera no produce coordenadas enormes
+const GUARD_BAND: f32 = 1.5;
+
+// Posición en pantalla con la profundidad de vista en z; None si el punto está detrás de la cámara,
+// donde la división por w lo reflejaría a un lugar sin sentido, o fuera del margen de la pantalla
+pub fn world_to_screen(point: Vec3, uniforms: &Uniforms) -> Option<Vec3> {
+    let pos = Vec4::new(point.x, point.y, point.z, 1.0);
+    let transformed = uniforms.projection_matrix * uniforms.view_matrix * pos;
+    let w = transformed[3];
+    if w <= f32::EPSILON {
+        return None;
+    }
+    let ndc = Vec4::new(
+        transformed[0] / w,
+        transformed[1] / w,
+        transformed[2] / w,
+        1.0
+    );
+    if ndc.x.abs() > GUARD_BAND || ndc.y.abs() > GUARD_BAND {
+        return None;
+    }
+    let screen = uniforms.viewport_matrix * ndc;
+    Some(Vec3::new(screen[0], screen[1], w))
+}
+
+// Proyecta un segmento del mundo a la pantalla recortándolo contra el plano cercano y los costados
+// del margen de la pantalla: los puntos detrás de la cámara (w negativo) no generan pixeles sueltos
+// y las órbitas que pasan junto a la cámara terminan en el borde en lugar de dar la vuelta
+pub fn project_segment(start: Vec3, end: Vec3, uniforms: &Uniforms) -> Option<(Vec3, Vec3)> {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let mut a = view_projection * Vec4::new(start.x, start.y, start.z, 1.0);
+    let mut b = view_projection * Vec4::new(end.x, end.y, end.z, 1.0);
+
+    // Distancias firmadas en espacio de recorte a cada plano: positiva del lado visible
+    let planes: [fn(&Vec4) -> f32; 5] = [
+        |v| v.z + v.w,
+        |v| GUARD_BAND * v.w + v.x,
+        |v| GUARD_BAND * v.w - v.x,
+        |v| GUARD_BAND * v.w + v.y,
+        |v| GUARD_BAND * v.w - v.y,
+    ];
+    for distance in planes {
+        let da = distance(&a);
+        let db = distance(&b);
+        if da < 0.0 && db < 0.0 {
+            return None;
+        }
+        if da < 0.0 {
+            a = a + (b - a) * (da / (da - db));
+        } else if db < 0.0 {
+            b = b + (a - b) * (db / (db - da));
+        }
+    }
+
+    // z de salida es la profundidad en espacio de vista (w), igual que la que guarda el z-buffer
+    let to_screen = |clip: Vec4| {
+        let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        let screen = uniforms.viewport_matrix * ndc;
+        Vec3::new(screen.x, screen.y, clip.w)
+    };
+
+    Some((to_screen(a), to_screen(b)))
+}
+
+// Modelos, materiales y fondo compartidos por el modo con ventana y el modo sin ventana
+pub struct Assets {
+    pub spheres: Vec<Obj>, // Niveles de detalle de la esfera, del más fino al más grueso
+    pub moon: Obj,
+    pub spaceship: Obj,
+    pub materials: MaterialRegistry,
+    pub starfield: Starfield,
+}
+
+impl Assets {
+    // Esferas procedurales con todos sus niveles de detalle, o el modelo sphere.obj en un único nivel
+    // Las texturas horneadas siguen las UV de las esferas procedurales, así que con sphere.obj no se usan.
+    // `normal_mode` decide cómo se generan las normales que no traen la luna y la nave
+    pub fn load(sphere_obj: bool, bake_resolution: usize, normal_mode: NormalMode) -> Self {
+        let spheres = if sphere_obj {
+            vec![load_model(Obj::load_spherical("assets/models/sphere.obj"))]
+        } else {
+            SPHERE_LODS.iter().map(|&(stacks, slices)| Obj::sphere(stacks, slices)).collect()
+        };
+
+        Assets {
+            spheres,
+            moon: load_model(Obj::load("assets/models/moon.obj", normal_mode)),
+            spaceship: load_model(Obj::load("assets/models/Navesita.obj", normal_mode)),
+            // Ruido y parámetros de cada tipo de superficie, creados una sola vez
+            materials: MaterialRegistry::new(if sphere_obj { 0 } else { bake_resolution }),
+            starfield: Starfield::new(),
+        }
+    }
+}
+
+// Teselación (stacks, slices) de cada nivel de detalle de la esfera, del más fino al más grueso
+const SPHERE_LODS: [(usize, usize); 3] = [(48, 64), (24, 32), (12, 16)];
+
+// Radio en pantalla (pixeles) por debajo del cual se baja al siguiente nivel. La silueta de una
+// esfera de n divisiones se separa r * (1 - cos(PI / n)) pixeles de la real: con estos umbrales
+// el error queda bajo medio pixel y el cambio de nivel no se nota
+const LOD_THRESHOLDS: [f32; 2] = [96.0, 24.0];
+
+// Radio aproximado en pixeles de una esfera de radio `radius` vista desde la cámara
+pub fn projected_radius(center: Vec3, radius: f32, uniforms: &Uniforms, screen_height: usize) -> f32 {
+    let distance = (center - uniforms.camera_position).magnitude();
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    // El elemento (1, 1) de la proyección es 1 / tan(fov / 2)
+    radius / distance * uniforms.projection_matrix[(1, 1)] * screen_height as f32 / 2.0
+}
+
+fn select_lod(radius: f32, levels: usize) -> usize {
+    let level = LOD_THRESHOLDS.iter().filter(|&&threshold| radius < threshold).count();
+    level.min(levels - 1)
+}
+
+// Radio en pantalla (pixeles) desde el que se dibuja la capa de nubes de la Tierra
+const CLOUD_MIN_RADIUS: f32 = 12.0;
+
+// Opciones de dibujo que el usuario puede cambiar en tiempo de ejecución
+pub struct RenderSettings {
+    pub exposure: f32,
+    pub show_axes: bool,
+    pub show_collisions: bool,
+    pub show_labels: bool,
+    pub show_minimap: bool,
+    pub show_overhead: bool,   // Vista cenital en la esquina superior derecha (I)
+    pub show_trail: bool,
+    pub sky_clear: bool,       // El cielo hace de clear del color (`--sky-clear`)
+    pub orbit_display: OrbitDisplay,
+    pub temporal_aa: bool,
+    pub motion_blur: bool,
+    pub depth_of_field: bool,
+    pub focus_nudge: f32,      // Multiplicador de la distancia de enfoque de la profundidad de campo
+    pub render_scale: usize,   // Índice en RENDER_SCALES
+    pub fov: f32,              // Campo de visión elegido con Ctrl + rueda
+    pub current_fov: f32,      // Campo de visión con que se dibuja; se acerca a `fov`, o a ZOOM_FOV mientras se mantiene C
+    pub fov_hud_timer: f32,    // Segundos que quedan mostrando el campo de visión en el HUD
+    pub tuning: Option<Tuning>, // Modo de ajuste de parámetros de los shaders, si está activo
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            exposure: DEFAULT_EXPOSURE,
+            show_axes: false,
+            show_collisions: false,
+            show_labels: true,
+            show_minimap: true,
+            show_overhead: false,
+            show_trail: true,
+            sky_clear: false,
+            orbit_display: OrbitDisplay::Circle,
+            temporal_aa: false,
+            motion_blur: false,
+            depth_of_field: false,
+            focus_nudge: 1.0,
+            render_scale: RENDER_SCALES.len() - 1,
+            fov: DEFAULT_FOV,
+            current_fov: DEFAULT_FOV,
+            fov_hud_timer: 0.0,
+            tuning: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn adjust_fov(&mut self, delta: f32) {
+        self.fov = (self.fov + delta).clamp(MIN_FOV, MAX_FOV);
+    }
+
+    // Lleva el campo de visión actual hacia el objetivo de forma exponencial, así el zoom de la tecla C
+    // entra y sale suave; mientras cambia se mantiene visible en el HUD
+    pub fn update_fov(&mut self, zoom_held: bool, delta_time: f32) {
+        let target = if zoom_held { ZOOM_FOV } else { self.fov };
+        let previous = self.current_fov;
+        self.current_fov = target + (self.current_fov - target) * (-FOV_SMOOTHING * delta_time).exp();
+        if (self.current_fov - target).abs() < 0.05 {
+            self.current_fov = target;
+        }
+        if self.current_fov != previous {
+            self.fov_hud_timer = FOV_HUD_SECONDS;
+        } else {
+            self.fov_hud_timer = (self.fov_hud_timer - delta_time).max(0.0);
+        }
+    }
+
+    // Sensibilidad del mouse proporcional a lo que abarca un pixel: con zoom la mira se mueve
+    // igual de rápido en pantalla que con el campo de visión inicial
+    pub fn mouse_sensitivity(&self) -> f32 {
+        let half_angle = |fov: f32| (fov * PI / 360.0).tan();
+        MOUSE_SENSITIVITY * half_angle(self.current_fov) / half_angle(DEFAULT_FOV)
+    }
+}
+
+// Depuración: contorno de la esfera de colisión de cada cuerpo, visto desde la cámara, más su ecuador.
+// Se dibuja encima de todo para que también se vea cuando la esfera queda dentro de la superficie
+const COLLISION_SEGMENTS: usize = 64;
+
+fn draw_collision_spheres(framebuffer: &mut Framebuffer, solar_system: &SolarSystem, uniforms: &Uniforms) {
+    framebuffer.set_current_color(0x40FF70);
+    for body in &solar_system.bodies {
+        let radius = body.collision_radius;
+        let to_eye = uniforms.camera_position - body.position;
+        let distance = to_eye.magnitude();
+        if distance <= radius {
+            continue;
+        }
+
+        // El contorno de una esfera vista desde afuera es un círculo más chico y más cerca del ojo
+        let view = to_eye / distance;
+        let outline_center = body.position + view * (radius * radius / distance);
+        let outline_radius = radius * (1.0 - (radius / distance).powi(2)).sqrt();
+        let helper = if view.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let u = view.cross(&helper).normalize();
+        let v = view.cross(&u);
+        let axis = body.rotation_axis();
+        let equator_u = axis.cross(&helper).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+        let equator_v = axis.cross(&equator_u);
+
+        let circles = [(outline_center, u, v, outline_radius), (body.position, equator_u, equator_v, radius)];
+        for (center, u, v, circle_radius) in circles {
+            let point = |i: usize| {
+                let angle = i as f32 / COLLISION_SEGMENTS as f32 * 2.0 * PI;
+                center + (u * angle.cos() + v * angle.sin()) * circle_radius
+            };
+            for i in 0..COLLISION_SEGMENTS {
+                if let Some((start, end)) = project_segment(point(i), point(i + 1), uniforms) {
+                    line(framebuffer, Vec3::new(start.x, start.y, NEAR_PLANE), Vec3::new(end.x, end.y, NEAR_PLANE));
+                }
+            }
+        }
+    }
+}
+
+// Radio en el mundo de la malla con que se dibuja un cuerpo (las lunas usan moon.obj, que no es unitario)
+fn body_radius(body: &CelestialBody, assets: &Assets) -> f32 {
+    let model = if body.parent.is_some() { &assets.moon } else { &assets.spheres[0] };
+    body.max_scale() * model.bounding_radius()
+}
+
+// Escena de una vista, desde el clear: estrellas, órbitas, cuerpos, nave, estelas y escape, con la cámara,
+// la proyección y el viewport que ya tiene `uniforms`
+fn draw_scene<'a>(
+    framebuffer: &mut Framebuffer,
+    uniforms: &mut Uniforms<'a>,
+    assets: &'a Assets,
+    solar_system: &'a SolarSystem,
+    settings: &RenderSettings,
+    scratch: &mut RenderScratch,
+    stats: &mut FrameStats,
+) {
+    let frustum = Frustum::from_matrix(&(uniforms.projection_matrix * uniforms.view_matrix));
+
+    // Con `--sky-clear` el color no se limpia: el cielo se dibuja después de lo opaco, solo donde no llegó
+    // nada, y hace de clear. El modo alambre escribe profundidad sin color, así que ahí no se puede
+    let sky_clear = settings.sky_clear && uniforms.render_mode != RenderMode::Wireframe;
+    let clear_start = Instant::now();
+    if sky_clear {
+        framebuffer.clear_depth();
+    } else {
+        framebuffer.clear();
+    }
+    stats.add(Stage::Clear, clear_start.elapsed());
+
+    // Fondo de estrellas a profundidad máxima, tapado por todo lo que se dibuja después
+    if !sky_clear {
+        assets.starfield.draw(framebuffer, uniforms);
+    }
+
+    // Renderizar órbitas como segmentos conectados con prueba de profundidad
+    framebuffer.set_current_color(0x444444);
+    for (i, body) in solar_system.bodies.iter().enumerate() {
+        if settings.orbit_display.shows_circle() && !body.orbit_points.is_empty() {
+            let orbit_center = solar_system.orbit_center(i);
+            let point_count = body.orbit_points.len();
+            for j in 0..point_count {
+                let start = orbit_center + body.orbit_points[j];
+                let end = orbit_center + body.orbit_points[(j + 1) % point_count];
+                if !frustum.sphere_visible((start + end) * 0.5, (end - start).magnitude() * 0.5) {
+                    continue;
+                }
+                if let Some((screen_start, screen_end)) = project_segment(start, end, uniforms) {
+                    line(framebuffer, screen_start, screen_end);
+                }
+            }
+        }
+    }
+    
+    // Depuración: eje de rotación de cada cuerpo, atravesando ambos polos
+    if settings.show_axes {
+        framebuffer.set_current_color(0xFF4040);
+        for body in &solar_system.bodies {
+            let half_length = body.scale * 1.6;
+            let start = body.position - body.rotation_axis() * half_length;
+            let end = body.position + body.rotation_axis() * half_length;
+            if let Some((screen_start, screen_end)) = project_segment(start, end, uniforms) {
+                line(framebuffer, screen_start, screen_end);
+            }
+        }
+    }
+
+    // Esferas que dan sombra, incluidas las que quedan fuera de cámara: su sombra puede caer en algo visible
+    uniforms.occluders.clear();
+    uniforms.rings.clear();
+    uniforms.sun_radius = 0.0;
+    for body in &solar_system.bodies {
+        if body.shader == ShaderKind::Sun {
+            uniforms.sun_radius = body.scale;
+        } else {
+            uniforms.occluders.push(Occluder { center: body.position, radius: body_radius(body, assets) });
+        }
+        if let Some(ring) = body.ring {
+            let normal = ring_model_matrix(body, &ring) * Vec4::new(0.0, 1.0, 0.0, 0.0);
+            uniforms.rings.push(RingOccluder {
+                center: body.position,
+                normal: Vec3::new(normal.x, normal.y, normal.z).normalize(),
+                inner_radius: body.scale * ring.inner_scale,
+                outer_radius: body.scale * ring.outer_scale,
+                shader: ring.shader,
+            });
+        }
+    }
+
+    // Cada cuerpo visible, la nave, los anillos y las colas pasan por la cola de dibujo, que los ordena
+    let mut queue = RenderQueue::new();
+    for body in &solar_system.bodies {
+        let distance = (body.position - uniforms.camera_position).magnitude();
+
+        if frustum.sphere_visible(body.position, body_radius(body, assets)) {
+            // Las lunas usan el modelo moon.obj; el resto, la esfera con el detalle que pide su tamaño en pantalla
+            let radius = projected_radius(body.position, body.scale, uniforms, framebuffer.height);
+            let level = select_lod(radius, assets.spheres.len());
+            let mesh = if body.parent.is_some() {
+                Mesh::Moon
+            } else {
+                if stats.visible {
+                    stats.record_lod(&body.name, level, radius);
+                }
+                Mesh::Sphere(level)
+            };
+            let model_matrix = create_shaped_model_matrix(body.position, body.scale, body.shape, body.model_rotation());
+            queue.push(mesh, model_matrix, body.shader, body.surface.as_ref(), body.texture.as_ref(), distance);
+
+            // Las nubes de la Tierra van en una segunda esfera algo más grande; el shader las hace girar
+            // a su propio ritmo. Si el planeta ocupa pocos pixeles no se notarían y se omiten, y en los
+            // modos alambre y plano taparían la malla del planeta con otra igual
+            let clouds = uniforms.render_mode == RenderMode::Filled && radius >= CLOUD_MIN_RADIUS;
+            if body.shader == ShaderKind::Earth && body.parent.is_none() && clouds {
+                let shell_matrix = create_model_matrix(body.position, body.scale * CLOUD_SHELL_SCALE, body.model_rotation());
+                queue.push(Mesh::CloudShell(level), shell_matrix, ShaderKind::Clouds, None, None, distance);
+            }
+        } else {
+            stats.record_culled();
+        }
+
+        if let Some(ring) = body.ring {
+            queue.push(Mesh::Ring(ring), ring_model_matrix(body, &ring), ring.shader, None, None, distance);
+        }
+        if body.has_tail {
+            let tail = Mesh::CometTail { position: body.position, length: body.scale * 0.8 };
+            queue.push(tail, Mat4::identity(), ShaderKind::CometTail, None, None, distance);
+        }
+    }
+
+    // En cabina la cámara está dentro del modelo, así que la nave no se dibuja
+    let ship = &solar_system.spaceship;
+    if !solar_system.cockpit_view() {
+        if frustum.sphere_visible(ship.position, spaceship::MODEL_SCALE * assets.spaceship.bounding_radius()) {
+            let model_matrix = create_model_matrix(ship.position, spaceship::MODEL_SCALE, ship.rotation);
+            let distance = (ship.position - uniforms.camera_position).magnitude();
+            queue.push(Mesh::Spaceship, model_matrix, ShaderKind::Spaceship, None, None, distance);
+        } else {
+            stats.record_culled();
+        }
+    }
+
+    if sky_clear {
+        // Las estrellas quedan igual que dibujadas primero: lo opaco ya escribió su profundidad y las tapa
+        let transparent = queue.execute_opaque(framebuffer, uniforms, assets, scratch, stats);
+        let sky_start = Instant::now();
+        framebuffer.fill_sky();
+        stats.add(Stage::Clear, sky_start.elapsed());
+        assets.starfield.draw(framebuffer, uniforms);
+        transparent.execute(framebuffer, uniforms, assets, scratch, stats);
+    } else {
+        queue.execute(framebuffer, uniforms, assets, scratch, stats);
+    }
+
+    // Estela de la nave: translúcida, sobre todo lo opaco; en cabina su último tramo queda detrás de la cámara
+    if settings.show_trail {
+        solar_system.trail.draw(framebuffer, uniforms, solar_system.spaceship.position);
+    }
+    // Estelas orbitales, con el color del cuerpo en el minimapa
+    if settings.orbit_display.shows_trail() {
+        for body in &solar_system.bodies {
+            body.orbit_trail.draw(framebuffer, uniforms, body.position, minimap::body_color(body));
+        }
+    }
+
+    if settings.show_collisions {
+        draw_collision_spheres(framebuffer, solar_system, uniforms);
+    }
+
+    // Escape de la nave: aditivo, así no depende del orden respecto de los anillos y colas
+    solar_system.exhaust.draw(framebuffer, uniforms);
+}
+
+// Dibuja una vista adicional en su framebuffer con la cámara que ya tiene `uniforms`: la misma escena
+// que la principal, sin anti-aliasing temporal y con solo bloom y tone mapping
+fn render_inset<'a>(
+    inset: &mut InsetTarget,
+    uniforms: &mut Uniforms<'a>,
+    assets: &'a Assets,
+    solar_system: &'a SolarSystem,
+    settings: &RenderSettings,
+    scratch: &mut RenderScratch,
+    stats: &mut FrameStats,
+) {
+    let InsetTarget { width, height, fov, scene: framebuffer, post, .. } = inset;
+    uniforms.viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+    let (near, far) = clip_planes(uniforms.camera_position, solar_system.system_radius());
+    uniforms.set_projection(create_perspective_matrix(*fov, near, far, *width as f32, *height as f32));
+
+    draw_scene(framebuffer, uniforms, assets, solar_system, settings, scratch, stats);
+
+    let post_start = Instant::now();
+    if uniforms.debug_mode != DebugMode::Off {
+        debug_view::resolve(framebuffer, uniforms.debug_mode);
+    } else {
+        let context = FrameContext {
+            uniforms,
+            solar_system,
+            exposure: settings.exposure,
+            delta_time: scratch.delta_time,
+            sun_visibility: post::sun_visibility(framebuffer, uniforms, solar_system),
+            motion_blur: false,
+            focus: None,
+            ship_radius: spaceship::MODEL_SCALE * assets.spaceship.bounding_radius(),
+        };
+        post.run(framebuffer, &context);
+    }
+    stats.add(Stage::Post, post_start.elapsed());
+}
+
+// Dibuja un frame completo en `targets.display` (escena, post-procesamiento y HUD) sin tocar la ventana,
+// con las vistas adicionales ya dibujadas en `targets.insets` encima. La cámara principal llega en
+// `uniforms` (ver `Uniforms::set_camera`)
+fn render_frame<'a>(
+    targets: &mut RenderTargets,
+    uniforms: &mut Uniforms<'a>,
+    assets: &'a Assets,
+    solar_system: &'a SolarSystem,
+    settings: &RenderSettings,
+    scratch: &mut RenderScratch,
+    stats: &mut FrameStats,
+) {
+    let RenderTargets { scene: framebuffer, display, insets, .. } = targets;
+    uniforms.viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+
+    // Los planos de recorte siguen a la cámara para que las órbitas exteriores no se corten al acercarse.
+    // La relación de aspecto es la de la ventana, la escena puede tener un redondeo distinto
+    let (near, far) = clip_planes(uniforms.camera_position, solar_system.system_radius());
+    uniforms.set_projection(create_perspective_matrix(settings.current_fov, near, far, display.width as f32, display.height as f32));
+
+    // Con el anti-aliasing temporal la escena se rasteriza con la proyección desplazada; lo que se
+    // dibuja después (etiquetas, destello, HUD) usa la original para no temblar
+    let projection_matrix = uniforms.projection_matrix;
+    let motion = camera_motion(uniforms, solar_system, framebuffer.height, scratch);
+    let temporal_aa = settings.temporal_aa && uniforms.debug_mode == DebugMode::Off;
+    if !temporal_aa || motion > TAA_RESET_MOTION {
+        framebuffer.reset_accumulation();
+    }
+    if temporal_aa {
+        let (jitter_x, jitter_y) = taa_jitter(framebuffer.accumulated_frames);
+        uniforms.projection_matrix[(0, 2)] += jitter_x * 2.0 / framebuffer.width as f32;
+        uniforms.projection_matrix[(1, 2)] += jitter_y * 2.0 / framebuffer.height as f32;
+    }
+
+    draw_scene(framebuffer, uniforms, assets, solar_system, settings, scratch, stats);
+
+    uniforms.projection_matrix = projection_matrix;
+
+    // Post-procesamiento: bloom si algún fragmento emitió luz, luego tone mapping de todo el frame.
+    // Las vistas de depuración muestran los valores crudos, sin bloom ni tone mapping
+    let post_start = Instant::now();
+    if uniforms.debug_mode != DebugMode::Off {
+        debug_view::resolve(framebuffer, uniforms.debug_mode);
+    } else {
+        let sun_visibility = post::sun_visibility(framebuffer, uniforms, solar_system);
+        let focus = settings.depth_of_field.then(|| post::depth_of_field_focus(framebuffer, uniforms, solar_system, settings.focus_nudge));
+        let context = FrameContext {
+            uniforms,
+            solar_system,
+            exposure: settings.exposure,
+            delta_time: scratch.delta_time,
+            sun_visibility,
+            motion_blur: settings.motion_blur,
+            focus,
+            ship_radius: spaceship::MODEL_SCALE * assets.spaceship.bounding_radius(),
+        };
+        scratch.post.run(framebuffer, &context);
+        if temporal_aa {
+            framebuffer.accumulate(TAA_MIN_WEIGHT);
+        }
+    }
+
+    stats.add(Stage::Post, post_start.elapsed());
+
+    // Texto después del post-procesamiento y de ampliar la escena, para que el bloom no lo difumine
+    // y se vea nítido a cualquier escala. A escala 1 no hay nada que ampliar: el texto va sobre la escena,
+    // que es lo que se presenta, sin copia intermedia. Desde acá las coordenadas son las de la ventana
+    let present_start = Instant::now();
+    let display = if targets_match(framebuffer, display) {
+        framebuffer
+    } else {
+        display.upscale_from(framebuffer);
+        display
+    };
+    uniforms.viewport_matrix = create_viewport_matrix(display.width as f32, display.height as f32);
+    stats.add(Stage::Present, present_start.elapsed());
+
+    if solar_system.cockpit_view() {
+        draw_cockpit_rim(display);
+    }
+    if settings.show_labels {
+        labels::draw(display, solar_system, uniforms);
+    }
+    // Las vistas adicionales tapan las etiquetas de la principal; la cenital ocupa la esquina del minimapa
+    for inset in insets.iter() {
+        display.upscale_into(&inset.scene, inset.x, inset.y, inset.width, inset.height);
+        viewport::draw_border(display, inset.x, inset.y, inset.width, inset.height);
+    }
+    if settings.show_minimap && insets.is_empty() {
+        minimap::draw(display, solar_system, uniforms);
+    }
+    targeting::draw(display, solar_system, uniforms);
+    draw_hud(display, solar_system, uniforms, &scratch.post, settings, &assets.materials);
+    if stats.visible {
+        draw_stats_overlay(display, stats);
+    }
+}
+
+// Estado del renderizador entre frames: framebuffers, recursos, opciones del usuario y buffers de trabajo.
+// El llamador solo le pasa la simulación, la cámara y el reloj de los shaders
+pub struct Renderer {
+    targets: RenderTargets,
+    assets: Assets,
+    scratch: RenderScratch,
+    pub settings: RenderSettings,
+    pub stats: FrameStats,
+    pub debug_mode: DebugMode,
+    pub render_mode: RenderMode,
+    viewport_matrix: Mat4,          // Viewport y vista-proyección inversa del último frame, para `screen_to_ray`
+    inverse_view_projection: Mat4,
+    overhead_camera: Camera,        // Cámara fija de la vista cenital
+}
+
+impl Renderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_assets(width, height, Assets::load(false, DEFAULT_BAKE_RESOLUTION, NormalMode::Smooth))
+    }
+
+    pub fn with_assets(width: usize, height: usize, assets: Assets) -> Self {
+        // Post-procesamiento en orden de aplicación: lo que va antes del tone mapping trabaja con los
+        // colores crudos, lo que va después se suma sobre la imagen final
+        let post = PostPipeline::new(vec![
+            Box::new(SunHaze),
+            Box::new(Bloom),
+            Box::new(ToneMap::new()),
+            Box::new(MotionBlur::new()),
+            Box::new(DepthOfField::new()),
+            Box::new(LensFlare),
+            Box::new(WarpDistortion::new()),
+            Box::new(ImpactFlash),
+        ]);
+
+        Renderer {
+            targets: RenderTargets::new(width, height),
+            assets,
+            scratch: RenderScratch::new(post),
+            settings: RenderSettings::default(),
+            stats: FrameStats::new(),
+            debug_mode: DebugMode::Off,
+            render_mode: RenderMode::Filled,
+            viewport_matrix: create_viewport_matrix(width as f32, height as f32),
+            inverse_view_projection: Mat4::identity(),
+            overhead_camera: viewport::overhead_camera(),
+        }
+    }
+
+    // Tamaño de la ventana; los framebuffers solo se recrean si cambió
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let display = &self.targets.display;
+        if width > 0 && height > 0 && (width != display.width || height != display.height) {
+            self.targets.resize(width, height);
+        }
+    }
+
+    // Radios de las mallas con que se dibujan los cuerpos, para ajustar las esferas de colisión
+    pub fn mesh_radii(&self) -> MeshRadii {
+        MeshRadii { sphere: self.assets.spheres[0].bounding_radius(), moon: self.assets.moon.bounding_radius() }
+    }
+
+    pub fn post_mut(&mut self) -> &mut PostPipeline {
+        &mut self.scratch.post
+    }
+
+    // Entra al modo de ajuste con el material `kind` (si tiene parámetros), o sale de él y vuelve a
+    // hornear las superficies que se ajustaron
+    pub fn toggle_tuning(&mut self, kind: Option<ShaderKind>) {
+        if self.settings.tuning.take().is_some() {
+            self.assets.materials.bake_missing();
+        } else {
+            self.settings.tuning = kind.and_then(Tuning::new);
+        }
+    }
+
+    pub fn handle_tuning_input(&mut self, input: &InputState) {
+        if let Some(tuning) = &mut self.settings.tuning {
+            tuning.handle_input(input, &mut self.assets.materials);
+        }
+    }
+
+    // Dibuja un frame completo y lo devuelve con el tamaño de la ventana. `time` es el reloj de los
+    // shaders (ver `advance_clock`) y `delta_time` los segundos reales desde el frame anterior;
+    // la proyección y el viewport se recalculan en cada llamada
+    pub fn render_scene(&mut self, solar_system: &SolarSystem, camera: &Camera, time: f32, delta_time: f32) -> &Framebuffer {
+        self.scratch.delta_time = delta_time;
+        let mut uniforms = Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: Mat4::identity(),
+            inverse_view: Mat4::identity(),
+            inverse_projection: Mat4::identity(),
+            time,
+            material: self.assets.materials.get(ShaderKind::Earth),
+            surface: None,
+            surface_texture: None,
+            texture: None,
+            camera_position: camera.eye,
+            occluders: Vec::new(),
+            rings: Vec::new(),
+            sun_radius: 0.0,
+            debug_mode: self.debug_mode,
+            render_mode: self.render_mode,
+        };
+
+        // Vistas de este frame en orden: la principal y, si está activa, la cenital. Las adicionales se
+        // dibujan primero en sus framebuffers y la principal las compone sobre el suyo
+        self.targets.set_scale(RENDER_SCALES[self.settings.render_scale]);
+        let (width, height) = (self.targets.display.width, self.targets.display.height);
+        let overhead = self.settings.show_overhead.then(|| Viewport::overhead(width, height, &self.overhead_camera)).flatten();
+        let viewports: Vec<Viewport> = std::iter::once(Viewport::full(width, height, self.settings.current_fov, camera)).chain(overhead).collect();
+        self.targets.prepare_insets(&viewports[1..]);
+        for (inset, viewport) in self.targets.insets.iter_mut().zip(&viewports[1..]) {
+            uniforms.set_camera(viewport.camera);
+            render_inset(inset, &mut uniforms, &self.assets, solar_system, &self.settings, &mut self.scratch, &mut self.stats);
+        }
+
+        match solar_system.impact().and_then(Impact::shake) {
+            Some(shake) => uniforms.set_shaken_camera(viewports[0].camera, shake),
+            None => uniforms.set_camera(viewports[0].camera),
+        }
+        render_frame(&mut self.targets, &mut uniforms, &self.assets, solar_system, &self.settings, &mut self.scratch, &mut self.stats);
+
+        self.viewport_matrix = uniforms.viewport_matrix;
+        self.inverse_view_projection = uniforms.inverse_view * uniforms.inverse_projection;
+        self.targets.output()
+    }
+
+    // Rayo en espacio mundial que parte del ojo y pasa por el pixel (x, y) de la ventana, con la cámara del último frame
+    pub fn screen_to_ray(&self, x: f32, y: f32) -> Option<(Vec3, Vec3)> {
+        let inverse_viewport = self.viewport_matrix.try_inverse()?;
+
+        let ndc = inverse_viewport * Vec4::new(x, y, 0.0, 1.0);
+        let near = self.inverse_view_projection * Vec4::new(ndc.x, ndc.y, -1.0, 1.0);
+        let far = self.inverse_view_projection * Vec4::new(ndc.x, ndc.y, 1.0, 1.0);
+
+        let near = Vec3::new(near.x, near.y, near.z) / near.w;
+        let far = Vec3::new(far.x, far.y, far.z) / far.w;
+
+        Some((near, (far - near).normalize()))
+    }
+}
+
+// Modelo OBJ cargado, o termina mostrando el archivo, la línea y el motivo del error
+fn load_model(model: Result<Obj, ObjError>) -> Obj {
+    model.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::{drift, oscillation};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Asignador que cuenta las peticiones de memoria de cada hilo, así las pruebas que corren en
+    // paralelo no se mezclan
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn count() {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count();
+            System.alloc(layout)
+        }
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count();
+            System.alloc_zeroed(layout)
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count();
+            System.realloc(ptr, layout, new_size)
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(|count| count.get())
+    }
+
+    const WIDTH: usize = 170;
+    const HEIGHT: usize = 200;
+
+    // Esfera unitaria en el origen vista desde z = 4, ocupando buena parte del framebuffer
+    fn sphere_uniforms(material: &Material, render_mode: RenderMode) -> Uniforms<'_> {
+        let camera = Camera::new(Vec3::new(0.0, 0.0, 4.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let mut uniforms = Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: create_perspective_matrix(DEFAULT_FOV, 0.1, 100.0, WIDTH as f32, HEIGHT as f32),
+            viewport_matrix: create_viewport_matrix(WIDTH as f32, HEIGHT as f32),
+            inverse_view: Mat4::identity(),
+            inverse_projection: Mat4::identity(),
+            time: 0.0,
+            material,
+            surface: None,
+            surface_texture: None,
+            texture: None,
+            camera_position: camera.eye,
+            occluders: Vec::new(),
+            rings: Vec::new(),
+            sun_radius: 0.0,
+            debug_mode: DebugMode::Off,
+            render_mode,
+        };
+        uniforms.set_camera(&camera);
+        uniforms
+    }
+
+    // Dibuja la esfera dos veces con los mismos buffers de trabajo; devuelve los fragmentos
+    // sombreados y las peticiones de memoria de la segunda llamada
+    fn second_draw_allocations(draw: fn(&mut Framebuffer, &Uniforms, &[Vertex], &[u32], &mut RenderScratch, &mut FrameStats), render_mode: RenderMode) -> (usize, usize) {
+        let material = Material::new(ShaderKind::Earth);
+        let uniforms = sphere_uniforms(&material, render_mode);
+        let sphere = Obj::sphere(24, 32);
+        let (vertices, indices) = sphere.get_indexed();
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+        let mut scratch = RenderScratch::new(PostPipeline::new(Vec::new()));
+        let mut stats = FrameStats::new();
+
+        draw(&mut framebuffer, &uniforms, vertices, indices, &mut scratch, &mut stats);
+        framebuffer.clear();
+        let before = allocations();
+        draw(&mut framebuffer, &uniforms, vertices, indices, &mut scratch, &mut stats);
+        (scratch.fragments.len(), allocations() - before)
+    }
+
+    #[test]
+    fn render_reuses_scratch_buffers() {
+        for draw in [render, render_sphere, render_additive] {
+            let (fragments, allocated) = second_draw_allocations(draw, RenderMode::Filled);
+            assert!(fragments > 1000, "la esfera debería cubrir el framebuffer, solo hubo {} fragmentos", fragments);
+            assert_eq!(allocated, 0);
+        }
+    }
+
+    #[test]
+    fn wireframe_reuses_scratch_buffers() {
+        let (fragments, allocated) = second_draw_allocations(render, RenderMode::Wireframe);
+        assert!(fragments > 1000);
+        assert_eq!(allocated, 0);
+    }
+
+    // Períodos de las animaciones de los shaders; todos dividen a CLOCK_PERIOD
+    const ANIMATION_PERIODS: [f32; 10] = [1.0 / 3.0, 0.5, 1.0, 4.0, 10.0, 36.0, 360.0, 600.0, 1800.0, CLOCK_PERIOD];
+
+    #[test]
+    fn clock_wraps_smoothly_over_hours() {
+        // Seis horas y media a 60 FPS: el reloj da la vuelta seis veces
+        let step = 1.0 / 60.0;
+        let (mut clock, mut wraps) = (0.0, 0);
+        for _ in 0..(6.5 * 3600.0 * 60.0) as usize {
+            let next = advance_clock(clock, step);
+            assert!((0.0..CLOCK_PERIOD).contains(&next), "reloj fuera de rango: {}", next);
+            if next < clock {
+                wraps += 1;
+                assert!((clock + step - CLOCK_PERIOD - next).abs() < 1e-3, "salto al dar la vuelta: {} -> {}", clock, next);
+            } else {
+                assert!((next - clock - step).abs() < 1e-3, "paso irregular: {} -> {}", clock, next);
+            }
+            clock = next;
+        }
+        assert_eq!(wraps, 6);
+    }
+
+    #[test]
+    fn animations_are_continuous_across_the_wrap() {
+        let before = CLOCK_PERIOD - 1e-3;
+        for period in ANIMATION_PERIODS {
+            let (sin_before, cos_before) = oscillation(before, period).sin_cos();
+            let (sin_after, cos_after) = oscillation(0.0, period).sin_cos();
+            assert!((sin_before - sin_after).abs() < 0.05 && (cos_before - cos_after).abs() < 0.05, "período {}", period);
+        }
+        for velocity in [Vec3::new(0.06, 0.06, 0.0), Vec3::new(1.6, 0.0, 0.0), Vec3::new(0.24, -0.18, 0.12), Vec3::new(0.0, 0.0, 0.24)] {
+            let jump = (drift(before, velocity) - drift(0.0, velocity)).magnitude();
+            assert!(jump < 0.01, "velocidad {:?}: salto de {}", velocity, jump);
+        }
+    }
+}
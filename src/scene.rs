@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use crate::material::ShaderKind;
+use crate::solar_system::{BodyError, COLLISION_MARGIN};
+use crate::texture::TextureError;
+
+// Descripción de una escena cargada desde un archivo TOML (ver assets/scene.toml)
+#[derive(Debug, Deserialize)]
+pub struct SceneConfig {
+    // Separación entre la superficie visible de cada cuerpo y su esfera de colisión
+    #[serde(default = "default_collision_margin")]
+    pub collision_margin: f32,
+    pub bodies: Vec<BodyConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BodyConfig {
+    pub name: String,
+    pub orbital_radius: f32,
+    pub orbital_speed: f32,
+    #[serde(default = "default_rotation_speed")]
+    pub rotation_speed: f32,
+    // Inclinación del eje de rotación en grados
+    #[serde(default)]
+    pub axial_tilt: f32,
+    pub scale: f32,
+    // Escala relativa por eje (x, y, z) para cuerpos irregulares
+    #[serde(default = "default_shape")]
+    pub shape: [f32; 3],
+    // Rotación sincrónica: el cuerpo muestra siempre la misma cara a su padre (el eje x apunta a él)
+    #[serde(default)]
+    pub tidally_locked: bool,
+    pub shader: ShaderKind,
+    #[serde(default)]
+    pub rings: bool,
+    #[serde(default)]
+    pub ring: Option<RingConfig>,
+    // Con excentricidad > 0 la órbita es elíptica y orbital_radius es el semieje mayor
+    #[serde(default)]
+    pub eccentricity: f32,
+    #[serde(default)]
+    pub periapsis_argument: f32,
+    // Posición inicial en la órbita (anomalía media en t = 0), en radianes
+    #[serde(default)]
+    pub phase: f32,
+    #[serde(default)]
+    pub tail: bool,
+    // Masa para la gravedad sobre la nave; sin masa el cuerpo no la atrae
+    #[serde(default)]
+    pub mass: f32,
+    // Imagen equirectangular (PNG o JPEG, ruta relativa al directorio de trabajo) para el color base
+    #[serde(default)]
+    pub texture: Option<PathBuf>,
+    #[serde(default)]
+    pub moons: Vec<BodyConfig>,
+}
+
+// Anillo con radios en múltiplos de la escala del cuerpo; tiene prioridad sobre `rings = true`
+#[derive(Debug, Deserialize)]
+pub struct RingConfig {
+    pub inner_scale: f32,
+    pub outer_scale: f32,
+    // Inclinación respecto del ecuador del cuerpo, en grados
+    #[serde(default)]
+    pub tilt: f32,
+    #[serde(default = "default_ring_shader")]
+    pub shader: ShaderKind,
+}
+
+fn default_ring_shader() -> ShaderKind {
+    ShaderKind::Ring
+}
+
+fn default_shape() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_collision_margin() -> f32 {
+    COLLISION_MARGIN
+}
+
+fn default_rotation_speed() -> f32 {
+    0.02
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(String, std::io::Error),
+    Parse(String, toml::de::Error),
+    InvalidEccentricity { body: String, eccentricity: f32 },
+    InvalidRing { body: String, inner_scale: f32, outer_scale: f32 },
+    InvalidShape { body: String, shape: [f32; 3] },
+    InvalidBody(BodyError),
+    Texture(TextureError),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(path, err) => write!(f, "no se pudo leer la escena '{}': {}", path, err),
+            SceneError::Parse(path, err) => write!(f, "escena inválida '{}': {}", path, err),
+            SceneError::InvalidEccentricity { body, eccentricity } => {
+                write!(f, "el cuerpo '{}' tiene una excentricidad inválida ({}), debe estar en [0, 1)", body, eccentricity)
+            }
+            SceneError::InvalidRing { body, inner_scale, outer_scale } => {
+                write!(f, "el anillo de '{}' es inválido ({} a {}), debe cumplir 0 < inner_scale < outer_scale", body, inner_scale, outer_scale)
+            }
+            SceneError::InvalidShape { body, shape } => {
+                write!(f, "el cuerpo '{}' tiene una forma inválida ({:?}), cada factor debe ser positivo", body, shape)
+            }
+            SceneError::InvalidBody(err) => write!(f, "{}", err),
+            SceneError::Texture(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+pub fn load_scene(path: &str) -> Result<SceneConfig, SceneError> {
+    let contents = fs::read_to_string(path).map_err(|err| SceneError::Io(path.to_string(), err))?;
+    let scene: SceneConfig = toml::from_str(&contents).map_err(|err| SceneError::Parse(path.to_string(), err))?;
+
+    for body in &scene.bodies {
+        validate_body(body)?;
+    }
+
+    Ok(scene)
+}
+
+fn validate_body(body: &BodyConfig) -> Result<(), SceneError> {
+    if !(0.0..1.0).contains(&body.eccentricity) {
+        return Err(SceneError::InvalidEccentricity {
+            body: body.name.clone(),
+            eccentricity: body.eccentricity,
+        });
+    }
+    if let Some(ring) = &body.ring {
+        if !(ring.inner_scale > 0.0 && ring.inner_scale < ring.outer_scale) {
+            return Err(SceneError::InvalidRing {
+                body: body.name.clone(),
+                inner_scale: ring.inner_scale,
+                outer_scale: ring.outer_scale,
+            });
+        }
+    }
+    if !body.shape.iter().all(|&factor| factor > 0.0 && factor.is_finite()) {
+        return Err(SceneError::InvalidShape { body: body.name.clone(), shape: body.shape });
+    }
+    for moon in &body.moons {
+        validate_body(moon)?;
+    }
+    Ok(())
+}
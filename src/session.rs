@@ -0,0 +1,201 @@
+// Grabación y reproducción de sesiones: `--record sesion.bin` guarda en cada frame el paso de tiempo,
+// la pose de la cámara, el cuerpo seleccionado y las teclas; `--replay sesion.bin` los vuelve a aplicar
+// en lugar de la entrada real, así el mismo recorrido se puede repetir exacto (p. ej. para comparar shaders)
+use minifb::{Key, KeyRepeat, Window};
+use nalgebra_glm::Vec3;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::camera::Camera;
+
+const MAGIC: &[u8; 4] = b"GSTS";
+const VERSION: u8 = 1;
+
+// Teclas que lee la aplicación; en el archivo cada una es un bit según su posición en esta tabla.
+// Agregar teclas solo al final, para que las sesiones ya grabadas se sigan leyendo igual
+const KEYS: [Key; 59] = [
+    Key::W, Key::A, Key::S, Key::D, Key::X,
+    Key::LeftShift, Key::RightShift, Key::LeftCtrl, Key::RightCtrl, Key::LeftAlt, Key::RightAlt,
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8,
+    Key::O, Key::F, Key::B, Key::G, Key::L,
+    Key::Enter, Key::Space, Key::LeftBracket, Key::RightBracket, Key::Minus, Key::Equal,
+    Key::PageUp, Key::PageDown,
+    Key::M, Key::T, Key::F9, Key::F10, Key::F11, Key::R, Key::C, Key::V, Key::N, Key::E, Key::Q, Key::K, Key::P, Key::F12, Key::J, Key::Comma, Key::Period, Key::I,
+];
+
+// Bytes por frame: paso de tiempo, 9 floats de pose, cuerpo seleccionado y tres máscaras de teclas
+const FRAME_SIZE: usize = 4 + 9 * 4 + 4 + 3 * 8;
+
+// Estado del teclado en un frame: teclas mantenidas, recién presionadas y presionadas con autorepetición
+#[derive(Clone, Copy, Default)]
+pub struct InputState {
+    down: u64,
+    pressed: u64,
+    repeated: u64,
+}
+
+impl InputState {
+    pub fn from_window(window: &Window) -> Self {
+        let mut state = InputState::default();
+        for (bit, &key) in KEYS.iter().enumerate() {
+            let mask = 1u64 << bit;
+            if window.is_key_down(key) {
+                state.down |= mask;
+            }
+            if window.is_key_pressed(key, KeyRepeat::No) {
+                state.pressed |= mask;
+            }
+            if window.is_key_pressed(key, KeyRepeat::Yes) {
+                state.repeated |= mask;
+            }
+        }
+        state
+    }
+
+    // Copia sin las teclas `keys`, como si no se hubieran tocado
+    pub fn without(&self, keys: &[Key]) -> Self {
+        let mask = keys.iter().fold(0, |mask, &key| mask | key_mask(key));
+        InputState { down: self.down & !mask, pressed: self.pressed & !mask, repeated: self.repeated & !mask }
+    }
+
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.down & key_mask(key) != 0
+    }
+
+    pub fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        let keys = match repeat {
+            KeyRepeat::No => self.pressed,
+            KeyRepeat::Yes => self.repeated,
+        };
+        keys & key_mask(key) != 0
+    }
+}
+
+fn key_mask(key: Key) -> u64 {
+    KEYS.iter().position(|&known| known == key).map_or(0, |bit| 1u64 << bit)
+}
+
+// Todo lo que hace falta para reproducir un frame
+#[derive(Clone, Copy)]
+pub struct SessionFrame {
+    pub timestep: f32,
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub up: Vec3,
+    pub selected_body: Option<usize>,
+    pub input: InputState,
+}
+
+impl SessionFrame {
+    // Impone la pose grabada, igual que al volver a un marcador
+    pub fn apply_camera(&self, camera: &mut Camera) {
+        camera.up = self.up;
+        camera.look_at(self.eye, self.center);
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(PathBuf, std::io::Error),
+    Format(PathBuf, String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Io(path, err) => write!(f, "no se pudo acceder a la sesión '{}': {}", path.display(), err),
+            SessionError::Format(path, reason) => write!(f, "sesión inválida '{}': {}", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+// Escribe los frames a medida que ocurren, así una sesión interrumpida conserva lo grabado
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &str) -> Result<Self, SessionError> {
+        let path = PathBuf::from(path);
+        let file = File::create(&path).map_err(|err| SessionError::Io(path.clone(), err))?;
+        let mut recorder = SessionRecorder { writer: BufWriter::new(file), path };
+        recorder.write(MAGIC)?;
+        recorder.write(&[VERSION])?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, frame: &SessionFrame) -> Result<(), SessionError> {
+        let mut bytes = Vec::with_capacity(FRAME_SIZE);
+        bytes.extend_from_slice(&frame.timestep.to_le_bytes());
+        for vector in [frame.eye, frame.center, frame.up] {
+            for value in [vector.x, vector.y, vector.z] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let selected = frame.selected_body.map_or(-1, |index| index as i32);
+        bytes.extend_from_slice(&selected.to_le_bytes());
+        for keys in [frame.input.down, frame.input.pressed, frame.input.repeated] {
+            bytes.extend_from_slice(&keys.to_le_bytes());
+        }
+        self.write(&bytes)
+    }
+
+    pub fn finish(mut self) -> Result<(), SessionError> {
+        self.writer.flush().map_err(|err| SessionError::Io(self.path.clone(), err))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), SessionError> {
+        self.writer.write_all(bytes).map_err(|err| SessionError::Io(self.path.clone(), err))
+    }
+}
+
+// Sesión grabada completa, leída de una vez
+pub struct Session {
+    frames: Vec<SessionFrame>,
+}
+
+impl Session {
+    pub fn load(path: &str) -> Result<Self, SessionError> {
+        let path = Path::new(path);
+        let bytes = fs::read(path).map_err(|err| SessionError::Io(path.to_path_buf(), err))?;
+        let invalid = |reason: String| SessionError::Format(path.to_path_buf(), reason);
+
+        if bytes.len() < 5 || &bytes[..4] != MAGIC {
+            return Err(invalid("no es un archivo de sesión".to_string()));
+        }
+        if bytes[4] != VERSION {
+            return Err(invalid(format!("versión {} no soportada", bytes[4])));
+        }
+        let body = &bytes[5..];
+        if body.is_empty() || body.len() % FRAME_SIZE != 0 {
+            return Err(invalid(format!("{} bytes de frames, se esperaba un múltiplo de {}", body.len(), FRAME_SIZE)));
+        }
+
+        let frames = body.chunks_exact(FRAME_SIZE).map(parse_frame).collect();
+        Ok(Session { frames })
+    }
+
+    pub fn frames(&self) -> &[SessionFrame] {
+        &self.frames
+    }
+}
+
+fn parse_frame(bytes: &[u8]) -> SessionFrame {
+    let f32_at = |i: usize| f32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+    let vector_at = |i: usize| Vec3::new(f32_at(i), f32_at(i + 4), f32_at(i + 8));
+    let selected = i32::from_le_bytes(bytes[40..44].try_into().unwrap());
+    SessionFrame {
+        timestep: f32_at(0),
+        eye: vector_at(4),
+        center: vector_at(16),
+        up: vector_at(28),
+        selected_body: usize::try_from(selected).ok(),
+        input: InputState { down: u64_at(44), pressed: u64_at(52), repeated: u64_at(60) },
+    }
+}
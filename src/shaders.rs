@@ -1,9 +1,38 @@
-use nalgebra_glm::{dot, mat4_to_mat3, normalize, Mat3, Vec2, Vec3, Vec4};
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{cross, dot, mat4_to_mat3, normalize, Mat3, Vec2, Vec3, Vec4};
+use crate::debug_view::{DebugMode, RenderMode};
 use crate::vertex::Vertex;
-use crate::Uniforms;
+use crate::renderer::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
+use crate::material::{ShaderKind, ShaderParam, ShaderParams};
+use crate::shadows::{light_visibility, ring_crossing};
+use std::f32::consts::TAU;
 
+// The shader clock is simulation time in seconds, wrapped every CLOCK_PERIOD so it never grows
+// large enough to lose float precision. Every animation repeats over a period that divides it,
+// so the wrap itself is invisible
+pub const CLOCK_PERIOD: f32 = 3600.0;
+
+// Angle of an oscillation that completes one cycle every `period` seconds
+pub fn oscillation(time: f32, period: f32) -> f32 {
+  (time % period) / period * TAU
+}
+
+// Offset of a noise pattern moving at `velocity` units per second. A straight line would grow
+// without bound, so the offset follows a circle that closes once per CLOCK_PERIOD instead; the
+// curve is so wide that the drift looks steady
+pub fn drift(time: f32, velocity: Vec3) -> Vec3 {
+  let speed = velocity.magnitude();
+  if speed <= f32::EPSILON {
+      return Vec3::zeros();
+  }
+  let direction = velocity / speed;
+  let side = if direction.z.abs() < 0.9 { normalize(&cross(&direction, &Vec3::z())) } else { Vec3::x() };
+  let radius = speed * CLOCK_PERIOD / TAU;
+  let angle = oscillation(time, CLOCK_PERIOD);
+  (direction * angle.sin() + side * (1.0 - angle.cos())) * radius
+}
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   // Transform position
@@ -39,70 +68,591 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     normal: vertex.normal,
     tex_coords: vertex.tex_coords,
     color: vertex.color,
-    transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+    // z keeps the view-space depth (clip w) for perspective-correct interpolation and the depth test
+    transformed_position: Vec3::new(screen_position.x, screen_position.y, w),
     transformed_normal,
   }
 }
 
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  match uniforms.current_shader {
-      // Different Earth-like planets
-      1 => tropical_earth_shader(fragment, uniforms, time),    // Lush tropical version
-      3 => frozen_earth_shader(fragment, uniforms, time),      // Ice age version
-      5 => desert_earth_shader(fragment, uniforms, time),      // Desert world
-      2 => ocean_earth_shader(fragment, uniforms, time),       // Water world
-      7 => jungle_earth_shader(fragment, uniforms, time),      // Dense jungle world
-      4 => volcanic_earth_shader(fragment, uniforms, time),    // Volcanic active Earth
-      6 => ancient_earth_shader(fragment, uniforms, time),     // Primordial Earth
-      8 => spaceship_shader(fragment, uniforms, time),
-      _ => (Color::new(0, 0, 0), 0),
+// Returns the shaded color (its alpha below 1.0 blends it, 0.0 discards it) and the emitted light;
+// the emissive color feeds the bloom pass with its own hue
+pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  if let Some(color) = debug_shader(fragment, uniforms) {
+      return (color, Color::black());
+  }
+  if uniforms.render_mode == RenderMode::Flat {
+      return (flat_shader(fragment, uniforms), Color::black());
+  }
+  let (color, emission) = match (uniforms.texture, fragment.uv) {
+      (Some(texture), Some(uv)) => textured_shader(fragment, uniforms, time, texture.sample(uv)),
+      _ => surface_shader(fragment, uniforms, time),
+  };
+  let (color, emission) = match uniforms.material.atmosphere {
+      Some(atmosphere) => apply_atmosphere_rim(fragment, uniforms, color, emission, atmosphere.color, atmosphere.strength),
+      None => (color, emission),
+  };
+  apply_shadows(fragment, uniforms, color, emission)
+}
+
+// Checker squares across u in the UV debug view (half as many across v, so they stay square on a sphere)
+const UV_CHECKER_CELLS: f32 = 64.0;
+
+// Replaces the shading in the per-fragment debug views; depth and overdraw are resolved after drawing
+fn debug_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+  let as_rgb = |v: Vec3| Color::from_float(v.x * 0.5 + 0.5, v.y * 0.5 + 0.5, v.z * 0.5 + 0.5);
+  let normal = normalize(&fragment.normal);
+  match uniforms.debug_mode {
+      DebugMode::ViewNormals => Some(as_rgb(normalize(&(mat4_to_mat3(&uniforms.view_matrix) * normal)))),
+      DebugMode::WorldNormals => Some(as_rgb(normal)),
+      DebugMode::Uv => {
+          // A fine checker on top of the UV gradient: seams and pole pinching break its squares
+          let uv = fragment.uv.unwrap_or(Vec2::new(0.0, 0.0));
+          let cell = (uv.x * UV_CHECKER_CELLS).floor() + (uv.y * UV_CHECKER_CELLS * 0.5).floor();
+          let shade = if cell.rem_euclid(2.0) < 1.0 { 1.0 } else { 0.5 };
+          Some(Color::from_float(uv.x.fract() * shade, uv.y.fract() * shade, 0.0))
+      }
+      DebugMode::Lighting => {
+          if uniforms.material.kind == ShaderKind::Sun {
+              return Some(Color::from_float(1.0, 1.0, 1.0));
+          }
+          let world = world_position(fragment, uniforms);
+          let diffuse = sun_diffuse(&normal, fragment, uniforms);
+          let light = diffuse * light_visibility(world, &uniforms.occluders, uniforms.sun_radius.max(f32::EPSILON)) * ring_transmittance(world, uniforms);
+          Some(Color::from_float(light, light, light))
+      }
+      DebugMode::Off | DebugMode::Depth | DebugMode::Overdraw => None,
+  }
+}
+
+// Flat render mode: a plain gray Lambert term with the triangle's normal (set by the rasterizer), no
+// noise, atmosphere or shadows. The sun has no light of its own to fall on it, so it is lit from the camera
+const FLAT_ALBEDO: f32 = 0.8;
+const FLAT_AMBIENT: f32 = 0.1;
+
+fn flat_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let light_direction = if uniforms.material.kind == ShaderKind::Sun {
+      normalize(&(uniforms.camera_position - world_position(fragment, uniforms)))
+  } else {
+      sun_direction(fragment, uniforms)
+  };
+  let diffuse = dot(&normalize(&fragment.normal), &light_direction).max(0.0);
+  let gray = FLAT_ALBEDO * (FLAT_AMBIENT + (1.0 - FLAT_AMBIENT) * diffuse);
+  Color::from_float(gray, gray, gray)
+}
+
+// Darkens the reflected light where another body or a ring blocks the sun; emitted light is left alone.
+// Rings are shadowed by their planet like any other surface, since it is one of the occluders
+fn apply_shadows(fragment: &Fragment, uniforms: &Uniforms, color: Color, emission: Color) -> (Color, Color) {
+  if matches!(uniforms.material.kind, ShaderKind::Sun | ShaderKind::CometTail) || uniforms.sun_radius <= 0.0 {
+      return (color, emission);
+  }
+  let world = world_position(fragment, uniforms);
+  let visibility = light_visibility(world, &uniforms.occluders, uniforms.sun_radius) * ring_transmittance(world, uniforms);
+  if visibility >= 1.0 {
+      return (color, emission);
+  }
+  // Some ambient light remains inside the umbra, like the unlit side of the shaders
+  (color * (0.2 + 0.8 * visibility), emission)
+}
+
+// Samples across the sun's disc when averaging a ring's opacity for its shadow
+const RING_SHADOW_SAMPLES: usize = 5;
+// Smallest blur (in ring widths) so even a crisp ring casts a slightly soft shadow
+const RING_SHADOW_MIN_BLUR: f32 = 0.01;
+
+// Light that gets through the rings between a point and the sun: the ring opacity where the ray
+// crosses each ring plane, averaged over the width of the sun's disc so bands and gaps blur softly
+fn ring_transmittance(point: Vec3, uniforms: &Uniforms) -> f32 {
+  uniforms.rings.iter()
+      .filter_map(|ring| ring_crossing(point, ring, uniforms.sun_radius).map(|crossing| (ring.shader, crossing)))
+      .map(|(shader, (t, blur))| {
+          let blur = blur.max(RING_SHADOW_MIN_BLUR);
+          if t + blur < 0.0 || t - blur > 1.0 {
+              return 1.0;
+          }
+          let coverage: f32 = (0..RING_SHADOW_SAMPLES)
+              .map(|i| {
+                  let offset = (i as f32 / (RING_SHADOW_SAMPLES - 1) as f32 * 2.0 - 1.0) * blur;
+                  ring_opacity(shader, t + offset)
+              })
+              .sum();
+          1.0 - coverage / RING_SHADOW_SAMPLES as f32
+      })
+      .product()
+}
+
+// Additive fresnel rim near grazing angles, only where the atmosphere is lit by the sun
+fn apply_atmosphere_rim(
+  fragment: &Fragment,
+  uniforms: &Uniforms,
+  color: Color,
+  emission: Color,
+  rim_color: Color,
+  strength: f32,
+) -> (Color, Color) {
+  let world = world_position(fragment, uniforms);
+  let normal = normalize(&fragment.normal);
+  let view_dir = normalize(&(uniforms.camera_position - world));
+  let fresnel = (1.0 - dot(&normal, &view_dir).max(0.0)).powf(4.0);
+
+  // Light scattered in the atmosphere reaches slightly past the terminator
+  let lit = smoothstep(-0.3, 0.3, dot(&normal, &sun_direction(fragment, uniforms))) * sun_irradiance(&world);
+  let rim = fresnel * strength * lit;
+  if rim <= 0.0 {
+      return (color, emission);
+  }
+
+  (color + rim_color * rim, emission + rim_color * (rim * 0.15))
+}
+
+fn surface_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  match uniforms.material.kind {
+      ShaderKind::Earth => aurora(fragment, uniforms, time, tropical_earth_shader(fragment, uniforms, time)), // Lush tropical version
+      ShaderKind::Mercury => frozen_earth_shader(fragment, uniforms),            // Ice age version
+      ShaderKind::Jupiter => desert_earth_shader(fragment, uniforms),            // Desert world
+      ShaderKind::Mars => ocean_earth_shader(fragment, uniforms, time),          // Water world
+      ShaderKind::Sun => sun_shader(fragment, uniforms, time),                   // Animated sun surface
+      ShaderKind::Saturn => saturn_shader(fragment, uniforms),                   // Pale gold banded giant
+      ShaderKind::Uranus => ancient_earth_shader(fragment, uniforms, time),      // Primordial Earth
+      ShaderKind::Moon => moon_shader(fragment, uniforms),                       // Grey cratered moons
+      ShaderKind::Ring => ring_shader(fragment, uniforms),                                 // Banded planetary rings
+      ShaderKind::UranusRing => uranus_ring_shader(fragment, uniforms),                    // Narrow dark ringlets
+      ShaderKind::Neptune => neptune_shader(fragment, uniforms, time),           // Ice giant with a dark storm and cirrus
+      ShaderKind::Venus => venus_shader(fragment, uniforms, time),               // Cloud-covered Venus
+      ShaderKind::Spaceship => spaceship_shader(fragment, uniforms, time),
+      ShaderKind::CometTail => comet_tail_shader(fragment),
+      ShaderKind::Clouds => cloud_shader(fragment, uniforms, time),              // Earth's drifting cloud shell
+      ShaderKind::Rocky => rocky_shader(fragment, uniforms),                     // Generated cratered rock
+      ShaderKind::GasGiant => gas_giant_shader(fragment, uniforms, time),        // Generated banded giant
+      ShaderKind::Icy => icy_shader(fragment, uniforms),                         // Generated cracked ice
+  }
+}
+
+// An image texture replaces the procedural base color. The sun keeps glowing with it; everything
+// else is lit like the procedural surfaces, cloud shadows included
+fn textured_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32, base_color: Color) -> (Color, Color) {
+  if uniforms.material.kind == ShaderKind::Sun {
+      return (base_color, base_color * uniforms.material.params.get(ShaderParam::EmissionGain));
   }
+  let normal = normalize(&fragment.normal);
+  let color = shade_lit(base_color, sun_diffuse(&normal, fragment, uniforms), uniforms) * cloud_shadow(fragment, uniforms, time);
+  (color, Color::black())
+}
+
+fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+    let base_color = Color::from_float(0.55, 0.58, 0.65);  // Brushed metal
+    let light_dir = sun_direction(fragment, uniforms);
+    let normal = normalize(&fragment.normal);
+    let diffuse = sun_diffuse(&normal, fragment, uniforms);
+    let metallic_effect = (fragment.vertex_position.x * 10.0 + oscillation(time, 1.0)).sin() * 0.1 + 0.9;
+
+    // Blinn-Phong highlight of the sun on the hull
+    let view_dir = normalize(&(uniforms.camera_position - world_position(fragment, uniforms)));
+    let half_vector = normalize(&(light_dir + view_dir));
+    let specular = if dot(&normal, &light_dir) > 0.0 { dot(&normal, &half_vector).max(0.0).powf(32.0) } else { 0.0 };
+    let highlight = Color::from_float(1.0, 1.0, 1.0) * (specular * sun_irradiance(&world_position(fragment, uniforms)));
+
+    // Engine glow on the rear of the hull (model-space -z)
+    let engine_start = -5.0;
+    if fragment.vertex_position.z < engine_start {
+        let flicker = oscillation(time, 1.0 / 3.0).sin() * 0.1 + 0.9;
+        let engine_color = Color::from_float(0.4, 0.7, 1.0) * flicker;
+        return (engine_color, engine_color * 0.8);
+    }
+
+    (shade_lit(base_color, diffuse * metallic_effect, uniforms) + highlight, Color::black())
+}
+
+// Normalized radial position across the ring mesh (0 at the inner edge, 1 at the outer edge)
+fn ring_radial(fragment: &Fragment) -> f32 {
+    fragment.uv.map_or(0.0, |uv| uv.x).clamp(0.0, 1.0)
+}
+
+// Opacity of a ring at radial position `t`, shared by its shader and the shadow it casts
+fn ring_opacity(shader: ShaderKind, t: f32) -> f32 {
+    if !(0.0..=1.0).contains(&t) {
+        return 0.0;
+    }
+    match shader {
+        ShaderKind::UranusRing => uranus_ring_alpha(t),
+        _ => ring_alpha(t),
+    }
+}
+
+fn ring_alpha(t: f32) -> f32 {
+    // Gaps: a thin inner gap and the Cassini division are fully discarded
+    let inner_gap = t < 0.08;
+    let cassini_division = (0.62..0.68).contains(&t);
+    if inner_gap || cassini_division {
+        return 0.0;
+    }
+
+    // Fine ringlets modulate the opacity; the outer ring is thinner than the inner one
+    let ringlets = (t * 90.0).sin() * 0.5 + 0.5;
+    let density = if t < 0.62 { 0.8 } else { 0.5 };
+    let edge_fade = ((1.0 - t) * 10.0).min(1.0);
+    (density * (0.6 + 0.4 * ringlets) * edge_fade).clamp(0.0, 1.0)
 }
 
-fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-    let base_color = Color::from_float(0.2, 0.2, 0.7);  // Blue-ish
-    let light_dir = normalize(&Vec3::new(1.0, 1.0, 1.0));
+// The ring is a flat disc, so light it from either side
+fn ring_diffuse(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
     let normal = normalize(&fragment.normal);
-    let diffuse = dot(&normal, &light_dir).max(0.0);
-    let metallic_effect = ((fragment.vertex_position.x * 10.0 + time as f32 * 0.1).sin() * 0.1 + 0.9);
-    (base_color * (0.2 + 0.8 * diffuse * metallic_effect), 0)
+    dot(&normal, &sun_direction(fragment, uniforms)).abs() * sun_irradiance(&world_position(fragment, uniforms))
 }
-fn tropical_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
+
+fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+    let t = ring_radial(fragment);
+
+    // Alternating bright and dark bands of icy material
+    let bright_band = Color::from_float(0.93, 0.86, 0.72);
+    let dark_band = Color::from_float(0.62, 0.55, 0.45);
+    let bands = (t * 24.0).sin() * 0.5 + 0.5;
+    let base_color = dark_band.lerp(&bright_band, bands);
+
+    (shade_lit(base_color, ring_diffuse(fragment, uniforms), uniforms).with_alpha(ring_alpha(t)), Color::black())
+}
+
+// Centers (normalized radius) and half widths of Uranus' narrow rings; the outermost one is the widest
+const URANUS_RINGLETS: [(f32, f32); 5] = [
+    (0.08, 0.03),
+    (0.30, 0.025),
+    (0.50, 0.03),
+    (0.70, 0.025),
+    (0.92, 0.06),
+];
+
+// Each ringlet fades out smoothly from its center; everything in between is empty space
+fn uranus_ring_alpha(t: f32) -> f32 {
+    let ringlets = URANUS_RINGLETS.iter()
+        .map(|&(center, half_width)| (1.0 - (t - center).abs() / half_width).max(0.0))
+        .fold(0.0, f32::max);
+    ringlets * 0.85
+}
+
+fn uranus_ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+    let alpha = uranus_ring_alpha(ring_radial(fragment));
+    if alpha <= 0.0 {
+        return (Color::black().with_alpha(0.0), Color::black());
+    }
+
+    // Dark, slightly bluish grey dust
+    let base_color = Color::from_float(0.42, 0.45, 0.5);
+    (shade_lit(base_color, ring_diffuse(fragment, uniforms), uniforms).with_alpha(alpha), Color::black())
+}
+
+fn comet_tail_shader(fragment: &Fragment) -> (Color, Color) {
+    // uv.x goes from 0 at the nucleus to 1 at the tip; vertex color carries the brightness
+    let along_tail = fragment.uv.map_or(0.0, |uv| uv.x).clamp(0.0, 1.0);
+    let fade = (1.0 - along_tail).powf(1.5);
+    let color = fragment.color * fade;
+    (color, color * 0.7)
+}
+
+fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+    let noise_value = uniforms.material.noise.get_noise_3d(
+        fragment.vertex_position.x,
+        fragment.vertex_position.y,
+        fragment.vertex_position.z
+    );
+
+    // Grey regolith with darker crater floors
+    let highland_color = Color::from_float(0.65, 0.65, 0.62);
+    let crater_color = Color::from_float(0.35, 0.35, 0.34);
+
+    let base_color = crater_color.lerp(&highland_color, (noise_value + 1.0) / 2.0);
+
+    let (albedo, normal) = craters(fragment, uniforms);
+    let diffuse = sun_diffuse(&normal, fragment, uniforms);
+
+    (shade_lit(base_color * albedo, diffuse, uniforms), Color::black())
+}
+
+// Crater layers: (cells per unit of the model-space direction, depth weight)
+const CRATER_LAYERS: [(f32, f32); 2] = [(3.0, 1.0), (9.0, 0.35)];
+const CRATER_RADIUS: f32 = 0.55;       // Bowl radius in cell units
+const CRATER_RIM_WIDTH: f32 = 0.12;
+const CRATER_RIM_HEIGHT: f32 = 0.35;
+const CRATER_BUMP: f32 = 0.06;         // How strongly the relief tilts the normal
+const CRATER_STEP: f32 = 0.004;        // Finite difference step for the relief gradient
+
+// Albedo factor (darker floors, brighter rims) and the world-space normal tilted by the crater relief.
+// The field is sampled on the model-space direction, so the craters turn with the body
+fn craters(fragment: &Fragment, uniforms: &Uniforms) -> (f32, Vec3) {
+  let normal = normalize(&fragment.normal);
+  let surface_craters = uniforms.surface.as_ref().and_then(|surface| surface.craters.as_ref());
+  let noise = match surface_craters.or(uniforms.material.craters.as_ref()) {
+      Some(noise) => noise,
+      None => return (1.0, normal),
+  };
+  let direction = normalize(&fragment.vertex_position);
+  let height = crater_height(noise, direction);
+
+  // Relief gradient along two tangents of the sphere, brought to world space with the model rotation
+  let helper = if direction.y.abs() < 0.99 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+  let tangent = normalize(&cross(&helper, &direction));
+  let bitangent = cross(&direction, &tangent);
+  let slope_t = (crater_height(noise, direction + tangent * CRATER_STEP) - height) / CRATER_STEP;
+  let slope_b = (crater_height(noise, direction + bitangent * CRATER_STEP) - height) / CRATER_STEP;
+  let gradient = mat4_to_mat3(&uniforms.model_matrix) * (tangent * slope_t + bitangent * slope_b);
+  let perturbed = normalize(&(normal - gradient * CRATER_BUMP));
+
+  ((1.0 + 0.3 * height).clamp(0.5, 1.3), perturbed)
+}
+
+// Relief of the crater field: bowls below zero surrounded by a raised rim
+fn crater_height(noise: &FastNoiseLite, direction: Vec3) -> f32 {
+  CRATER_LAYERS.iter()
+      .map(|&(scale, weight)| {
+          let p = direction * scale;
+          let distance = noise.get_noise_3d(p.x, p.y, p.z) + 1.0;
+          let bowl = if distance < CRATER_RADIUS { (distance / CRATER_RADIUS).powi(2) - 1.0 } else { 0.0 };
+          let rim = (-((distance - CRATER_RADIUS) / CRATER_RIM_WIDTH).powi(2)).exp() * CRATER_RIM_HEIGHT;
+          (bowl + rim) * weight
+      })
+      .sum()
+}
+// World-space position of a fragment, from its interpolated model-space position
+fn world_position(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+  let world = uniforms.model_matrix * Vec4::new(
       fragment.vertex_position.x,
-      fragment.vertex_position.y
+      fragment.vertex_position.y,
+      fragment.vertex_position.z,
+      1.0
   );
-  
-  // Vibrant tropical colors
-  let water_color_1 = Color::from_float(0.0, 0.5, 0.8);     // Bright turquoise
-  let water_color_2 = Color::from_float(0.1, 0.6, 0.9);     // Light blue
-  let land_color_1 = Color::from_float(0.2, 0.8, 0.3);      // Bright green
-  let land_color_2 = Color::from_float(0.8, 0.8, 0.2);      // Sandy yellow
-  let cloud_color = Color::from_float(1.0, 1.0, 1.0);       // White clouds
+  Vec3::new(world.x, world.y, world.z)
+}
 
-  let land_threshold = 0.4;
+// Direction from the fragment towards the sun, which sits at the origin
+fn sun_direction(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+  normalize(&(-world_position(fragment, uniforms)))
+}
+
+// Sunlight weakens with distance from the sun: intensity * (reference / d)^exponent. A real 1/d^2 would
+// leave the outer planets black, so the exponent is stylized and the result never drops below the floor
+const SUN_LIGHT_INTENSITY: f32 = 1.0;
+const SUN_LIGHT_FALLOFF: f32 = 0.7;
+const SUN_LIGHT_REFERENCE: f32 = 7.0;   // Earth's orbit: bodies this far away get exactly the intensity
+const SUN_LIGHT_FLOOR: f32 = 0.25;
+
+fn sun_irradiance(world: &Vec3) -> f32 {
+  let distance = world.magnitude().max(f32::EPSILON);
+  (SUN_LIGHT_INTENSITY * (SUN_LIGHT_REFERENCE / distance).powf(SUN_LIGHT_FALLOFF)).max(SUN_LIGHT_FLOOR)
+}
 
+// Faint blue-gray light from the sky and the other bodies, so night sides stay barely readable
+const SPACE_AMBIENT: Color = Color::from_hex(0x121620);
+
+// Wrapped diffuse towards the sun: (N.L + wrap) / (1 + wrap) lets the light reach past the
+// terminator, so it fades over a band set by the material instead of a hard cut. Scaled by the distance falloff
+fn sun_diffuse(normal: &Vec3, fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let wrap = uniforms.material.lighting.wrap;
+  let world = world_position(fragment, uniforms);
+  ((dot(normal, &normalize(&-world)) + wrap) / (1.0 + wrap)).max(0.0) * sun_irradiance(&world)
+}
+
+// Surface color under the sun: the material's ambient floor, the diffuse term and the space ambient
+fn shade_lit(base_color: Color, diffuse: f32, uniforms: &Uniforms) -> Color {
+  let ambient = uniforms.material.lighting.ambient;
+  base_color * (ambient + (1.0 - ambient) * diffuse) + base_color * SPACE_AMBIENT
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+  let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+  t * t * (3.0 - 2.0 * t)
+}
+
+// Vibrant tropical palette
+const TROPICAL_WATER_1: Color = Color::from_hex(0x007FCC);   // Bright turquoise
+const TROPICAL_WATER_2: Color = Color::from_hex(0x1999E5);   // Light blue
+const TROPICAL_LAND_1: Color = Color::from_hex(0x33CC4C);    // Bright green
+const TROPICAL_LAND_2: Color = Color::from_hex(0xCCCC33);    // Sandy yellow
+const CITY_LIGHTS: Color = Color::from_hex(0xFFBF59);        // Warm sodium lights
+const SUN_GLINT: Color = Color::from_hex(0xFFFFFF);
+
+// Static part of a surface shader at a point of the unit sphere: its base color and the noise value
+// it came from. The planets that have one get it baked into a texture at startup (see surface_texture.rs)
+pub type StaticSurface = fn(&FastNoiseLite, &ShaderParams, Vec3) -> (Color, f32);
+
+pub fn static_surface(kind: ShaderKind) -> Option<StaticSurface> {
+  match kind {
+      ShaderKind::Earth => Some(tropical_surface),
+      ShaderKind::Mercury => Some(frozen_surface),
+      ShaderKind::Jupiter => Some(desert_surface),
+      ShaderKind::Saturn => Some(saturn_surface),
+      ShaderKind::Uranus => Some(ancient_surface),
+      ShaderKind::Rocky => Some(rocky_surface),
+      ShaderKind::Icy => Some(icy_surface),
+      _ => None,
+  }
+}
+
+// The static surface of a fragment: bilinear lookup in the baked texture when the body has one,
+// otherwise the live noise at its model-space position
+fn sample_surface(fragment: &Fragment, uniforms: &Uniforms, live: StaticSurface) -> (Color, f32) {
+  match (uniforms.surface_texture, fragment.uv) {
+      (Some(texture), Some(uv)) => texture.sample(uv),
+      _ => live(&uniforms.material.noise, &uniforms.material.params, fragment.vertex_position),
+  }
+}
+
+fn tropical_surface(noise: &FastNoiseLite, params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  let noise_value = noise.get_noise_2d(position.x, position.y);
+  let land_threshold = params.get(ShaderParam::LandThreshold);
   let base_color = if noise_value > land_threshold {
-      land_color_1.lerp(&land_color_2, (noise_value - land_threshold) / (1.0 - land_threshold))
+      TROPICAL_LAND_1.lerp(&TROPICAL_LAND_2, (noise_value - land_threshold) / (1.0 - land_threshold))
   } else {
-      water_color_1.lerp(&water_color_2, noise_value / land_threshold)
+      TROPICAL_WATER_1.lerp(&TROPICAL_WATER_2, noise_value / land_threshold)
   };
+  (base_color, noise_value)
+}
+
+fn tropical_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let (base_color, noise_value) = sample_surface(fragment, uniforms, tropical_surface);
+  let is_land = noise_value > uniforms.material.params.get(ShaderParam::LandThreshold);
+
+  // Sunlight comes from the sun at the origin
+  let light_dir = sun_direction(fragment, uniforms);
+  let normal = normalize(&fragment.normal);
+  let sun_angle = dot(&normal, &light_dir);
+  let mut day_color = shade_lit(base_color, sun_diffuse(&normal, fragment, uniforms), uniforms) * cloud_shadow(fragment, uniforms, time);
+
+  // Oceans reflect the sun: a tight glint plus a broader sheen (Blinn-Phong half vector)
+  if !is_land && sun_angle > 0.0 {
+      let view_dir = normalize(&(uniforms.camera_position - world_position(fragment, uniforms)));
+      let half_vector = normalize(&(light_dir + view_dir));
+      let alignment = dot(&normal, &half_vector).max(0.0);
+      let glint = alignment.powf(128.0);
+      let sheen = alignment.powf(16.0) * 0.25;
+      day_color = day_color + SUN_GLINT * ((glint + sheen) * sun_irradiance(&world_position(fragment, uniforms)));
+  }
+
+  // Smooth terminator: 1.0 on the day side, 0.0 on the night side
+  let daylight = smoothstep(-0.15, 0.2, sun_angle);
+  if daylight >= 1.0 || !is_land {
+      return (day_color, Color::black());
+  }
+
+  // City lights: sparse high-frequency points restricted to land, twinkling slightly
+  let city_noise = uniforms.material.noise.get_noise_3d(
+      fragment.vertex_position.x * 40.0,
+      fragment.vertex_position.y * 40.0,
+      fragment.vertex_position.z * 40.0
+  );
+  let city_threshold = uniforms.material.params.get(ShaderParam::CityThreshold);
+  if city_noise <= city_threshold {
+      return (day_color, Color::black());
+  }
+
+  let city_strength = ((city_noise - city_threshold) / (1.0 - city_threshold)).min(1.0);
+  let twinkle = (oscillation(time, 0.5) + city_noise * 50.0).sin() * 0.15 + 0.85;
+  let night = (1.0 - daylight) * city_strength * twinkle;
+  let color = day_color.lerp(&CITY_LIGHTS, night);
+  let emission = CITY_LIGHTS * (night * 0.25);
 
-  // Enhanced atmospheric effects
-  let light_position = Vec3::new(1.0, 1.0, 2.0);
-  let light_dir = normalize(&(light_position - fragment.vertex_position));
+  (color, emission)
+}
+
+// Radius of Earth's cloud shell relative to the planet
+pub const CLOUD_SHELL_SCALE: f32 = 1.02;
+// The clouds turn against the surface, one full lap every CLOUD_DRIFT_PERIOD seconds
+const CLOUD_DRIFT_PERIOD: f32 = 360.0;
+const CLOUD_OPACITY: f32 = 0.85;
+const CLOUD_SHADOW_STRENGTH: f32 = 0.35;
+const CLOUD_COLOR: Color = Color::from_hex(0xF2F4F7);
+
+// Cloud coverage (0 clear sky, 1 overcast) above a model-space direction. The lookup turns about the
+// planet's axis with time, so the shell drifts at its own speed while keeping the surface's model matrix.
+// `cover` shifts the noise range that turns into clouds
+fn cloud_density(noise: &FastNoiseLite, cover: f32, direction: Vec3, time: f32) -> f32 {
+  let (sin, cos) = oscillation(time, CLOUD_DRIFT_PERIOD).sin_cos();
+  let p = Vec3::new(direction.x * cos - direction.z * sin, direction.y, direction.x * sin + direction.z * cos);
+  smoothstep(0.05 - cover, 0.45 - cover, noise.get_noise_3d(p.x, p.y, p.z))
+}
+
+// Translucent shell drawn over Earth; its opacity is the cloud coverage. Only the half facing the
+// camera is kept, otherwise the far side would blend twice around the planet's limb
+fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
   let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0);
+  let view_dir = uniforms.camera_position - world_position(fragment, uniforms);
+  if dot(&normal, &view_dir) <= 0.0 {
+      return (Color::black().with_alpha(0.0), Color::black());
+  }
+  let cover = uniforms.material.params.get(ShaderParam::CloudCover);
+  let density = cloud_density(&uniforms.material.noise, cover, fragment.vertex_position, time);
+  let color = shade_lit(CLOUD_COLOR, sun_diffuse(&normal, fragment, uniforms), uniforms);
+  (color.with_alpha(density * CLOUD_OPACITY), Color::black())
+}
 
-  (base_color * (0.2 + 0.8 * diffuse), 0)
+// Sunlight left under the clouds: the coverage is looked up where the ray towards the sun crosses
+// the shell, so the shadows sit slightly off their clouds. The offset is capped near the terminator
+fn cloud_shadow(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> f32 {
+  let noise = match &uniforms.material.detail_noise {
+      Some(noise) => noise,
+      None => return 1.0,
+  };
+  let direction = normalize(&fragment.vertex_position);
+  let light = normalize(&(mat4_to_mat3(&uniforms.model_matrix).transpose() * sun_direction(fragment, uniforms)));
+  let facing = dot(&direction, &light);
+  if facing <= 0.0 {
+      return 1.0;
+  }
+  let above = normalize(&(direction + light * ((CLOUD_SHELL_SCALE - 1.0) / facing.max(0.2))));
+  1.0 - CLOUD_SHADOW_STRENGTH * cloud_density(noise, uniforms.material.params.get(ShaderParam::CloudCover), above, time)
 }
 
-fn frozen_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 2.0,
-      fragment.vertex_position.y * 2.0
+// Aurora ovals: colatitude band (degrees from each pole) and how far the band wobbles with longitude
+const AURORA_INNER: f32 = 8.0;
+const AURORA_OUTER: f32 = 22.0;
+const AURORA_WOBBLE: f32 = 2.5;
+const AURORA_GREEN: Color = Color::from_hex(0x33FF73);
+const AURORA_PURPLE: Color = Color::from_hex(0x9940E6);
+
+// Faint animated curtains around both poles, only on the night side. The band is found on the
+// model-space direction, so it turns with the planet; part of the light goes to the emissive channel
+fn aurora(fragment: &Fragment, uniforms: &Uniforms, time: f32, (color, emission): (Color, Color)) -> (Color, Color) {
+  let direction = normalize(&fragment.vertex_position);
+  let colatitude = direction.y.abs().clamp(0.0, 1.0).acos().to_degrees();
+  if colatitude > AURORA_OUTER + AURORA_WOBBLE {
+      return (color, emission);
+  }
+
+  let night = 1.0 - smoothstep(-0.2, 0.05, dot(&normalize(&fragment.normal), &sun_direction(fragment, uniforms)));
+  if night <= 0.0 {
+      return (color, emission);
+  }
+
+  // Position across the band (0 at the equator-side edge, 1 at the pole-side edge), bent slowly with longitude
+  let longitude = direction.z.atan2(direction.x);
+  let wobble = (longitude * 3.0 + oscillation(time, 10.0)).sin() * AURORA_WOBBLE;
+  let across = (AURORA_OUTER + wobble - colatitude) / (AURORA_OUTER - AURORA_INNER);
+  if !(0.0..=1.0).contains(&across) {
+      return (color, emission);
+  }
+  let band = smoothstep(0.0, 0.25, across) * smoothstep(1.0, 0.6, across);
+
+  // Curtains: noise sampled on a circle around the pole varies fast along the band and slowly across
+  // it, so the rays look stretched towards the pole; the time axis makes them ripple
+  let (sin_lon, cos_lon) = longitude.sin_cos();
+  let hemisphere = direction.y.signum() * 50.0;
+  let ripple = drift(time, Vec3::new(0.0, 0.0, 0.24));
+  let curtain = uniforms.material.noise.get_noise_3d(
+      cos_lon * 14.0 + hemisphere + ripple.x,
+      sin_lon * 14.0 + ripple.y,
+      across * 0.6 + ripple.z
   );
+  let rays = smoothstep(0.1, 0.7, curtain);
+
+  let strength = band * rays * night * 0.6;
+  if strength <= 0.0 {
+      return (color, emission);
+  }
+  let aurora_color = AURORA_GREEN.lerp(&AURORA_PURPLE, smoothstep(0.4, 1.0, across));
+  (color + aurora_color * strength, emission + aurora_color * (strength * 0.5))
+}
+
+fn frozen_surface(noise: &FastNoiseLite, _params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  let noise_value = noise.get_noise_2d(position.x * 2.0, position.y * 2.0);
 
   // Ice world colors
   let ice_color_1 = Color::from_float(0.8, 0.9, 0.95);      // White ice
@@ -116,46 +666,53 @@ fn frozen_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (
   } else {
       water_color
   };
+  (base_color, noise_value)
+}
 
-  // Crystalline reflection effect
-  let light_dir = normalize(&Vec3::new(1.0, 1.0, 1.0));
-  let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0).powf(1.5);
+fn frozen_earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+  let (base_color, _) = sample_surface(fragment, uniforms, frozen_surface);
+
+  // Crystalline reflection effect, lit by the sun across the crater relief
+  let (albedo, normal) = craters(fragment, uniforms);
+  let diffuse = sun_diffuse(&normal, fragment, uniforms).powf(1.5);
 
-  (base_color * (0.3 + 0.7 * diffuse), 0)
+  (shade_lit(base_color * albedo, diffuse, uniforms), Color::black())
 }
 
-fn desert_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 3.0,
-      fragment.vertex_position.y * 3.0
-  );
+// Desert world palette
+const DESERT_SAND_1: Color = Color::from_hex(0xE5CC7F);      // Light sand
+const DESERT_SAND_2: Color = Color::from_hex(0xCC994C);      // Dark sand
+const DESERT_ROCK: Color = Color::from_hex(0x7F664C);        // Rocky outcrops
+
+fn desert_surface(noise: &FastNoiseLite, params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  let scale = params.get(ShaderParam::DuneScale);
+  let noise_value = noise.get_noise_2d(position.x * scale, position.y * scale);
+
+  let terrain_threshold = params.get(ShaderParam::SandThreshold);
 
-  // Desert world colors
-  let sand_color_1 = Color::from_float(0.9, 0.8, 0.5);      // Light sand
-  let sand_color_2 = Color::from_float(0.8, 0.6, 0.3);      // Dark sand
-  let rock_color = Color::from_float(0.5, 0.4, 0.3);        // Rocky outcrops
-  
-  let terrain_threshold = 0.5;
-  
   let base_color = if noise_value > terrain_threshold {
-      sand_color_1.lerp(&sand_color_2, (noise_value - terrain_threshold) / (1.0 - terrain_threshold))
+      DESERT_SAND_1.lerp(&DESERT_SAND_2, (noise_value - terrain_threshold) / (1.0 - terrain_threshold))
   } else {
-      rock_color.lerp(&sand_color_2, noise_value / terrain_threshold)
+      DESERT_ROCK.lerp(&DESERT_SAND_2, noise_value / terrain_threshold)
   };
+  (base_color, noise_value)
+}
+
+fn desert_earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+  let (base_color, _) = sample_surface(fragment, uniforms, desert_surface);
 
   // Strong sun lighting
-  let light_dir = normalize(&Vec3::new(0.0, 1.0, 0.5));
   let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0);
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
 
-  (base_color * (0.4 + 0.6 * diffuse), 0)
+  (shade_lit(base_color, diffuse, uniforms), Color::black())
 }
 
-fn ocean_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x + time as f32 * 0.001,
-      fragment.vertex_position.y + time as f32 * 0.001
+fn ocean_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let offset = drift(time, Vec3::new(0.06, 0.06, 0.0));
+  let noise_value = uniforms.material.noise.get_noise_2d(
+      fragment.vertex_position.x + offset.x,
+      fragment.vertex_position.y + offset.y
   );
 
   // Ocean world colors with deep trenches
@@ -172,106 +729,309 @@ fn ocean_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (C
   };
 
   // Wave and reflection effects
-  let wave_effect = (time as f32 * 0.01 + noise_value * 10.0).sin() * 0.1;
-  let light_dir = normalize(&Vec3::new(1.0, 1.0, 1.0));
+  let wave_effect = (oscillation(time, 10.0) + noise_value * 10.0).sin() * 0.1;
   let normal = normalize(&fragment.normal);
-  let diffuse = (dot(&normal, &light_dir) + wave_effect).max(0.0);
+  let diffuse = (sun_diffuse(&normal, fragment, uniforms) + wave_effect).max(0.0);
 
-  (base_color * (0.2 + 0.8 * diffuse), 0)
+  (shade_lit(base_color, diffuse, uniforms), Color::black())
 }
 
-fn jungle_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 4.0,
-      fragment.vertex_position.y * 4.0
-  );
+// Saturn: pale gold latitude bands, softer than Jupiter's, with a bluish hexagon around the north pole
+const SATURN_LIGHT: Color = Color::from_hex(0xEADBB0);      // Pale gold zones
+const SATURN_DARK: Color = Color::from_hex(0xB38D57);       // Tawny belts
+const SATURN_POLE: Color = Color::from_hex(0x7F8F96);       // Grey-blue polar hexagon
+const SATURN_HEXAGON_EDGE: f32 = 0.02;                      // Width of the soft hexagon border
+
+fn saturn_surface(noise: &FastNoiseLite, params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  let direction = normalize(&position);
+  let band_scale = params.get(ShaderParam::BandScale);
+
+  // Broad zones and belts with thinner bands on top, bent a little by turbulence stretched along the longitude
+  let turbulence = noise.get_noise_3d(direction.x * 2.0, direction.y * 8.0, direction.z * 2.0);
+  let zones = (direction.y * 9.0 * band_scale + turbulence * 0.5).sin() * 0.5 + 0.5;
+  let bands = (direction.y * 31.0 * band_scale + turbulence * 1.2).sin() * 0.5 + 0.5;
+  let mut base_color = SATURN_DARK.lerp(&SATURN_LIGHT, 0.1 + 0.65 * zones + 0.25 * bands);
+
+  // North polar hexagon: the distance to the axis is compared against a hexagon instead of a circle,
+  // the hexagon's corners sitting at the tunable radius
+  if direction.y > 0.0 {
+      let axis_distance = (direction.x * direction.x + direction.z * direction.z).sqrt();
+      let sector = direction.z.atan2(direction.x).rem_euclid(TAU / 6.0) - TAU / 12.0;
+      let edge = params.get(ShaderParam::HexagonRadius) * (TAU / 12.0).cos() / sector.cos();
+      let inside = 1.0 - smoothstep(edge - SATURN_HEXAGON_EDGE, edge + SATURN_HEXAGON_EDGE, axis_distance);
+      base_color = base_color.lerp(&SATURN_POLE, inside * 0.45);
+  }
+
+  (base_color, zones)
+}
+
+fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+  let (base_color, _) = sample_surface(fragment, uniforms, saturn_surface);
+  let diffuse = sun_diffuse(&normalize(&fragment.normal), fragment, uniforms);
+
+  (shade_lit(base_color, diffuse, uniforms), Color::black())
+}
+
+// Primordial Earth: cooling lava above the terrain threshold
+const ANCIENT_LAVA: Color = Color::from_hex(0xCC6619);      // Cooling lava
+
+fn ancient_surface(noise: &FastNoiseLite, params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  let lava_threshold = params.get(ShaderParam::LavaThreshold);
+  let noise_value = noise.get_noise_2d(position.x * 1.5, position.y * 1.5);
 
-  // Dense vegetation colors
-  let canopy_color_1 = Color::from_float(0.1, 0.6, 0.2);    // Light canopy
-  let canopy_color_2 = Color::from_float(0.0, 0.4, 0.1);    // Dark canopy
-  let water_color = Color::from_float(0.2, 0.3, 0.1);       // Murky water
+  let rock_color = Color::from_float(0.4, 0.3, 0.3);        // Basalt
+  let steam_color = Color::from_float(0.7, 0.7, 0.7);       // Steam clouds
 
-  let vegetation_threshold = 0.2;
+  let steam_threshold = 0.8;
   
-  let base_color = if noise_value > vegetation_threshold {
-      canopy_color_1.lerp(&canopy_color_2, (noise_value - vegetation_threshold) / (1.0 - vegetation_threshold))
+  let base_color = if noise_value > steam_threshold {
+      steam_color
+  } else if noise_value > lava_threshold {
+      ANCIENT_LAVA.lerp(&rock_color, (noise_value - lava_threshold) / (steam_threshold - lava_threshold))
   } else {
-      water_color
+      rock_color
   };
+  (base_color, noise_value)
+}
+
+fn ancient_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let (base_color, noise_value) = sample_surface(fragment, uniforms, ancient_surface);
 
-  // Humid atmosphere effect
-  let light_dir = normalize(&Vec3::new(1.0, 1.0, 0.5));
+  // Atmospheric chaos effect
   let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0);
-  let humidity = (time as f32 * 0.001).sin() * 0.1;
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
+  let chaos = (oscillation(time, 36.0) + noise_value * 3.0).sin() * 0.2;
+
+  let glow = if noise_value > uniforms.material.params.get(ShaderParam::LavaThreshold) { ANCIENT_LAVA * 0.08 } else { Color::black() };
 
-  (base_color * (0.3 + 0.7 * diffuse + humidity), 0)
+  (shade_lit(base_color, (diffuse + chaos).max(0.0), uniforms), glow)
 }
 
-fn volcanic_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 2.0,
-      fragment.vertex_position.y * 2.0
+const NEPTUNE_DEEP: Color = Color::from_hex(0x2C58CC);      // Deep azure below the haze
+const NEPTUNE_HAZE: Color = Color::from_hex(0x4C82F0);      // Brighter azure of the equatorial haze
+const NEPTUNE_BANDS: f32 = 0.07;                            // Methane banding: barely there
+const NEPTUNE_SPOT: Color = Color::from_hex(0x12286E);      // Great Dark Spot
+const NEPTUNE_SPOT_LATITUDE: f32 = -0.35;                   // sin of ~20 degrees south
+const NEPTUNE_SPOT_SIZE: (f32, f32) = (0.3, 0.11);         // Half axes in longitude and latitude
+const NEPTUNE_SPOT_DRIFT_PERIOD: f32 = 1800.0;              // One lap around the planet
+const NEPTUNE_SPOT_LIFE_PERIOD: f32 = 600.0;                // Forms, lingers and dissolves again
+const NEPTUNE_CIRRUS: Color = Color::from_hex(0xF4F8FF);
+// Latitudes (sin) and half widths of the cirrus lanes; the winds there are the fastest in the system
+const NEPTUNE_CIRRUS_LANES: [(f32, f32); 3] = [(0.45, 0.05), (-0.22, 0.035), (-0.62, 0.04)];
+const NEPTUNE_CIRRUS_WIND: f32 = 1.6;
+
+fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let noise = &uniforms.material.noise;
+  let direction = normalize(&fragment.vertex_position);
+  let latitude = direction.y;
+  let longitude = direction.z.atan2(direction.x);
+
+  // Deep azure, a little brighter toward the equator, crossed by faint methane bands
+  let haze = 1.0 - latitude.abs();
+  let wobble = noise.get_noise_3d(direction.x * 2.0, direction.y * 2.0, direction.z * 2.0);
+  let bands = (latitude * 16.0 + wobble * 0.8).sin() * 0.5 + 0.5;
+  let base_color = NEPTUNE_DEEP.lerp(&NEPTUNE_HAZE, haze * 0.6) * (1.0 - NEPTUNE_BANDS + NEPTUNE_BANDS * 2.0 * bands);
+
+  // The Great Dark Spot: an ellipse that drifts slowly in longitude and fades in and out over minutes
+  let life = 0.5 - 0.5 * oscillation(time, NEPTUNE_SPOT_LIFE_PERIOD).cos();
+  let strength = smoothstep(0.3, 0.8, life);
+  let center = oscillation(time, NEPTUNE_SPOT_DRIFT_PERIOD);
+  let along = (longitude - center + std::f32::consts::PI).rem_euclid(TAU) - std::f32::consts::PI;
+  let across = latitude - NEPTUNE_SPOT_LATITUDE;
+  let (size_along, size_across) = NEPTUNE_SPOT_SIZE;
+  // Its edge is ragged: the noise that bends the bands also warps the ellipse
+  let ellipse = (along * (1.0 - latitude * latitude).sqrt() / size_along).powi(2) + (across / size_across).powi(2) + wobble * 0.3;
+  let spot = (1.0 - smoothstep(0.6, 1.0, ellipse)) * strength;
+  let color = base_color.lerp(&NEPTUNE_SPOT, spot * 0.75);
+
+  // Thin white cirrus wisps in narrow lanes, stretched along the longitude and swept fast by the wind
+  let offset = drift(time, Vec3::new(NEPTUNE_CIRRUS_WIND, 0.0, 0.0));
+  let lanes = NEPTUNE_CIRRUS_LANES.iter()
+      .map(|&(center, width)| (1.0 - ((latitude - center) / width).powi(2)).max(0.0))
+      .fold(0.0, f32::max);
+  let wisps = noise.get_noise_3d(direction.x * 1.5 + offset.x, direction.y * 24.0 + offset.y, direction.z * 1.5);
+  let cirrus = lanes * smoothstep(0.15, 0.6, wisps);
+  let color = color.lerp(&NEPTUNE_CIRRUS, cirrus * 0.85);
+
+  let normal = normalize(&fragment.normal);
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
+
+  (shade_lit(color, diffuse, uniforms), Color::black())
+}
+
+fn venus_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let position = fragment.vertex_position;
+  let offset = drift(time, Vec3::new(0.18, 0.0, 0.0));
+
+  // Two cloud layers advected in opposite directions
+  let upper_layer = uniforms.material.noise.get_noise_3d(position.x + offset.x, position.y * 2.0 + offset.y, position.z);
+  let lower_layer = uniforms.material.noise.get_noise_3d(position.x * 2.0 - offset.x, position.y * 4.0 - offset.y, position.z * 2.0);
+  let clouds = (upper_layer * 0.6 + lower_layer * 0.4 + 1.0) / 2.0;
+
+  // Dark streaks bent into chevrons pointing along the equator, sliding with the clouds
+  let chevron = position.x * 3.0 + position.y.abs() * 6.0;
+  let streak = ((chevron * 4.0 + oscillation(time, 4.0)).sin() * 0.5 + 0.5).powf(6.0) * (1.0 - position.y.abs());
+
+  // Pale yellow-cream cloud deck
+  let cloud_light = Color::from_float(0.98, 0.92, 0.72);
+  let cloud_dark = Color::from_float(0.82, 0.72, 0.48);
+  let streak_color = Color::from_float(0.62, 0.52, 0.34);
+
+  let base_color = cloud_dark.lerp(&cloud_light, clouds).lerp(&streak_color, streak * 0.35);
+
+  let normal = normalize(&fragment.normal);
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
+
+  (shade_lit(base_color, diffuse, uniforms), Color::black())
+}
+
+// Deterministic pseudo-random value in [0, 1) for an integer seed
+fn hash01(seed: u32) -> f32 {
+  let mut x = seed.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+  x ^= x >> 15;
+  x = x.wrapping_mul(0x2C1B_3C6D);
+  x ^= x >> 12;
+  (x & 0x00FF_FFFF) as f32 / 16_777_216.0
+}
+
+fn sun_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let position = normalize(&fragment.vertex_position);
+
+  // Convective granulation: high-frequency cells slowly advected over time
+  let convection = drift(time, Vec3::new(0.24, -0.18, 0.12));
+  let granulation = uniforms.material.noise.get_noise_3d(
+      position.x * 8.0 + convection.x,
+      position.y * 8.0 + convection.y,
+      position.z * 8.0 + convection.z
   );
 
-  // Volcanic terrain colors
-  let lava_color = Color::from_float(0.9, 0.3, 0.1);        // Bright lava
-  let rock_color_1 = Color::from_float(0.3, 0.2, 0.2);      // Dark rock
-  let rock_color_2 = Color::from_float(0.5, 0.3, 0.2);      // Light rock
+  // Large-scale temperature: hot regions and cool sunspots from a lower frequency noise
+  let circulation = drift(time, Vec3::new(0.03, 0.0, 0.0));
+  let large_scale = uniforms.material.noise.get_noise_3d(
+      position.x * 1.5 + circulation.x,
+      position.y * 1.5 + circulation.y,
+      position.z * 1.5
+  );
+  let spot_threshold = uniforms.material.params.get(ShaderParam::SunspotThreshold);
+  let sunspot = smoothstep(spot_threshold, spot_threshold + 0.25, -large_scale);
 
-  let lava_threshold = 0.7;
-  
-  let base_color = if noise_value > lava_threshold {
-      lava_color
+  let mut temperature = (0.6 + granulation * 0.25 + large_scale * 0.15).clamp(0.0, 1.0);
+  temperature *= 1.0 - 0.6 * sunspot;
+
+  // Short-lived flares: each period picks a random spot on the surface with a smooth rise and fall
+  let flare_period = 4.0;
+  let epoch = (time / flare_period) as u32;
+  let phase = (time % flare_period) / flare_period;
+  let theta = hash01(epoch * 3) * 2.0 * std::f32::consts::PI;
+  let z = hash01(epoch * 3 + 1) * 2.0 - 1.0;
+  let ring = (1.0 - z * z).sqrt();
+  let flare_center = Vec3::new(ring * theta.cos(), z, ring * theta.sin());
+  let flare_envelope = (phase * std::f32::consts::PI).sin().powf(4.0);
+  let flare = smoothstep(0.96, 0.995, dot(&position, &flare_center)) * flare_envelope;
+
+  // Colors from cool dark red-orange to white-hot yellow
+  let cool = Color::from_float(0.55, 0.12, 0.0);
+  let warm = Color::from_float(1.0, 0.55, 0.08);
+  let hot = Color::from_float(1.0, 0.92, 0.6);
+  let base_color = if temperature < 0.5 {
+      cool.lerp(&warm, temperature / 0.5)
   } else {
-      rock_color_1.lerp(&rock_color_2, noise_value / lava_threshold)
+      warm.lerp(&hot, (temperature - 0.5) / 0.5)
   };
+  let color = base_color.lerp(&hot, flare);
 
-  // Lava glow effect
-  let glow = if noise_value > lava_threshold {
-      50 // Emission value for lava
-  } else {
-      0
+  // Emission follows surface temperature in a warm orange; flares spike it toward white-hot
+  let glow_intensity = (0.35 + temperature * 0.45 + flare * 0.2).min(1.0);
+  let emission = warm.lerp(&hot, flare) * (glow_intensity * uniforms.material.params.get(ShaderParam::EmissionGain));
+
+  (color, emission)
+}
+
+// Palettes (dark, base, accent) for generated styles used by a scene without per-body parameters
+const ROCKY_PALETTE: [Color; 3] = [Color::from_hex(0x4A3F38), Color::from_hex(0x8C7A68), Color::from_hex(0xC8B8A0)];
+const GAS_GIANT_PALETTE: [Color; 3] = [Color::from_hex(0xA07850), Color::from_hex(0xE0C8A0), Color::from_hex(0xC05A30)];
+const ICY_PALETTE: [Color; 3] = [Color::from_hex(0x3A5A78), Color::from_hex(0xB8D8E8), Color::from_hex(0xF0F8FF)];
+
+// Noise and palette of a generated planet, or the shared material noise and a fixed palette
+fn generated_surface<'a>(uniforms: &'a Uniforms, fallback: [Color; 3]) -> (&'a FastNoiseLite, [Color; 3]) {
+  match &uniforms.surface {
+      Some(surface) => (&surface.noise, surface.palette),
+      None => (&uniforms.material.noise, fallback),
+  }
+}
+
+// Dark lowlands, plains in the base color and the highest ground in the accent color
+fn rocky_terrain(noise: &FastNoiseLite, palette: [Color; 3], position: Vec3) -> (Color, f32) {
+  let direction = normalize(&position);
+  let terrain = (noise.get_noise_3d(direction.x, direction.y, direction.z) + 1.0) / 2.0;
+  let base_color = palette[0]
+      .lerp(&palette[1], smoothstep(0.3, 0.55, terrain))
+      .lerp(&palette[2], smoothstep(0.65, 0.85, terrain));
+  (base_color, terrain)
+}
+
+fn rocky_surface(noise: &FastNoiseLite, _params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  rocky_terrain(noise, ROCKY_PALETTE, position)
+}
+
+fn rocky_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+  // Generated planets bring their own noise and palette; only the shared one is baked
+  let (base_color, _) = match &uniforms.surface {
+      Some(surface) => rocky_terrain(&surface.noise, surface.palette, fragment.vertex_position),
+      None => sample_surface(fragment, uniforms, rocky_surface),
   };
 
-  // Hot atmosphere effect
-  let light_dir = normalize(&Vec3::new(0.0, 1.0, 1.0));
-  let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0);
-  let heat_distortion = (time as f32 * 0.002 + noise_value * 5.0).sin() * 0.1;
+  let (albedo, normal) = craters(fragment, uniforms);
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
 
-  (base_color * (0.2 + 0.8 * (diffuse + heat_distortion)), glow)
+  (shade_lit(base_color * albedo, diffuse, uniforms), Color::black())
 }
 
-fn ancient_earth_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-  let noise_value = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 1.5,
-      fragment.vertex_position.y * 1.5
-  );
+fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms, time: f32) -> (Color, Color) {
+  let (noise, palette) = generated_surface(uniforms, GAS_GIANT_PALETTE);
+  let direction = normalize(&fragment.vertex_position);
+  let offset = drift(time, Vec3::new(0.12, 0.0, 0.0));
 
-  // Primordial Earth colors
-  let lava_color = Color::from_float(0.8, 0.4, 0.1);        // Cooling lava
-  let rock_color = Color::from_float(0.4, 0.3, 0.3);        // Basalt
-  let steam_color = Color::from_float(0.7, 0.7, 0.7);       // Steam clouds
+  // Latitude bands bent by turbulence that drifts along the longitude
+  let turbulence = noise.get_noise_3d(direction.x + offset.x, direction.y * 3.0 + offset.y, direction.z);
+  let bands = ((direction.y * 10.0 + turbulence * 1.5).sin() * 0.5 + 0.5).powf(1.5);
+  let base_color = palette[0].lerp(&palette[1], bands);
 
-  let terrain_threshold = 0.5;
-  let steam_threshold = 0.8;
-  
-  let base_color = if noise_value > steam_threshold {
-      steam_color
-  } else if noise_value > terrain_threshold {
-      lava_color.lerp(&rock_color, (noise_value - terrain_threshold) / (steam_threshold - terrain_threshold))
-  } else {
-      rock_color
+  // Oval storms in the accent color where a second, finer sample peaks
+  let storms = noise.get_noise_3d(direction.x * 3.0 - offset.x, direction.y * 8.0 - offset.y, direction.z * 3.0);
+  let color = base_color.lerp(&palette[2], smoothstep(0.5, 0.8, storms) * 0.7);
+
+  let diffuse = sun_diffuse(&normalize(&fragment.normal), fragment, uniforms);
+
+  (shade_lit(color, diffuse, uniforms), Color::black())
+}
+
+// Ridged noise peaks along thin lines: those are the dark cracks in the ice sheet
+fn icy_terrain(noise: &FastNoiseLite, palette: [Color; 3], position: Vec3) -> (Color, f32) {
+  let direction = normalize(&position);
+  let ridges = (noise.get_noise_3d(direction.x, direction.y, direction.z) + 1.0) / 2.0;
+  let ice = palette[1].lerp(&palette[2], smoothstep(0.6, 0.95, direction.y.abs()));
+  (ice.lerp(&palette[0], smoothstep(0.75, 0.9, ridges)), ridges)
+}
+
+fn icy_surface(noise: &FastNoiseLite, _params: &ShaderParams, position: Vec3) -> (Color, f32) {
+  icy_terrain(noise, ICY_PALETTE, position)
+}
+
+fn icy_shader(fragment: &Fragment, uniforms: &Uniforms) -> (Color, Color) {
+  let (base_color, _) = match &uniforms.surface {
+      Some(surface) => icy_terrain(&surface.noise, surface.palette, fragment.vertex_position),
+      None => sample_surface(fragment, uniforms, icy_surface),
   };
 
-  // Atmospheric chaos effect
-  let light_dir = normalize(&Vec3::new(1.0, 0.5, 0.0));
+  // Smooth ice reflects a soft highlight
+  let light_dir = sun_direction(fragment, uniforms);
   let normal = normalize(&fragment.normal);
-  let diffuse = dot(&normal, &light_dir).max(0.0);
-  let chaos = (time as f32 * 0.003 + noise_value * 3.0).sin() * 0.2;
-
-  let glow = if noise_value > terrain_threshold { 20 } else { 0 };
+  let diffuse = sun_diffuse(&normal, fragment, uniforms);
+  let view_dir = normalize(&(uniforms.camera_position - world_position(fragment, uniforms)));
+  let half_vector = normalize(&(light_dir + view_dir));
+  let specular = if dot(&normal, &light_dir) > 0.0 { dot(&normal, &half_vector).max(0.0).powf(48.0) * 0.4 } else { 0.0 };
 
-  (base_color * (0.3 + 0.7 * (diffuse + chaos)), glow)
-}
\ No newline at end of file
+  let specular = specular * sun_irradiance(&world_position(fragment, uniforms));
+  (shade_lit(base_color, diffuse, uniforms) + SUN_GLINT * specular, Color::black())
+}
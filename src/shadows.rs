@@ -0,0 +1,93 @@
+// Sombras analíticas entre esferas: cuánto del Sol (en el origen) ve un punto cuando hay cuerpos
+// en el camino. El Sol se trata como un disco, así el borde de la sombra tiene penumbra.
+// Los anillos dan sombra como un disco plano con hueco; su opacidad la pone el shader del anillo
+use nalgebra_glm::Vec3;
+use crate::material::ShaderKind;
+
+// Fracción del radio real del Sol usada como fuente de luz. Con el tamaño completo el Sol de la escena
+// es tan grande frente a las distancias que las sombras de las lunas casi no se notarían
+const LIGHT_SIZE: f32 = 0.2;
+
+#[derive(Clone, Copy)]
+pub struct Occluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+// Anillo plano alrededor de `center`, perpendicular a `normal`, entre los dos radios (en unidades del mundo)
+#[derive(Clone, Copy)]
+pub struct RingOccluder {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub shader: ShaderKind,
+}
+
+// 1.0 = el Sol se ve completo, 0.0 = tapado por completo. Las esferas que contienen al punto se
+// ignoran: son el cuerpo al que pertenece el fragmento y su lado nocturno ya lo resuelve el shader
+pub fn light_visibility(point: Vec3, occluders: &[Occluder], sun_radius: f32) -> f32 {
+    let sun_distance = point.magnitude();
+    if sun_distance <= f32::EPSILON {
+        return 1.0;
+    }
+    let to_sun = -point / sun_distance;
+    let sun_angle = (sun_radius * LIGHT_SIZE / sun_distance).max(1e-4);
+
+    let mut visibility = 1.0;
+    for occluder in occluders {
+        let offset = occluder.center - point;
+        let distance = offset.magnitude();
+        if distance <= occluder.radius * 1.01 {
+            continue;
+        }
+        // Solo tapan los cuerpos entre el punto y el Sol
+        let along = offset.dot(&to_sun);
+        if along <= 0.0 || along >= sun_distance {
+            continue;
+        }
+
+        let occluder_angle = (occluder.radius / distance).asin();
+        let separation = (offset - to_sun * along).magnitude().atan2(along);
+        let outer = occluder_angle + sun_angle;
+        if separation >= outer {
+            continue;
+        }
+
+        // Dentro de la umbra (o antumbra) se tapa la fracción del disco del Sol que cubre el cuerpo;
+        // en la penumbra se interpola suavemente hasta la luz completa
+        let inner = (occluder_angle - sun_angle).abs();
+        let covered = (occluder_angle / sun_angle).powi(2).min(1.0);
+        let t = ((separation - inner) / (outer - inner).max(1e-6)).clamp(0.0, 1.0);
+        let penumbra = t * t * (3.0 - 2.0 * t);
+        visibility *= 1.0 - covered * (1.0 - penumbra);
+    }
+    visibility
+}
+
+// Dónde cruza el plano del anillo el rayo que va del punto al Sol: posición radial en el anillo
+// (0 en el borde interno, 1 en el externo) y el ancho que ocupa ahí el disco del Sol en esas mismas
+// unidades, para suavizar el borde de la sombra. None si el rayo no cruza el plano antes de llegar al Sol;
+// los puntos del propio plano (los fragmentos del anillo) tampoco cuentan
+pub fn ring_crossing(point: Vec3, ring: &RingOccluder, sun_radius: f32) -> Option<(f32, f32)> {
+    let sun_distance = point.magnitude();
+    if sun_distance <= f32::EPSILON {
+        return None;
+    }
+    let to_sun = -point / sun_distance;
+    let facing = to_sun.dot(&ring.normal);
+    if facing.abs() < 1e-4 {
+        return None;
+    }
+    let along = (ring.center - point).dot(&ring.normal) / facing;
+    if along <= ring.outer_radius * 1e-3 || along >= sun_distance {
+        return None;
+    }
+
+    let width = ring.outer_radius - ring.inner_radius;
+    let radius = (point + to_sun * along - ring.center).magnitude();
+    // El disco del Sol proyectado sobre el plano se estira cuanto más rasante llega la luz
+    let sun_angle = sun_radius * LIGHT_SIZE / sun_distance;
+    let blur = along * sun_angle / facing.abs() / width;
+    Some(((radius - ring.inner_radius) / width, blur))
+}
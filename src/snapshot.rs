@@ -0,0 +1,96 @@
+// Instantáneas de la simulación (F10 guarda, F11 carga): el estado que cambia con el
+// tiempo, como JSON. La disposición del sistema no se guarda; para un sistema generado basta la
+// semilla, y en los demás casos la instantánea solo se aplica si los cuerpos coinciden con los actuales
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+
+// Archivo de las teclas, en el directorio de trabajo
+pub const DEFAULT_PATH: &str = "snapshot.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub time: f32,
+    pub time_scale: f32,
+    pub paused: bool,
+    pub seed: Option<u64>,
+    pub selected_body: Option<usize>,
+    pub bodies: Vec<BodySnapshot>,
+    pub spaceship: SpaceshipSnapshot,
+    pub camera: CameraSnapshot,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BodySnapshot {
+    pub name: String,
+    pub rotation: f32,
+    pub orbital_phase: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpaceshipSnapshot {
+    pub position: [f32; 3],
+    pub velocity: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CameraSnapshot {
+    pub eye: [f32; 3],
+    pub center: [f32; 3],
+    pub up: [f32; 3],
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(String, std::io::Error),
+    Format(String, serde_json::Error),
+    BodyCount { path: String, expected: usize, found: usize },
+    BodyName { path: String, index: usize, expected: String, found: String },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, err) => write!(f, "no se pudo acceder a la instantánea '{}': {}", path, err),
+            SnapshotError::Format(path, err) => write!(f, "{}: instantánea inválida: {}", path, err),
+            SnapshotError::BodyCount { path, expected, found } => {
+                write!(f, "{}: la instantánea tiene {} cuerpos y el sistema actual {}", path, found, expected)
+            }
+            SnapshotError::BodyName { path, index, expected, found } => {
+                write!(f, "{}: el cuerpo {} de la instantánea es '{}', en el sistema actual es '{}'", path, index, found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl Snapshot {
+    pub fn save(&self, path: &str) -> Result<(), SnapshotError> {
+        let json = serde_json::to_string_pretty(self).map_err(|err| SnapshotError::Format(path.to_string(), err))?;
+        fs::write(path, json).map_err(|err| SnapshotError::Io(path.to_string(), err))
+    }
+
+    pub fn load(path: &str) -> Result<Self, SnapshotError> {
+        let contents = fs::read_to_string(path).map_err(|err| SnapshotError::Io(path.to_string(), err))?;
+        serde_json::from_str(&contents).map_err(|err| SnapshotError::Format(path.to_string(), err))
+    }
+
+    // Los cuerpos deben ser los mismos y en el mismo orden que `names`
+    pub fn check_bodies<'a>(&self, path: &str, names: impl ExactSizeIterator<Item = &'a str>) -> Result<(), SnapshotError> {
+        if names.len() != self.bodies.len() {
+            return Err(SnapshotError::BodyCount { path: path.to_string(), expected: names.len(), found: self.bodies.len() });
+        }
+        for (index, (expected, body)) in names.zip(&self.bodies).enumerate() {
+            if expected != body.name {
+                return Err(SnapshotError::BodyName {
+                    path: path.to_string(),
+                    index,
+                    expected: expected.to_string(),
+                    found: body.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
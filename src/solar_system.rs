@@ -1,155 +1,1582 @@
 
-use nalgebra_glm::{Vec3, Mat4, perspective};
+use nalgebra_glm::Vec3;
 use std::f32::consts::PI;
+use std::fmt;
+use crate::bookmarks::Bookmark;
 use crate::camera::Camera;
+use crate::scene::{self, BodyConfig, SceneError};
+use crate::generator;
+use crate::impact::{Impact, IMPACT_SPEED};
+use crate::material::{ShaderKind, SurfaceParams};
+use crate::particles::ParticleSystem;
+use crate::snapshot::{BodySnapshot, CameraSnapshot, Snapshot, SnapshotError, SpaceshipSnapshot};
+use crate::spaceship::{self, Spaceship};
+use crate::texture::Texture;
+use crate::trail::{OrbitTrail, Trail};
 
 pub struct CelestialBody {
+    pub name: String,
     pub position: Vec3,
     pub rotation: f32,
     pub orbital_radius: f32,
     pub orbital_speed: f32,
+    pub orbital_phase: f32,       // Ángulo (anomalía media) en t = 0, en radianes
     pub rotation_speed: f32,
+    pub axial_tilt: f32,          // Inclinación del eje de rotación en radianes (alrededor del eje Z)
     pub scale: f32,
-    pub shader_id: u8,
-    pub orbit_points: Vec<Vec3>,  // Puntos para renderizar la órbita
-    pub collision_radius: f32,    // Radio de colisión
+    pub shape: Vec3,              // Escala relativa por eje del modelo para cuerpos irregulares; (1, 1, 1) = esférico
+    pub tidally_locked: bool,     // Su giro sigue a la órbita: siempre muestra la misma cara al padre
+    pub shader: ShaderKind,
+    pub orbit_points: Vec<Vec3>,  // Puntos de la órbita, relativos al cuerpo padre
+    pub collision_radius: f32,    // Radio de colisión: el de la malla dibujada más COLLISION_MARGIN (ver `fit_collision_radii`)
+    pub parent: Option<usize>,    // Índice del cuerpo alrededor del cual orbita (None = el Sol/origen)
+    pub ring: Option<Ring>,       // Anillo en el plano del ecuador del cuerpo
+    pub kepler: Option<KeplerOrbit>, // Órbita elíptica; si es None la órbita es circular
+    pub has_tail: bool,           // Dibujar una cola de cometa opuesta al Sol
+    pub mass: f32,                // Masa para la gravedad sobre la nave (0 = no atrae)
+    pub surface: Option<SurfaceParams>, // Ruido y paleta propios de un planeta generado
+    pub texture: Option<Texture>, // Imagen equirectangular que reemplaza el color base del shader
+    pub orbit_trail: OrbitTrail,  // Posiciones recientes en el mundo, para la estela orbital
+}
+
+// Anillo plano alrededor de un cuerpo. Los radios son múltiplos de la escala del cuerpo y la
+// inclinación (radianes, alrededor del eje X) se suma a la inclinación axial del cuerpo
+#[derive(Clone, Copy)]
+pub struct Ring {
+    pub inner_scale: f32,
+    pub outer_scale: f32,
+    pub tilt: f32,
+    pub shader: ShaderKind,
+}
+
+impl Default for Ring {
+    // Anillo ancho con bandas al estilo de Saturno
+    fn default() -> Self {
+        Ring { inner_scale: 1.2, outer_scale: 2.4, tilt: 0.0, shader: ShaderKind::Ring }
+    }
+}
+
+// Contacto con un cuerpo: su índice, la normal de la superficie y cuánto se penetró en el radio de colisión
+pub struct Collision {
+    pub body: usize,
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+// Intentos de deslizamiento por movimiento y separación extra tras un empuje
+const COLLISION_ITERATIONS: usize = 4;
+const COLLISION_SKIN: f32 = 0.01;
+
+// Margen por defecto entre la superficie visible de un cuerpo y su esfera de colisión; una escena
+// puede cambiarlo con `collision_margin`
+pub const COLLISION_MARGIN: f32 = 0.05;
+
+// Radio de las mallas con que se dibujan los cuerpos a escala 1: la esfera de los planetas (y del
+// Sol y el cometa) y moon.obj para las lunas. Hasta que se cargan los modelos se usan los nominales
+#[derive(Clone, Copy)]
+pub struct MeshRadii {
+    pub sphere: f32,
+    pub moon: f32,
+}
+
+impl Default for MeshRadii {
+    fn default() -> Self {
+        MeshRadii { sphere: 1.0, moon: 0.5 }
+    }
+}
+
+// Distancia de llegada del warp: proporcional al radio de colisión del destino más un margen
+const WARP_DISTANCE_FACTOR: f32 = 2.5;
+const WARP_MARGIN: f32 = 1.0;
+
+// Piloto automático: la velocidad hacia el punto de parada es proporcional a lo que falta (se frena
+// solo al acercarse), la cámara gira hacia el rumbo con esta respuesta y se llega a esta distancia
+// del punto de parada. Junto con la respuesta de la nave, la aproximación queda sin oscilar
+const AUTOPILOT_APPROACH_RATE: f32 = 1.0;
+const AUTOPILOT_TURN_RESPONSE: f32 = 3.0;
+const AUTOPILOT_ARRIVAL_TOLERANCE: f32 = 0.1;
+
+// Esquive del piloto automático: los cuerpos a menos de este múltiplo de su radio de colisión de la
+// recta al destino empujan el rumbo hacia un costado, con más fuerza cuanto más cerca pasa la recta
+const AVOIDANCE_CLEARANCE: f32 = 1.5;
+const AVOIDANCE_STRENGTH: f32 = 2.0;
+
+// Límites de la escala de tiempo, tanto desde el teclado como al cargar una instantánea
+const MIN_TIME_SCALE: f32 = 1.0 / 64.0;
+const MAX_TIME_SCALE: f32 = 64.0;
+
+// Segundos que el HUD anuncia la llegada del piloto automático
+const ARRIVAL_NOTICE_SECONDS: f32 = 3.0;
+
+// Mira: si el rayo central no toca ningún cuerpo se apunta al más cercano a él dentro de este
+// ángulo (radianes, contando desde su borde aparente), y el objetivo sigue en el panel este
+// tiempo después de salir de la mira
+const CROSSHAIR_CONE: f32 = 0.035;
+const CROSSHAIR_HOLD_SECONDS: f32 = 1.0;
+
+// Vista de pájaro: duración de la transición en segundos y alturas permitidas
+const BIRD_EYE_TRANSITION: f32 = 0.5;
+const BIRD_EYE_DEFAULT_HEIGHT: f32 = 50.0;
+const BIRD_EYE_MIN_HEIGHT: f32 = 10.0;
+const BIRD_EYE_MAX_HEIGHT: f32 = 150.0;
+
+// Duración del warp en segundos (el túnel de post.rs sigue este mismo progreso)
+const WARP_DURATION: f32 = 0.5;
+
+// Duración del viaje a un marcador de cámara, la misma que la del warp
+const BOOKMARK_TRANSITION: f32 = WARP_DURATION;
+
+// Duración de la nivelación de la cámara (L)
+const LEVEL_TRANSITION: f32 = 0.5;
+
+// Gravedad sobre la nave: aceleración = GRAVITY_CONSTANT * masa / distancia². Cerca de un cuerpo la
+// distancia no baja de su radio de colisión y la aceleración se limita, para evitar tirones enormes
+const GRAVITY_CONSTANT: f32 = 1.0;
+const MAX_GRAVITY_ACCELERATION: f32 = 40.0;
+
+// Anillos de Urano: angostos y oscuros, entre 1.6 y 2 radios del planeta. Con la inclinación axial
+// de Urano quedan casi de canto respecto de la órbita
+const URANUS_RING: Ring = Ring { inner_scale: 1.6, outer_scale: 2.05, tilt: 0.0, shader: ShaderKind::UranusRing };
+
+// Posición de la cámara de persecución: detrás y un poco por encima de la nave
+const CHASE_DISTANCE: f32 = 2.0;
+const CHASE_HEIGHT: f32 = 0.4;
+
+// Frecuencia natural (rad/s) del resorte con amortiguamiento crítico de la cámara de persecución:
+// más baja, más tarda la cámara en alcanzar a la nave en los giros rápidos
+const CHASE_SPRING_FREQUENCY: f32 = 6.0;
+
+// Distancia de la cámara en primera persona delante del centro de la nave (la punta del modelo)
+const NOSE_OFFSET: f32 = 0.03;
+
+// Vuelo automático hacia un cuerpo; se recuerda su posición del frame anterior para estimar su
+// velocidad y perseguirlo mientras se mueve por su órbita
+#[derive(Clone, Copy)]
+struct Autopilot {
+    target: usize,
+    last_position: Vec3,
+}
+
+// Cómo acompaña la cámara a la nave en vuelo libre; se alterna con V
+#[derive(Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    Free,        // Detrás de la nave a distancia fija
+    Chase,       // Detrás de la nave, pero con retraso de un resorte
+    FirstPerson, // En la punta de la nave, sin dibujar el modelo
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Free => CameraMode::Chase,
+            CameraMode::Chase => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::Free,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::Free => "LIBRE",
+            CameraMode::Chase => "PERSECUCIÓN",
+            CameraMode::FirstPerson => "CABINA",
+        }
+    }
+}
+
+// Arma un `CelestialBody` por nombre de campo en lugar de por posición. `build` valida los valores y
+// completa lo que se deriva de ellos: la posición inicial, los puntos de la órbita y el radio de colisión
+pub struct CelestialBodyBuilder {
+    name: String,
+    orbit: Option<(f32, f32)>,    // Radio y velocidad orbital; None = no orbita
+    kepler: Option<KeplerOrbit>,
+    orbital_phase: f32,
+    rotation_speed: f32,
+    axial_tilt: f32,              // En grados, como en las escenas
+    scale: f32,
+    shape: Vec3,
+    tidally_locked: bool,
+    shader: ShaderKind,
+    parent: Option<usize>,
+    ring: Option<Ring>,
+    has_tail: bool,
+    mass: f32,
+    texture: Option<Texture>,
+}
+
+// Valores con los que no se puede armar un cuerpo
+#[derive(Debug)]
+pub enum BodyError {
+    InvalidScale { body: String, scale: f32 },
+    InvalidOrbit { body: String, orbital_radius: f32 },
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BodyError::InvalidScale { body, scale } => {
+                write!(f, "el cuerpo '{}' tiene una escala inválida ({}), debe ser positiva", body, scale)
+            }
+            BodyError::InvalidOrbit { body, orbital_radius } => {
+                write!(f, "el cuerpo '{}' tiene un radio orbital inválido ({}), debe ser positivo", body, orbital_radius)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BodyError {}
+
+impl CelestialBodyBuilder {
+    // Órbita circular alrededor del padre (o del Sol)
+    pub fn orbit(mut self, radius: f32, speed: f32) -> Self {
+        self.orbit = Some((radius, speed));
+        self
+    }
+
+    // Órbita elíptica; el radio orbital pasa a ser su semieje mayor
+    pub fn kepler_orbit(mut self, orbit: KeplerOrbit, speed: f32) -> Self {
+        self.orbit = Some((orbit.semi_major_axis, speed));
+        self.kepler = Some(orbit);
+        self
+    }
+
+    // Anomalía media en t = 0, en radianes
+    pub fn phase(mut self, phase: f32) -> Self {
+        self.orbital_phase = phase;
+        self
+    }
+
+    pub fn rotation_speed(mut self, speed: f32) -> Self {
+        self.rotation_speed = speed;
+        self
+    }
+
+    // Inclinación del eje en grados
+    pub fn axial_tilt(mut self, degrees: f32) -> Self {
+        self.axial_tilt = degrees;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn shader(mut self, shader: ShaderKind) -> Self {
+        self.shader = shader;
+        self
+    }
+
+    // Escala relativa por eje (x, y, z del modelo) que deforma la malla; el eje x es el que apunta
+    // al padre en los cuerpos con rotación sincrónica
+    pub fn shape(mut self, shape: Vec3) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn tidally_locked(mut self) -> Self {
+        self.tidally_locked = true;
+        self
+    }
+
+    // Índice del cuerpo alrededor del cual orbita; la posición que arma `build` es relativa a él
+    pub fn parent(mut self, parent: usize) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn ring(mut self, ring: Ring) -> Self {
+        self.ring = Some(ring);
+        self
+    }
+
+    pub fn tail(mut self) -> Self {
+        self.has_tail = true;
+        self
+    }
+
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    pub fn texture(mut self, texture: Texture) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    pub fn build(self) -> Result<CelestialBody, BodyError> {
+        if !(self.scale > 0.0 && self.scale.is_finite()) {
+            return Err(BodyError::InvalidScale { body: self.name, scale: self.scale });
+        }
+
+        let (orbital_radius, orbital_speed) = self.orbit.unwrap_or((0.0, 0.0));
+        if self.orbit.is_some() && !(orbital_radius > 0.0 && orbital_radius.is_finite()) {
+            return Err(BodyError::InvalidOrbit { body: self.name, orbital_radius });
+        }
+
+        let (position, orbit_points) = match &self.kepler {
+            Some(orbit) => (orbit.position(self.orbital_phase), orbit.orbit_points()),
+            None if orbital_radius > 0.0 => (
+                Vec3::new(orbital_radius * self.orbital_phase.cos(), 0.0, orbital_radius * self.orbital_phase.sin()),
+                generate_orbit_points(orbital_radius),
+            ),
+            None => (Vec3::new(0.0, 0.0, 0.0), Vec::new()),
+        };
+        // Con las mallas nominales; `SolarSystem::fit_collision_radii` lo ajusta a los modelos cargados
+        let mesh_radii = MeshRadii::default();
+        let mesh = if self.parent.is_some() { mesh_radii.moon } else { mesh_radii.sphere };
+
+        Ok(CelestialBody {
+            name: self.name,
+            position,
+            rotation: 0.0,
+            orbital_radius,
+            orbital_speed,
+            orbital_phase: self.orbital_phase,
+            rotation_speed: self.rotation_speed,
+            axial_tilt: self.axial_tilt.to_radians(),
+            scale: self.scale,
+            shape: self.shape,
+            tidally_locked: self.tidally_locked,
+            shader: self.shader,
+            orbit_points,
+            collision_radius: self.scale * self.shape.max() * mesh + COLLISION_MARGIN,
+            parent: self.parent,
+            ring: self.ring,
+            kepler: self.kepler,
+            has_tail: self.has_tail,
+            mass: self.mass,
+            surface: None,
+            texture: self.texture,
+            orbit_trail: OrbitTrail::new(),
+        })
+    }
+}
+
+// Elementos keplerianos de una órbita elíptica con el padre en uno de los focos
+#[derive(Clone, Copy)]
+pub struct KeplerOrbit {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub argument_of_periapsis: f32,
+}
+
+impl KeplerOrbit {
+    // Posición relativa al foco para una anomalía media dada (resolviendo la ecuación de Kepler por Newton)
+    pub fn position(&self, mean_anomaly: f32) -> Vec3 {
+        let e = self.eccentricity;
+        let mean_anomaly = mean_anomaly.rem_euclid(2.0 * PI);
+        let mut eccentric_anomaly = if e > 0.8 { PI } else { mean_anomaly };
+        for _ in 0..10 {
+            let f = eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly;
+            eccentric_anomaly -= f / (1.0 - e * eccentric_anomaly.cos());
+        }
+        self.position_at_eccentric_anomaly(eccentric_anomaly)
+    }
+
+    fn position_at_eccentric_anomaly(&self, eccentric_anomaly: f32) -> Vec3 {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+        let x = a * (eccentric_anomaly.cos() - e);
+        let z = a * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        Vec3::new(x * cos_w - z * sin_w, 0.0, x * sin_w + z * cos_w)
+    }
+
+    // Distancia máxima al foco (afelio)
+    pub fn aphelion(&self) -> f32 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    pub fn orbit_points(&self) -> Vec<Vec3> {
+        (0..360)
+            .map(|i| self.position_at_eccentric_anomaly(i as f32 * PI / 180.0))
+            .collect()
+    }
 }
 
 pub struct SolarSystem {
     pub bodies: Vec<CelestialBody>,
-    pub spaceship_position: Vec3,
-    pub spaceship_rotation: Vec3,
+    pub spaceship: Spaceship,
+    pub exhaust: ParticleSystem,  // Escape de los motores de la nave
+    pub trail: Trail,             // Recorrido reciente de la nave
     time: f32,
     pub bird_eye_view: bool,
+    bird_eye_blend: f32,          // 0 = vuelo libre, 1 = vista de pájaro completa
+    bird_eye_height: f32,         // Altura de la vista de pájaro, ajustable con la rueda
+    bird_eye_return: Option<(Vec3, Vec3)>, // Pose (ojo, objetivo) a la que se vuelve al salir
     pub warp_target: Option<usize>,
     pub warp_animation: f32,
+    bookmark_flight: Option<(Vec3, Vec3, Bookmark, f32)>, // Pose de partida, marcador de destino y progreso en [0, 1]
+    autopilot: Option<Autopilot>,
+    arrival_notice: Option<(usize, f32)>, // Cuerpo al que llegó el piloto automático y segundos que le quedan en el HUD
+    crosshair_target: Option<(usize, f32)>, // Cuerpo en la mira y segundos desde que dejó de estarlo
+    impact: Option<Impact>,       // Último choque a velocidad, mientras dura su destello o sacudida
+    pub selected_body: Option<usize>, // Cuerpo seleccionado con el mouse
+    pub orbit_target: Option<usize>,  // Cuerpo alrededor del cual orbita la cámara
+    pub following: bool,          // Con orbit_target: la cámara además gira con el cuerpo alrededor de su órbita
+    follow_angle: f32,            // Ángulo orbital del cuerpo seguido en el frame anterior
+    pub time_scale: f32,          // Multiplicador de la velocidad de la simulación
+    pub paused: bool,             // Congela el movimiento de los cuerpos celestes
+    pub gravity: bool,            // Los cuerpos atraen a la nave en vuelo libre
+    pub seed: Option<u64>,        // Semilla del sistema generado (None = sistema fijo o escena)
+    pub camera_mode: CameraMode,
+    chase_spring: Option<(Vec3, Vec3)>, // Posición y velocidad del ojo en modo persecución
+    leveling: Option<f32>,        // Avance (0 a 1) de la nivelación de la cámara en curso
+    mesh_radii: MeshRadii,        // Radios de las mallas de los cuerpos, para recalcular colisiones al regenerar
+    collision_margin: f32,        // Separación entre la superficie visible y la esfera de colisión
+}
+
+impl CelestialBody {
+    // Escala del eje más largo: con la malla, da el radio de la esfera que envuelve al cuerpo
+    pub fn max_scale(&self) -> f32 {
+        self.scale * self.shape.max()
+    }
+
+    // Rotación del modelo: primero el giro sobre su eje y luego la inclinación de ese eje
+    pub fn model_rotation(&self) -> Vec3 {
+        Vec3::new(0.0, self.rotation, self.axial_tilt)
+    }
+
+    // Dirección del eje de rotación (polo norte) en coordenadas del mundo
+    pub fn rotation_axis(&self) -> Vec3 {
+        Vec3::new(-self.axial_tilt.sin(), self.axial_tilt.cos(), 0.0)
+    }
+
+    // Constructor con valores por defecto: un cuerpo quieto en el origen, de escala 1, sin masa ni anillo
+    pub fn builder(name: &str) -> CelestialBodyBuilder {
+        CelestialBodyBuilder {
+            name: name.to_string(),
+            orbit: None,
+            kepler: None,
+            orbital_phase: 0.0,
+            rotation_speed: 0.02,
+            axial_tilt: 0.0,
+            scale: 1.0,
+            shape: Vec3::new(1.0, 1.0, 1.0),
+            tidally_locked: false,
+            shader: ShaderKind::Moon,
+            parent: None,
+            ring: None,
+            has_tail: false,
+            mass: 0.0,
+            texture: None,
+        }
+    }
+
+    // Posición relativa a su centro de órbita para una anomalía media dada; None si no orbita
+    pub fn orbit_offset(&self, mean_anomaly: f32) -> Option<Vec3> {
+        match &self.kepler {
+            Some(orbit) => Some(orbit.position(mean_anomaly)),
+            None if self.orbital_radius > 0.0 => {
+                Some(Vec3::new(self.orbital_radius * mean_anomaly.cos(), 0.0, self.orbital_radius * mean_anomaly.sin()))
+            }
+            None => None,
+        }
+    }
+
+    // Distancia máxima que alcanza el cuerpo respecto a su centro de órbita
+    pub fn max_orbit_distance(&self) -> f32 {
+        match &self.kepler {
+            Some(orbit) => orbit.aphelion(),
+            None => self.orbital_radius,
+        }
+    }
 }
 
 impl SolarSystem {
     pub fn new() -> Self {
-        let mut bodies = Vec::new();
-        let orbit_point_count = 72;
-        
-    
         // Sol (centro del sistema) con mayor escala y emisión
-        bodies.push(CelestialBody {
-            position: Vec3::new(0.0, 0.0, 0.0),
-            rotation: 0.0,
-            orbital_radius: 0.0,
-            orbital_speed: 0.0,
-            rotation_speed: 0.01,
-            scale: 3.0,
-            shader_id: 7,
-            orbit_points: Vec::new(),
-            collision_radius: 3.5,
-        });
-
-        // Planetas con órbitas y colisiones
+        let mut bodies = vec![
+            CelestialBody::builder("Sol")
+                .rotation_speed(0.01)
+                .axial_tilt(7.25)
+                .scale(3.0)
+                .shader(ShaderKind::Sun)
+                .mass(300.0)
+                .build()
+                .expect("el Sol del sistema fijo es válido"),
+        ];
+
+        // Planetas: (nombre, radio orbital, velocidad orbital, velocidad de rotación, inclinación axial en grados,
+        //  escala, shader, anillo, masa)
         let planet_configs = [
-            (4.0, 0.8, 0.4, 3, 0.5),   // Mercurio
-            (7.0, 0.5, 0.8, 1, 1.0),   // Tierra
-            (10.0, 0.3, 0.6, 2, 0.7),  // Marte
-            (15.0, 0.15, 1.5, 5, 1.8), // Júpiter
-            (20.0, 0.1, 1.3, 4, 1.5),  // Saturno
+            ("Mercurio", 4.0, 0.8, 0.02, 0.03, 0.4, ShaderKind::Mercury, None, 0.5),
+            ("Venus", 5.5, 0.65, -0.01, 2.6, 0.75, ShaderKind::Venus, None, 2.5), // Rotación retrógrada (177.4° = 2.6° girando al revés)
+            ("Tierra", 7.0, 0.5, 0.02, 23.4, 0.8, ShaderKind::Earth, None, 3.0),
+            ("Marte", 10.0, 0.3, 0.02, 25.2, 0.6, ShaderKind::Mars, None, 0.6),
+            ("Júpiter", 15.0, 0.15, 0.02, 3.1, 1.5, ShaderKind::Jupiter, None, 40.0),
+            ("Saturno", 20.0, 0.1, 0.02, 26.7, 1.3, ShaderKind::Saturn, Some(Ring::default()), 25.0),
+            ("Urano", 27.0, 0.07, 0.02, 97.8, 1.0, ShaderKind::Uranus, Some(URANUS_RING), 8.0), // Gira de costado
+            ("Neptuno", 34.0, 0.05, 0.02, 28.3, 0.95, ShaderKind::Neptune, None, 9.0),
         ];
 
-        for (orbital_radius, orbital_speed, scale, shader_id, collision_scale) in planet_configs.iter() {
-            let mut orbit_points = Vec::new();
-            for i in 0..360 {
-                let angle = i as f32 * PI / 180.0;
-                let x = orbital_radius * angle.cos();
-                let z = orbital_radius * angle.sin();
-                orbit_points.push(Vec3::new(x, 0.0, z));
+        for (name, orbital_radius, orbital_speed, rotation_speed, axial_tilt, scale, shader, ring, mass) in planet_configs {
+            let mut builder = CelestialBody::builder(name)
+                .orbit(orbital_radius, orbital_speed)
+                .rotation_speed(rotation_speed)
+                .axial_tilt(axial_tilt)
+                .scale(scale)
+                .shader(shader)
+                .mass(mass);
+            if let Some(ring) = ring {
+                builder = builder.ring(ring);
             }
-
-            bodies.push(CelestialBody {
-                position: Vec3::new(*orbital_radius, 0.0, 0.0),
-                rotation: 0.0,
-                orbital_radius: *orbital_radius,
-                orbital_speed: *orbital_speed,
-                rotation_speed: 0.02,
-                scale: *scale,
-                shader_id: *shader_id,
-                orbit_points,
-                collision_radius: scale * collision_scale,
-            });
+            bodies.push(builder.build().expect("los planetas del sistema fijo son válidos"));
         }
 
-        for (orbital_radius, orbital_speed, scale, shader_id, collision_scale) in planet_configs.iter() {
-            let mut orbit_points = Vec::with_capacity(orbit_point_count);
-            for i in 0..orbit_point_count {
-                let angle = (i as f32 * 2.0 * PI) / orbit_point_count as f32;
-                let x = orbital_radius * angle.cos();
-                let z = orbital_radius * angle.sin();
-                orbit_points.push(Vec3::new(x, 0.0, z));
+        // Lunas: (nombre, nombre del padre, radio orbital, velocidad orbital, fase, escala, masa, forma irregular).
+        // Fobos y Deimos son rocas alargadas muy cerca de Marte, con el eje largo apuntando al planeta
+        let moon_configs = [
+            ("Luna", "Tierra", 1.5, 1.2, 0.0, 0.4, 0.2, None),
+            ("Fobos", "Marte", 1.0, 2.5, 0.0, 0.12, 0.01, Some(Vec3::new(1.35, 0.8, 1.0))),
+            ("Deimos", "Marte", 1.6, 1.1, 2.5, 0.08, 0.005, Some(Vec3::new(1.25, 0.85, 0.95))),
+            ("Ío", "Júpiter", 2.4, 1.0, 0.0, 0.35, 0.2, None),
+            ("Europa", "Júpiter", 3.0, 0.7, 0.0, 0.3, 0.15, None),
+        ];
+
+        for (name, parent_name, orbital_radius, orbital_speed, phase, scale, mass, shape) in moon_configs {
+            let parent = bodies.iter().position(|body| body.name == parent_name).expect("la luna debe tener un planeta padre");
+            let mut builder = CelestialBody::builder(name)
+                .orbit(orbital_radius, orbital_speed)
+                .phase(phase)
+                .parent(parent)
+                .scale(scale)
+                .shader(ShaderKind::Moon)
+                .mass(mass);
+            if let Some(shape) = shape {
+                builder = builder.shape(shape).tidally_locked();
             }
+            let mut moon = builder.build().expect("las lunas del sistema fijo son válidas");
+            moon.position += bodies[parent].position;
+            bodies.push(moon);
+        }
+
+        // Cometa con órbita muy excéntrica que pasa cerca del Sol
+        let comet_orbit = KeplerOrbit {
+            semi_major_axis: 22.0,
+            eccentricity: 0.8,
+            argument_of_periapsis: 0.8,
+        };
+        bodies.push(
+            CelestialBody::builder("Cometa")
+                .kepler_orbit(comet_orbit, 0.12)
+                .rotation_speed(0.05)
+                .scale(0.25)
+                .shader(ShaderKind::Moon)
+                .tail()
+                .build()
+                .expect("el cometa del sistema fijo es válido"),
+        );
+
+        Self::with_bodies(bodies)
+    }
+
+    // Carga la disposición del sistema solar desde un archivo de escena TOML
+    pub fn from_file(path: &str) -> Result<Self, SceneError> {
+        let scene = scene::load_scene(path)?;
+        let mut bodies = Vec::new();
+
+        for config in &scene.bodies {
+            push_body(&mut bodies, config, None)?;
         }
 
-        SolarSystem {
+        let mut system = Self::with_bodies(bodies);
+        system.collision_margin = scene.collision_margin;
+        system.fit_collision_radii(system.mesh_radii);
+        Ok(system)
+    }
+
+    // Sistema aleatorio: la misma semilla siempre produce los mismos cuerpos y superficies
+    pub fn generate(seed: u64) -> Self {
+        let mut system = Self::with_bodies(generator::generate_bodies(seed));
+        system.seed = Some(seed);
+        system
+    }
+
+    // Reemplaza los cuerpos por un sistema generado con otra semilla. La nave y la cámara se quedan
+    // donde están, pero se cancela todo lo que apuntaba a un cuerpo del sistema anterior
+    pub fn regenerate(&mut self, seed: u64, camera: &mut Camera) {
+        if self.orbit_target.is_some() {
+            self.stop_orbit(camera);
+        }
+        self.warp_target = None;
+        self.warp_animation = 0.0;
+        self.autopilot = None;
+        self.arrival_notice = None;
+        self.crosshair_target = None;
+        self.selected_body = None;
+        self.bodies = generator::generate_bodies(seed);
+        self.seed = Some(seed);
+        self.fit_collision_radii(self.mesh_radii);
+    }
+
+    fn with_bodies(bodies: Vec<CelestialBody>) -> Self {
+        let mut system = SolarSystem {
             bodies,
-            spaceship_position: Vec3::new(25.0, 5.0, 25.0),
-            spaceship_rotation: Vec3::new(0.0, 0.0, 0.0),
+            spaceship: Spaceship::new(Vec3::new(25.0, 5.0, 25.0)),
+            exhaust: ParticleSystem::new(),
+            trail: Trail::new(),
             time: 0.0,
             bird_eye_view: false,
+            bird_eye_blend: 0.0,
+            bird_eye_height: BIRD_EYE_DEFAULT_HEIGHT,
+            bird_eye_return: None,
             warp_target: None,
             warp_animation: 0.0,
+            bookmark_flight: None,
+            autopilot: None,
+            arrival_notice: None,
+            impact: None,
+            crosshair_target: None,
+            selected_body: None,
+            orbit_target: None,
+            following: false,
+            follow_angle: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            gravity: false,
+            seed: None,
+            camera_mode: CameraMode::Free,
+            chase_spring: None,
+            leveling: None,
+            mesh_radii: MeshRadii::default(),
+            collision_margin: COLLISION_MARGIN,
+        };
+        system.fit_collision_radii(system.mesh_radii);
+        system
+    }
+
+    // Radios de colisión a partir de la malla con que se dibuja cada cuerpo, así coinciden con lo
+    // que se ve; se vuelve a llamar con los radios reales una vez cargados los modelos
+    pub fn fit_collision_radii(&mut self, mesh_radii: MeshRadii) {
+        self.mesh_radii = mesh_radii;
+        for body in &mut self.bodies {
+            let mesh = if body.parent.is_some() { mesh_radii.moon } else { mesh_radii.sphere };
+            body.collision_radius = body.max_scale() * mesh + self.collision_margin;
         }
     }
 
     pub fn update(&mut self, delta_time: f32, camera: &mut Camera) {
-        self.time += delta_time;
+        // Solo el movimiento celeste depende de la escala de tiempo; la nave y el warp usan tiempo real
+        let sim_delta = self.simulation_delta(delta_time);
+        self.time += sim_delta;
         
-        // Actualizar cuerpos celestes
-        for body in &mut self.bodies {
-            body.rotation += body.rotation_speed * delta_time;
+        // Actualizar cuerpos celestes (los padres siempre preceden a sus lunas en el vector)
+        for i in 0..self.bodies.len() {
+            let center = self.orbit_center(i);
+            let body = &mut self.bodies[i];
+            body.rotation += body.rotation_speed * sim_delta;
             
-            if body.orbital_radius > 0.0 {
-                let angle = self.time * body.orbital_speed;
-                body.position.x = body.orbital_radius * angle.cos();
-                body.position.z = body.orbital_radius * angle.sin();
+            let mean_anomaly = self.time * body.orbital_speed + body.orbital_phase;
+            if let Some(offset) = body.orbit_offset(mean_anomaly) {
+                body.position = center + offset;
+            }
+
+            // Rotación sincrónica: el giro alrededor de y es el opuesto del ángulo orbital, así el
+            // eje x del modelo queda siempre sobre la recta al padre (-x mira hacia él)
+            if body.tidally_locked {
+                let offset = body.position - center;
+                body.rotation = -offset.z.atan2(offset.x);
             }
         }
 
+        // Estelas orbitales: cada muestra que falta se calcula en su instante exacto, aunque un frame
+        // a escala de tiempo alta abarque varias
+        for i in 0..self.bodies.len() {
+            let Some(interval) = OrbitTrail::interval(self.bodies[i].orbital_speed) else { continue };
+            for sample in self.bodies[i].orbit_trail.pending(self.time, interval) {
+                let position = self.position_at(i, sample as f32 * interval);
+                self.bodies[i].orbit_trail.push(sample, position);
+            }
+        }
+
+        self.arrival_notice = self.arrival_notice
+            .map(|(target, seconds)| (target, seconds - delta_time))
+            .filter(|&(_, seconds)| seconds > 0.0);
+        if self.impact.as_mut().is_some_and(|impact| !impact.update(delta_time)) {
+            self.impact = None;
+        }
+
         // Manejar warping
         if let Some(target) = self.warp_target {
-            self.warp_animation += delta_time * 2.0;
+            self.warp_animation += delta_time / WARP_DURATION;
             if self.warp_animation >= 1.0 {
-                camera.eye = self.bodies[target].position + Vec3::new(5.0, 2.0, 5.0);
-                camera.center = self.bodies[target].position;
+                let target_position = self.bodies[target].position;
+                camera.look_at(self.warp_arrival(target), target_position);
                 self.warp_target = None;
                 self.warp_animation = 0.0;
+                // Al llegar se sigue al cuerpo para que no se aleje con su órbita
+                self.start_orbit(target, true, camera);
+            }
+        }
+
+        // Viaje a un marcador: interpola la pose con la misma curva suave de la vista de pájaro
+        if let Some((from_eye, from_center, bookmark, progress)) = self.bookmark_flight {
+            let progress = (progress + delta_time / BOOKMARK_TRANSITION).min(1.0);
+            let t = progress * progress * (3.0 - 2.0 * progress);
+            camera.up = bookmark.up;
+            camera.look_at(from_eye.lerp(&bookmark.eye, t), from_center.lerp(&bookmark.center, t));
+            self.bookmark_flight = if progress < 1.0 { Some((from_eye, from_center, bookmark, progress)) } else { None };
+        }
+
+        // Vista de pájaro: interpola entre la pose de vuelo libre guardada y la vista cenital
+        let blend_step = delta_time / BIRD_EYE_TRANSITION;
+        self.bird_eye_blend = if self.bird_eye_view {
+            (self.bird_eye_blend + blend_step).min(1.0)
+        } else {
+            (self.bird_eye_blend - blend_step).max(0.0)
+        };
+        if let Some((free_eye, free_target)) = self.bird_eye_return {
+            let t = self.bird_eye_blend * self.bird_eye_blend * (3.0 - 2.0 * self.bird_eye_blend);
+            let overhead = Vec3::new(0.0, self.bird_eye_height, 0.0);
+            camera.look_at(free_eye.lerp(&overhead, t), free_target.lerp(&Vec3::new(0.0, 0.0, 0.0), t));
+            if !self.bird_eye_view && self.bird_eye_blend <= 0.0 {
+                self.bird_eye_return = None;
+            }
+        }
+
+        // Modo órbita: la cámara sigue la posición actual del cuerpo. En modo seguimiento también
+        // gira con el cuerpo alrededor de su centro de órbita, así el desplazamiento queda fijo
+        // en el marco orbital del cuerpo y el Sol se mantiene en el mismo lado de la pantalla
+        match self.orbit_target {
+            Some(target) => {
+                camera.follow(self.bodies[target].position);
+                if self.following {
+                    let angle = self.orbital_angle(target);
+                    let delta = (angle - self.follow_angle + PI).rem_euclid(2.0 * PI) - PI;
+                    self.follow_angle = angle;
+                    camera.orbit(delta, 0.0);
+                }
+            }
+            None if camera.orbit_mode => camera.exit_orbit_mode(),
+            None => {}
+        }
+
+        // Vuelo libre: la nave integra su velocidad y la cámara la persigue;
+        // durante warps, vista de pájaro u órbita la nave queda quieta frente a la cámara
+        // Las colisiones siempre se prueban con la posición de la nave, nunca con la de la cámara
+        let free_flight = self.free_flight();
+        if !free_flight {
+            self.autopilot = None;
+        }
+        if free_flight {
+            if let Some(progress) = self.leveling {
+                // Cada paso recorre la parte que le toca de la curva suave sobre lo que falta, así la
+                // nivelación termina a tiempo aunque el mouse siga moviendo la cámara mientras tanto
+                let ease = |t: f32| t * t * (3.0 - 2.0 * t);
+                let next = (progress + delta_time / LEVEL_TRANSITION).min(1.0);
+                let remaining = 1.0 - ease(progress);
+                camera.relevel(if remaining > f32::EPSILON { (ease(next) - ease(progress)) / remaining } else { 1.0 });
+                self.leveling = (next < 1.0).then_some(next);
+            }
+            if let Some((_, distance)) = self.nearest_body(&self.spaceship.position) {
+                self.spaceship.adapt_to_distance(distance);
+            }
+            // Euler semi-implícito: la gravedad cambia la velocidad (a la escala de tiempo de la
+            // simulación) antes de que esa velocidad mueva la nave
+            if self.gravity {
+                self.spaceship.velocity += self.gravity_at(&self.spaceship.position) * sim_delta;
+            }
+            if !self.steer_autopilot(delta_time, camera) {
+                self.spaceship.accelerate(delta_time, &camera.get_forward(), &camera.get_right(), self.gravity);
+            }
+            let start = self.spaceship.position;
+            let movement = self.spaceship.velocity * delta_time;
+            // Un choque rápido hace rebotar la nave y dispara el impacto; un roce lento solo la frena
+            if let Some(collision) = self.check_collision(&(start + movement)) {
+                let speed = -self.spaceship.velocity.dot(&collision.normal);
+                if speed > IMPACT_SPEED && self.impact.is_none() {
+                    let point = self.bodies[collision.body].position + collision.normal * self.bodies[collision.body].collision_radius;
+                    self.impact = Some(Impact::new(point, speed));
+                    self.spaceship.bounce_off(&collision.normal);
+                } else {
+                    self.spaceship.stop_into(&collision.normal);
+                }
+            }
+            self.spaceship.position += self.slide_movement(&start, &movement);
+            self.place_camera(delta_time, camera);
+        } else {
+            self.chase_spring = None;
+            self.leveling = None;
+            let offset = match self.camera_mode {
+                CameraMode::FirstPerson => -NOSE_OFFSET,
+                _ => CHASE_DISTANCE,
+            };
+            self.spaceship.park(camera.eye + camera.get_forward() * offset);
+        }
+        self.spaceship.orient(camera.get_rotation());
+
+        // Solo hay escape mientras la nave acelera hacia adelante en vuelo libre
+        let thrust = if free_flight { self.spaceship.thrust() } else { 0.0 };
+        let ship_model = crate::renderer::create_model_matrix(self.spaceship.position, spaceship::MODEL_SCALE, self.spaceship.rotation);
+        self.exhaust.update(delta_time, &ship_model, thrust);
+        self.trail.update(delta_time, free_flight.then_some(self.spaceship.position));
+    }
+
+    // Objetivo de la mira con la pose final de la cámara del frame (después de la entrada del usuario
+    // o de la sesión reproducida); se conserva un momento al salir de ella
+    pub fn update_crosshair(&mut self, delta_time: f32, camera: &Camera) {
+        self.crosshair_target = match self.aimed_body(&camera.eye, &camera.get_forward()) {
+            Some(target) => Some((target, 0.0)),
+            None => self.crosshair_target
+                .map(|(target, seconds)| (target, seconds + delta_time))
+                .filter(|&(_, seconds)| seconds < CROSSHAIR_HOLD_SECONDS),
+        };
+    }
+
+    // Tiempo de simulación que corresponde a `delta_time` segundos reales, con la pausa y la escala de tiempo
+    pub fn simulation_delta(&self, delta_time: f32) -> f32 {
+        if self.paused { 0.0 } else { delta_time * self.time_scale }
+    }
+
+    fn free_flight(&self) -> bool {
+        self.warp_target.is_none() && !self.bird_eye_active() && self.orbit_target.is_none() && self.bookmark_flight.is_none()
+    }
+
+    // En cabina la nave no se dibuja: la cámara está en su punta
+    pub fn cockpit_view(&self) -> bool {
+        self.camera_mode == CameraMode::FirstPerson
+    }
+
+    // Empieza a nivelar la cámara; solo tiene efecto en vuelo libre
+    pub fn level_camera(&mut self) {
+        if self.free_flight() {
+            self.leveling = Some(0.0);
+        }
+    }
+
+    pub fn cycle_camera_mode(&mut self) {
+        self.camera_mode = self.camera_mode.next();
+        self.chase_spring = None;
+    }
+
+    // Enciende el piloto automático hacia el cuerpo seleccionado, o lo apaga si ya estaba volando.
+    // Toma el control desde cualquier modo de cámara, como el warp
+    pub fn toggle_autopilot(&mut self, camera: &mut Camera) {
+        if self.autopilot.is_some() {
+            self.autopilot = None;
+            return;
+        }
+        let Some(target) = self.selected_body else { return };
+        if self.orbit_target.is_some() {
+            self.stop_orbit(camera);
+        }
+        self.cancel_bird_eye_view();
+        self.warp_target = None;
+        self.warp_animation = 0.0;
+        self.bookmark_flight = None;
+        self.arrival_notice = None;
+        self.autopilot = Some(Autopilot { target, last_position: self.bodies[target].position });
+    }
+
+    pub fn disengage_autopilot(&mut self) {
+        self.autopilot = None;
+    }
+
+    pub fn autopilot_target(&self) -> Option<usize> {
+        self.autopilot.map(|autopilot| autopilot.target)
+    }
+
+    // Cuerpo al que acaba de llegar el piloto automático, mientras el HUD lo anuncia
+    pub fn arrival_notice(&self) -> Option<usize> {
+        self.arrival_notice.map(|(target, _)| target)
+    }
+
+    // Choque en curso: destello y sacudida de cámara
+    pub fn impact(&self) -> Option<&Impact> {
+        self.impact.as_ref()
+    }
+
+    // Un paso del piloto automático: apunta al punto de parada sobre la recta hacia el destino,
+    // desviándose de los cuerpos que se cruzan, y suma la velocidad del destino para alcanzarlo
+    // en su órbita. Devuelve falso si no está activo
+    fn steer_autopilot(&mut self, delta_time: f32, camera: &mut Camera) -> bool {
+        let Some(autopilot) = self.autopilot else { return false };
+        let target_position = self.bodies[autopilot.target].position;
+        let target_velocity = if delta_time > f32::EPSILON {
+            (target_position - autopilot.last_position) / delta_time
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        };
+        self.autopilot = Some(Autopilot { target: autopilot.target, last_position: target_position });
+
+        let ship = self.spaceship.position;
+        let to_target = target_position - ship;
+        let distance = to_target.magnitude();
+        let remaining = distance - self.standoff_distance(autopilot.target);
+        if remaining <= AUTOPILOT_ARRIVAL_TOLERANCE {
+            self.autopilot = None;
+            self.arrival_notice = Some((autopilot.target, ARRIVAL_NOTICE_SECONDS));
+            return false;
+        }
+
+        let direction = to_target / distance;
+        let avoidance = self.avoidance(&ship, &(ship + direction * remaining), autopilot.target);
+        let heading = (direction + avoidance * AVOIDANCE_STRENGTH).normalize();
+        let speed = self.spaceship.max_speed().min(remaining * AUTOPILOT_APPROACH_RATE);
+
+        // La cámara (y con ella la nave) gira de a poco hacia el rumbo
+        let facing = camera.get_forward().lerp(&heading, (AUTOPILOT_TURN_RESPONSE * delta_time).min(1.0));
+        if facing.magnitude() > f32::EPSILON {
+            let reach = (camera.center() - camera.eye).magnitude();
+            camera.look_at(camera.eye, camera.eye + facing.normalize() * reach);
+        }
+        self.spaceship.steer(delta_time, &(target_velocity + heading * speed), &camera.get_forward(), &camera.get_right());
+        true
+    }
+
+    // Desvío lateral para el tramo de `start` a `goal`: cada cuerpo (salvo el destino) cuya esfera de
+    // colisión ampliada corta el tramo empuja hacia afuera desde su centro
+    fn avoidance(&self, start: &Vec3, goal: &Vec3, target: usize) -> Vec3 {
+        let mut push = Vec3::new(0.0, 0.0, 0.0);
+        let path = goal - start;
+        let length = path.magnitude();
+        if length <= f32::EPSILON {
+            return push;
+        }
+        let direction = path / length;
+        for (i, body) in self.bodies.iter().enumerate() {
+            let to_body = body.position - start;
+            let along = to_body.dot(&direction);
+            if i == target || along <= 0.0 || along >= length {
+                continue;
+            }
+            let offset = direction * along - to_body;
+            let miss = offset.magnitude();
+            let clearance = body.collision_radius * AVOIDANCE_CLEARANCE;
+            if miss >= clearance {
+                continue;
             }
+            // Si la recta pasa justo por el centro cualquier costado sirve; se elige uno horizontal
+            let away = if miss > f32::EPSILON {
+                offset / miss
+            } else {
+                let side = direction.cross(&Vec3::new(0.0, 1.0, 0.0));
+                if side.magnitude() > f32::EPSILON { side.normalize() } else { Vec3::new(1.0, 0.0, 0.0) }
+            };
+            push += away * (1.0 - miss / clearance);
         }
+        push
+    }
 
-        // Actualizar vista de pájaro
-        if self.bird_eye_view {
-            camera.eye = Vec3::new(0.0, 50.0, 0.0);
-            camera.center = Vec3::new(0.0, 0.0, 0.0);
+    // Ubica el ojo respecto de la nave según el modo; la orientación la sigue dando el mouse
+    fn place_camera(&mut self, delta_time: f32, camera: &mut Camera) {
+        let ship = self.spaceship.position;
+        match self.camera_mode {
+            CameraMode::Free => camera.chase(ship, CHASE_DISTANCE, CHASE_HEIGHT),
+            CameraMode::FirstPerson => camera.chase(ship, -NOSE_OFFSET, 0.0),
+            CameraMode::Chase => {
+                // Resorte críticamente amortiguado hacia la pose fija de persecución: alcanza el objetivo
+                // sin oscilar y se queda atrás en los giros. Se usa la solución exacta, estable con cualquier paso
+                let target = ship - camera.get_forward() * CHASE_DISTANCE + camera.get_up() * CHASE_HEIGHT;
+                let (eye, velocity) = self.chase_spring.unwrap_or((target, Vec3::new(0.0, 0.0, 0.0)));
+                let omega = CHASE_SPRING_FREQUENCY;
+                let offset = eye - target;
+                let slope = velocity + offset * omega;
+                let decay = (-omega * delta_time).exp();
+                let eye = target + (offset + slope * delta_time) * decay;
+                let velocity = (velocity - slope * (omega * delta_time)) * decay;
+                self.chase_spring = Some((eye, velocity));
+                camera.eye = eye;
+                camera.has_changed = true;
+            }
         }
+    }
 
-        // Actualizar posición de la nave espacial
-        self.spaceship_position = camera.eye + camera.get_forward() * 2.0;
-        self.spaceship_rotation = camera.get_rotation();
+    // Centro de la órbita de un cuerpo: la posición actual de su padre o el origen
+    pub fn orbit_center(&self, index: usize) -> Vec3 {
+        match self.bodies[index].parent {
+            Some(parent) => self.bodies[parent].position,
+            None => Vec3::new(0.0, 0.0, 0.0),
+        }
     }
 
-    pub fn check_collision(&self, new_position: &Vec3) -> bool {
-        for body in &self.bodies {
-            let distance = (body.position - new_position).magnitude();
-            if distance < body.collision_radius {
-                return true;
+    // Posición en el mundo de un cuerpo en el instante de simulación `time`, con su padre también en ese instante
+    fn position_at(&self, index: usize, time: f32) -> Vec3 {
+        let body = &self.bodies[index];
+        let center = body.parent.map_or(Vec3::new(0.0, 0.0, 0.0), |parent| self.position_at(parent, time));
+        match body.orbit_offset(time * body.orbital_speed + body.orbital_phase) {
+            Some(offset) => center + offset,
+            None => body.position,
+        }
+    }
+
+    // Distancia máxima desde el origen que alcanza cualquier cuerpo
+    pub fn system_radius(&self) -> f32 {
+        self.bodies.iter()
+            .map(|body| {
+                // Las órbitas de todos los ancestros se suman, por si una luna tiene sus propias lunas
+                let mut reach = body.max_orbit_distance() + body.collision_radius;
+                let mut parent = body.parent;
+                while let Some(index) = parent {
+                    reach += self.bodies[index].max_orbit_distance();
+                    parent = self.bodies[index].parent;
+                }
+                reach
+            })
+            .fold(0.0, f32::max)
+    }
+
+    // Aceleración gravitatoria que sienten los objetos en `point` por la atracción de todos los cuerpos
+    pub fn gravity_at(&self, point: &Vec3) -> Vec3 {
+        let mut acceleration = Vec3::new(0.0, 0.0, 0.0);
+        for body in self.bodies.iter().filter(|body| body.mass > 0.0) {
+            let offset = body.position - point;
+            let distance = offset.magnitude().max(body.collision_radius).max(f32::EPSILON);
+            acceleration += offset / distance * (GRAVITY_CONSTANT * body.mass / (distance * distance));
+        }
+        let magnitude = acceleration.magnitude();
+        if magnitude > MAX_GRAVITY_ACCELERATION {
+            acceleration *= MAX_GRAVITY_ACCELERATION / magnitude;
+        }
+        acceleration
+    }
+
+    // Cuerpo cuya superficie (radio de colisión) está más cerca de `point`, con esa distancia
+    pub fn nearest_body(&self, point: &Vec3) -> Option<(usize, f32)> {
+        self.bodies.iter()
+            .enumerate()
+            .map(|(i, body)| (i, ((body.position - point).magnitude() - body.collision_radius).max(0.0)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // Devuelve el cuerpo con mayor penetración en `position`, con la normal de contacto hacia afuera
+    pub fn check_collision(&self, position: &Vec3) -> Option<Collision> {
+        let mut deepest: Option<Collision> = None;
+        for (i, body) in self.bodies.iter().enumerate() {
+            let offset = position - body.position;
+            let distance = offset.magnitude();
+            let depth = body.collision_radius - distance;
+            if depth <= 0.0 || deepest.as_ref().is_some_and(|c| c.depth >= depth) {
+                continue;
+            }
+            let normal = if distance > f32::EPSILON { offset / distance } else { Vec3::new(0.0, 1.0, 0.0) };
+            deepest = Some(Collision { body: i, normal, depth });
+        }
+        deepest
+    }
+
+    // Ajusta un desplazamiento para deslizarse sobre los cuerpos en lugar de detenerse:
+    // se elimina la componente que entra en la superficie y se empuja un poco hacia afuera
+    pub fn slide_movement(&self, start: &Vec3, movement: &Vec3) -> Vec3 {
+        let mut movement = *movement;
+        for _ in 0..COLLISION_ITERATIONS {
+            let collision = match self.check_collision(&(start + movement)) {
+                Some(collision) => collision,
+                None => return movement,
+            };
+
+            let into_surface = movement.dot(&collision.normal);
+            if into_surface < 0.0 {
+                movement -= collision.normal * into_surface;
+            }
+            if let Some(collision) = self.check_collision(&(start + movement)) {
+                movement += collision.normal * (collision.depth + COLLISION_SKIN);
+            }
+        }
+
+        // Atrapado entre cuerpos muy juntos: quedarse quieto evita el temblor entre dos empujes
+        if self.check_collision(&(start + movement)).is_some() {
+            Vec3::new(0.0, 0.0, 0.0)
+        } else {
+            movement
+        }
+    }
+
+    // Intersecta un rayo (dirección normalizada) con la esfera envolvente de cada cuerpo
+    // y devuelve el índice del cuerpo cuya intersección está más cerca del origen del rayo
+    pub fn pick(&self, origin: &Vec3, direction: &Vec3) -> Option<usize> {
+        let mut closest: Option<(usize, f32)> = None;
+
+        for (i, body) in self.bodies.iter().enumerate() {
+            // Margen sobre el radio de colisión para que los cuerpos pequeños sean fáciles de seleccionar
+            let radius = body.collision_radius.max(body.scale);
+            let to_center = body.position - origin;
+            let projection = to_center.dot(direction);
+            let distance_sq = to_center.magnitude_squared() - projection * projection;
+            let radius_sq = radius * radius;
+            if distance_sq > radius_sq {
+                continue;
+            }
+
+            let half_chord = (radius_sq - distance_sq).sqrt();
+            let t = if projection - half_chord >= 0.0 { projection - half_chord } else { projection + half_chord };
+            if t < 0.0 {
+                continue;
+            }
+
+            if closest.is_none_or(|(_, best)| t < best) {
+                closest = Some((i, t));
             }
         }
-        false
+
+        closest.map(|(i, _)| i)
+    }
+
+    // Cuerpo bajo la mira: el que intersecta el rayo o, si ninguno, el más cercano a él dentro del cono
+    fn aimed_body(&self, origin: &Vec3, direction: &Vec3) -> Option<usize> {
+        self.pick(origin, direction).or_else(|| {
+            self.bodies.iter()
+                .enumerate()
+                .filter_map(|(i, body)| {
+                    let to_center = body.position - origin;
+                    let distance = to_center.magnitude();
+                    if distance <= body.collision_radius || to_center.dot(direction) <= 0.0 {
+                        return None;
+                    }
+                    let angle = (to_center.dot(direction) / distance).clamp(-1.0, 1.0).acos();
+                    let edge = angle - (body.collision_radius / distance).asin();
+                    (edge <= CROSSHAIR_CONE).then_some((i, edge))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        })
+    }
+
+    pub fn crosshair_target(&self) -> Option<usize> {
+        self.crosshair_target.map(|(target, _)| target)
+    }
+
+    // Fija el cuerpo en la mira como selección, que es la que usan el warp, el seguimiento y el piloto automático
+    pub fn lock_crosshair_target(&mut self) {
+        if let Some(target) = self.crosshair_target() {
+            self.selected_body = Some(target);
+        }
+    }
+
+    // Rapidez con la que el cuerpo recorre su órbita alrededor de su centro, en unidades por segundo
+    // real (con la pausa y la escala de tiempo actuales)
+    pub fn orbital_speed(&self, index: usize) -> f32 {
+        let body = &self.bodies[index];
+        let rate = (body.orbital_speed * self.simulation_delta(1.0)).abs();
+        match &body.kepler {
+            Some(orbit) => {
+                // Derivada numérica respecto a la anomalía media
+                let step = 1e-3;
+                let mean_anomaly = self.time * body.orbital_speed + body.orbital_phase;
+                let chord = orbit.position(mean_anomaly + step) - orbit.position(mean_anomaly - step);
+                chord.magnitude() / (2.0 * step) * rate
+            }
+            None => body.orbital_radius * rate,
+        }
+    }
+
+    pub fn warp_to_selected(&mut self) {
+        if let Some(index) = self.selected_body {
+            self.warp_to_planet(index);
+        }
+    }
+
+    // Entra o sale del modo órbita alrededor del cuerpo seleccionado
+    pub fn toggle_orbit_mode(&mut self, camera: &mut Camera) {
+        if self.orbit_target.is_some() {
+            self.stop_orbit(camera);
+        } else if let Some(index) = self.selected_body {
+            self.start_orbit(index, false, camera);
+        }
+    }
+
+    // Entra o sale del modo seguimiento del cuerpo seleccionado
+    pub fn toggle_follow_mode(&mut self, camera: &mut Camera) {
+        if self.orbit_target.is_some() && self.following {
+            self.stop_orbit(camera);
+        } else if let Some(index) = self.selected_body {
+            self.start_orbit(index, true, camera);
+        }
+    }
+
+    fn start_orbit(&mut self, index: usize, following: bool, camera: &mut Camera) {
+        self.cancel_bird_eye_view();
+        self.bookmark_flight = None;
+        let body = &self.bodies[index];
+        camera.enter_orbit_mode(body.position, body.collision_radius * 1.5);
+        self.orbit_target = Some(index);
+        self.following = following;
+        self.follow_angle = self.orbital_angle(index);
+    }
+
+    // Vuelve al vuelo libre desde la pose actual
+    pub fn stop_orbit(&mut self, camera: &mut Camera) {
+        self.orbit_target = None;
+        self.following = false;
+        camera.exit_orbit_mode();
+    }
+
+    // Ángulo del cuerpo alrededor de su centro de órbita, medido en el plano XZ
+    fn orbital_angle(&self, index: usize) -> f32 {
+        let offset = self.bodies[index].position - self.orbit_center(index);
+        offset.z.atan2(offset.x)
+    }
+
+    // Cuerpos que orbitan directamente al Sol (sin el Sol ni las lunas), en el orden de la escena
+    pub fn planet_indices(&self) -> Vec<usize> {
+        self.bodies.iter()
+            .enumerate()
+            .filter(|(_, body)| body.parent.is_none() && (body.orbital_radius > 0.0 || body.kepler.is_some()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Punto de llegada del warp: en diagonal sobre el destino, fuera de su radio de colisión
+    // y empujado hacia afuera si cae dentro de otro cuerpo (por ejemplo, una luna)
+    fn warp_arrival(&self, target: usize) -> Vec3 {
+        let mut arrival = self.bodies[target].position + Vec3::new(5.0, 2.0, 5.0).normalize() * self.standoff_distance(target);
+        for _ in 0..COLLISION_ITERATIONS {
+            match self.check_collision(&arrival) {
+                Some(collision) => arrival += collision.normal * (collision.depth + COLLISION_SKIN),
+                None => break,
+            }
+        }
+        arrival
+    }
+
+    // Distancia al centro del destino a la que se detienen el warp y el piloto automático
+    fn standoff_distance(&self, target: usize) -> f32 {
+        self.bodies[target].collision_radius * WARP_DISTANCE_FACTOR + WARP_MARGIN
     }
 
     pub fn warp_to_planet(&mut self, planet_index: usize) {
         if planet_index < self.bodies.len() {
+            self.cancel_bird_eye_view();
+            self.orbit_target = None;
+            self.following = false;
             self.warp_target = Some(planet_index);
             self.warp_animation = 0.0;
+            self.bookmark_flight = None;
         }
     }
 
-    pub fn toggle_bird_eye_view(&mut self) {
+    // Lleva la cámara a un marcador guardado, cancelando el warp, la órbita y la vista de pájaro
+    pub fn fly_to_bookmark(&mut self, bookmark: Bookmark, camera: &Camera) {
+        self.cancel_bird_eye_view();
+        self.orbit_target = None;
+        self.following = false;
+        self.warp_target = None;
+        self.warp_animation = 0.0;
+        self.bookmark_flight = Some((camera.eye, camera.center(), bookmark, 0.0));
+    }
+
+    // Guarda en `path` el estado que cambia con el tiempo y la pose de la cámara
+    pub fn save_snapshot(&self, path: &str, camera: &Camera) -> Result<(), SnapshotError> {
+        let array = |v: Vec3| [v.x, v.y, v.z];
+        let snapshot = Snapshot {
+            time: self.time,
+            time_scale: self.time_scale,
+            paused: self.paused,
+            seed: self.seed,
+            selected_body: self.selected_body,
+            bodies: self.bodies.iter()
+                .map(|body| BodySnapshot { name: body.name.clone(), rotation: body.rotation, orbital_phase: body.orbital_phase })
+                .collect(),
+            spaceship: SpaceshipSnapshot { position: array(self.spaceship.position), velocity: array(self.spaceship.velocity) },
+            camera: CameraSnapshot { eye: array(camera.eye), center: array(camera.center()), up: array(camera.up) },
+        };
+        snapshot.save(path)
+    }
+
+    // Vuelve al estado guardado en `path`. Un sistema generado con otra semilla se regenera primero;
+    // fuera de eso los cuerpos tienen que coincidir con los actuales. Se cancela cualquier transición
+    // en curso (warp, vista de pájaro, viaje a un marcador, órbita, piloto automático) para no quedar
+    // a mitad de una, y las estelas empiezan de nuevo porque el tiempo salta
+    pub fn load_snapshot(&mut self, path: &str, camera: &mut Camera) -> Result<(), SnapshotError> {
+        let snapshot = Snapshot::load(path)?;
+        if let Some(seed) = snapshot.seed.filter(|&seed| self.seed != Some(seed)) {
+            self.regenerate(seed, camera);
+        }
+        snapshot.check_bodies(path, self.bodies.iter().map(|body| body.name.as_str()))?;
+
+        if self.orbit_target.is_some() {
+            self.stop_orbit(camera);
+        }
+        self.cancel_bird_eye_view();
+        self.warp_target = None;
+        self.warp_animation = 0.0;
+        self.bookmark_flight = None;
+        self.autopilot = None;
+        self.arrival_notice = None;
+        self.crosshair_target = None;
+        self.chase_spring = None;
+        self.leveling = None;
+
+        self.time = snapshot.time;
+        self.time_scale = snapshot.time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+        self.paused = snapshot.paused;
+        self.selected_body = snapshot.selected_body.filter(|&index| index < self.bodies.len());
+        for (body, saved) in self.bodies.iter_mut().zip(&snapshot.bodies) {
+            body.rotation = saved.rotation;
+            body.orbital_phase = saved.orbital_phase;
+            body.orbit_trail = OrbitTrail::new();
+        }
+
+        let vector = |v: [f32; 3]| Vec3::new(v[0], v[1], v[2]);
+        self.spaceship.position = vector(snapshot.spaceship.position);
+        self.spaceship.velocity = vector(snapshot.spaceship.velocity);
+        self.trail.update(0.0, None);
+        camera.up = vector(snapshot.camera.up);
+        camera.look_at(vector(snapshot.camera.eye), vector(snapshot.camera.center));
+        Ok(())
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn toggle_gravity(&mut self) {
+        self.gravity = !self.gravity;
+    }
+
+    pub fn scale_time(&mut self, factor: f32) {
+        self.time_scale = (self.time_scale * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+
+    pub fn reset_time_scale(&mut self) {
+        self.time_scale = 1.0;
+    }
+
+    pub fn toggle_bird_eye_view(&mut self, camera: &Camera) {
+        self.bookmark_flight = None;
+        self.orbit_target = None;
+        self.following = false;
         self.bird_eye_view = !self.bird_eye_view;
+        // Al entrar desde vuelo libre se guarda la pose para volver a ella al salir
+        if self.bird_eye_view && self.bird_eye_return.is_none() {
+            self.bird_eye_return = Some((camera.eye, camera.center()));
+        }
+    }
+
+    // Verdadero mientras la vista de pájaro controla la cámara, incluida la transición de salida
+    pub fn bird_eye_active(&self) -> bool {
+        self.bird_eye_return.is_some()
+    }
+
+    pub fn adjust_bird_eye_height(&mut self, delta: f32) {
+        self.bird_eye_height = (self.bird_eye_height - delta).clamp(BIRD_EYE_MIN_HEIGHT, BIRD_EYE_MAX_HEIGHT);
+    }
+
+    // Sale de la vista de pájaro sin transición (warp y modo órbita toman el control de la cámara)
+    fn cancel_bird_eye_view(&mut self) {
+        self.bird_eye_view = false;
+        self.bird_eye_blend = 0.0;
+        self.bird_eye_return = None;
+    }
+}
+
+pub fn generate_orbit_points(orbital_radius: f32) -> Vec<Vec3> {
+    let mut orbit_points = Vec::new();
+    for i in 0..360 {
+        let angle = i as f32 * PI / 180.0;
+        let x = orbital_radius * angle.cos();
+        let z = orbital_radius * angle.sin();
+        orbit_points.push(Vec3::new(x, 0.0, z));
     }
-}
\ No newline at end of file
+    orbit_points
+}
+
+// Agrega el cuerpo y después sus lunas, con las lunas de cada luna detrás de ella: así los padres
+// siempre preceden a sus lunas en el vector
+fn push_body(bodies: &mut Vec<CelestialBody>, config: &BodyConfig, parent: Option<usize>) -> Result<(), SceneError> {
+    let mut body = body_from_config(config, parent)?;
+    if let Some(parent) = parent {
+        body.position += bodies[parent].position;
+    }
+    let index = bodies.len();
+    bodies.push(body);
+    for moon in &config.moons {
+        push_body(bodies, moon, Some(index))?;
+    }
+    Ok(())
+}
+
+fn body_from_config(config: &BodyConfig, parent: Option<usize>) -> Result<CelestialBody, SceneError> {
+    let mut builder = CelestialBody::builder(&config.name)
+        .phase(config.phase)
+        .rotation_speed(config.rotation_speed)
+        .axial_tilt(config.axial_tilt)
+        .scale(config.scale)
+        .shape(Vec3::from(config.shape))
+        .shader(config.shader)
+        .mass(config.mass);
+    if config.eccentricity > 0.0 {
+        let orbit = KeplerOrbit {
+            semi_major_axis: config.orbital_radius,
+            eccentricity: config.eccentricity,
+            argument_of_periapsis: config.periapsis_argument,
+        };
+        builder = builder.kepler_orbit(orbit, config.orbital_speed);
+    } else if config.orbital_radius != 0.0 {
+        builder = builder.orbit(config.orbital_radius, config.orbital_speed);
+    }
+    if let Some(parent) = parent {
+        builder = builder.parent(parent);
+    }
+    // Una tabla `ring` describe el anillo completo; `rings = true` es el anillo por defecto
+    let ring = match &config.ring {
+        Some(ring) => Some(Ring {
+            inner_scale: ring.inner_scale,
+            outer_scale: ring.outer_scale,
+            tilt: ring.tilt.to_radians(),
+            shader: ring.shader,
+        }),
+        None => config.rings.then(Ring::default),
+    };
+    if let Some(ring) = ring {
+        builder = builder.ring(ring);
+    }
+    if config.tail {
+        builder = builder.tail();
+    }
+    if config.tidally_locked {
+        builder = builder.tidally_locked();
+    }
+    // La imagen se lee al cargar la escena: un archivo que falta se informa antes de abrir la ventana
+    if let Some(path) = &config.texture {
+        builder = builder.texture(Texture::load(path).map_err(SceneError::Texture)?);
+    }
+    builder.build().map_err(SceneError::InvalidBody)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        let body = CelestialBody::builder("Ceres").build().unwrap();
+        assert_eq!(body.name, "Ceres");
+        assert_eq!(body.shader, ShaderKind::Moon);
+        assert_eq!(body.scale, 1.0);
+        assert_eq!(body.rotation_speed, 0.02);
+        assert_eq!(body.orbital_radius, 0.0);
+        assert_eq!(body.position, Vec3::new(0.0, 0.0, 0.0));
+        assert!(body.orbit_points.is_empty());
+        assert!(body.parent.is_none() && body.ring.is_none() && !body.has_tail);
+        assert_eq!(body.mass, 0.0);
+        assert_eq!(body.collision_radius, MeshRadii::default().sphere + COLLISION_MARGIN);
+    }
+
+    #[test]
+    fn builder_places_the_body_on_its_orbit() {
+        let body = CelestialBody::builder("Tierra")
+            .orbit(7.0, 0.5)
+            .phase(std::f32::consts::FRAC_PI_2)
+            .scale(0.8)
+            .shader(ShaderKind::Earth)
+            .build()
+            .unwrap();
+        assert_eq!(body.shader, ShaderKind::Earth);
+        assert!((body.position - Vec3::new(0.0, 0.0, 7.0)).magnitude() < 1e-5);
+        assert!(!body.orbit_points.is_empty());
+        assert!(body.orbit_points.iter().all(|point| (point.magnitude() - 7.0).abs() < 1e-4));
+        assert_eq!(body.collision_radius, 0.8 * MeshRadii::default().sphere + COLLISION_MARGIN);
+    }
+
+    #[test]
+    fn builder_rejects_a_non_positive_scale() {
+        for scale in [0.0, -1.0, f32::NAN] {
+            let result = CelestialBody::builder("Roto").scale(scale).build();
+            assert!(matches!(result, Err(BodyError::InvalidScale { ref body, .. }) if body == "Roto"), "escala {}", scale);
+        }
+    }
+
+    #[test]
+    fn builder_rejects_a_non_positive_orbit() {
+        for radius in [0.0, -3.0] {
+            let result = CelestialBody::builder("Roto").orbit(radius, 1.0).build();
+            match result {
+                Err(error @ BodyError::InvalidOrbit { .. }) => assert!(error.to_string().contains("'Roto'")),
+                _ => panic!("radio {} aceptado", radius),
+            }
+        }
+    }
+
+    fn start_camera() -> Camera {
+        Camera::new(Vec3::new(50.0, 20.0, 50.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    // Avanza `steps` pasos de `delta_time` segundos
+    fn run(solar_system: &mut SolarSystem, camera: &mut Camera, delta_time: f32, steps: usize) {
+        for _ in 0..steps {
+            solar_system.update(delta_time, camera);
+        }
+    }
+
+    #[test]
+    fn orbits_and_rotation_do_not_depend_on_the_timestep() {
+        let (mut slow, mut slow_camera) = (SolarSystem::new(), start_camera());
+        let (mut fast, mut fast_camera) = (SolarSystem::new(), start_camera());
+        run(&mut slow, &mut slow_camera, 1.0 / 30.0, 600);
+        run(&mut fast, &mut fast_camera, 1.0 / 60.0, 1200);
+        assert!((slow.time - fast.time).abs() < 1e-3);
+        for (a, b) in slow.bodies.iter().zip(&fast.bodies) {
+            assert!((a.position - b.position).magnitude() < 1e-3, "{}", a.name);
+            assert!((a.rotation - b.rotation).abs() < 1e-3, "{}", a.name);
+        }
+    }
+
+    #[test]
+    fn warp_progress_does_not_depend_on_the_timestep() {
+        // Pasos exactos en binario, así los dos llegan en el mismo instante
+        let (mut slow, mut slow_camera) = (SolarSystem::new(), start_camera());
+        let (mut fast, mut fast_camera) = (SolarSystem::new(), start_camera());
+        let target = slow.planet_indices()[2];
+        slow.warp_to_planet(target);
+        fast.warp_to_planet(target);
+        for step in 1..=8 {
+            run(&mut slow, &mut slow_camera, 1.0 / 32.0, 1);
+            run(&mut fast, &mut fast_camera, 1.0 / 64.0, 2);
+            assert!((slow.warp_animation - fast.warp_animation).abs() < 1e-6, "paso {}", step);
+            assert!((slow.warp_animation - step as f32 / 32.0 / WARP_DURATION).abs() < 1e-6);
+        }
+        run(&mut slow, &mut slow_camera, 1.0 / 32.0, 8);
+        run(&mut fast, &mut fast_camera, 1.0 / 64.0, 16);
+        assert!(slow.warp_target.is_none() && fast.warp_target.is_none());
+        assert!((slow_camera.eye - fast_camera.eye).magnitude() < 1e-3);
+        assert!((slow_camera.center() - fast_camera.center()).magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn scene_moons_can_have_moons() {
+        let path = std::env::temp_dir().join(format!("escena_lunas_{}.toml", std::process::id()));
+        std::fs::write(&path, r#"
+[[bodies]]
+name = "Planeta"
+orbital_radius = 10.0
+orbital_speed = 0.1
+scale = 1.0
+shader = "Jupiter"
+
+[[bodies.moons]]
+name = "Luna"
+orbital_radius = 3.0
+orbital_speed = 0.5
+scale = 0.3
+shader = "Moon"
+
+[[bodies.moons.moons]]
+name = "Sublunita"
+orbital_radius = 0.8
+orbital_speed = 2.0
+scale = 0.1
+shader = "Moon"
+
+[[bodies]]
+name = "Lejano"
+orbital_radius = 20.0
+orbital_speed = 0.05
+scale = 0.5
+shader = "Neptune"
+"#).unwrap();
+        let solar_system = SolarSystem::from_file(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let solar_system = solar_system.unwrap();
+
+        let names: Vec<&str> = solar_system.bodies.iter().map(|body| body.name.as_str()).collect();
+        assert_eq!(names, ["Planeta", "Luna", "Sublunita", "Lejano"]);
+        let parents: Vec<Option<usize>> = solar_system.bodies.iter().map(|body| body.parent).collect();
+        assert_eq!(parents, [None, Some(0), Some(1), None]);
+        let nested = &solar_system.bodies[2];
+        assert!(((nested.position - solar_system.bodies[1].position).magnitude() - 0.8).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,188 @@
+// Modelo de vuelo de la nave: velocidad, empuje e inercia (sin arrastre)
+use nalgebra_glm::Vec3;
+
+// Parámetros de vuelo, en unidades por segundo, a la distancia de referencia y sin modificadores
+pub const MAX_SPEED: f32 = 15.0;
+pub const ACCELERATION: f32 = 10.0;          // Empuje frontal (W/S)
+pub const LATERAL_ACCELERATION: f32 = 6.0;   // Empuje lateral (A/D)
+pub const VERTICAL_ACCELERATION: f32 = 6.0;  // Empuje vertical del mundo (E/Q)
+pub const BRAKE_DECELERATION: f32 = 15.0;    // Frenado activo
+
+// La velocidad máxima y el empuje escalan con la distancia al cuerpo más cercano: lentos junto a una
+// superficie, rápidos entre órbitas. A REFERENCE_DISTANCE la escala es 1
+const REFERENCE_DISTANCE: f32 = 5.0;
+const MIN_DISTANCE_SCALE: f32 = 0.1;
+const MAX_DISTANCE_SCALE: f32 = 4.0;
+
+// Modificadores de teclado: Shift acelera, Ctrl/Alt da precisión
+pub const BOOST_MULTIPLIER: f32 = 5.0;
+pub const PRECISION_MULTIPLIER: f32 = 0.2;
+
+// Qué tan rápido se reduce la velocidad cuando baja el máximo (al acercarse a un cuerpo)
+const OVERSPEED_RESPONSE: f32 = 4.0;
+
+// Qué tan rápido (1/s) sigue la velocidad a la que pide el piloto automático
+const STEER_RESPONSE: f32 = 4.0;
+
+// Rebote en un impacto: fracción de la velocidad contra la superficie que vuelve, y de la
+// velocidad a lo largo de ella que se conserva
+const BOUNCE_RESTITUTION: f32 = 0.4;
+const BOUNCE_FRICTION: f32 = 0.6;
+
+// Escala del modelo Navesita.obj en el mundo
+pub const MODEL_SCALE: f32 = 0.02;
+
+// Inclinación visual máxima (radianes) y qué tan rápido la nave la alcanza
+const MAX_BANK: f32 = 0.45;
+const MAX_PITCH_TILT: f32 = 0.15;
+const TILT_RESPONSE: f32 = 4.0;
+
+pub struct Spaceship {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub rotation: Vec3,
+    thrust: f32,        // Entrada frontal en [-1, 1]
+    lateral: f32,       // Entrada lateral en [-1, 1], positiva hacia la derecha
+    vertical: f32,      // Entrada vertical en [-1, 1], positiva hacia +Y del mundo
+    braking: bool,
+    bank: f32,          // Alabeo visual con el empuje lateral
+    pitch_tilt: f32,    // Cabeceo visual con el empuje frontal
+    distance_scale: f32, // Escala por distancia al cuerpo más cercano
+    modifier: f32,      // Multiplicador de las teclas modificadoras
+}
+
+impl Spaceship {
+    pub fn new(position: Vec3) -> Self {
+        Spaceship {
+            position,
+            velocity: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            thrust: 0.0,
+            lateral: 0.0,
+            vertical: 0.0,
+            braking: false,
+            bank: 0.0,
+            pitch_tilt: 0.0,
+            distance_scale: 1.0,
+            modifier: 1.0,
+        }
+    }
+
+    pub fn set_controls(&mut self, thrust: f32, lateral: f32, vertical: f32, braking: bool) {
+        self.thrust = thrust.clamp(-1.0, 1.0);
+        self.lateral = lateral.clamp(-1.0, 1.0);
+        self.vertical = vertical.clamp(-1.0, 1.0);
+        self.braking = braking;
+    }
+
+    pub fn set_speed_modifier(&mut self, modifier: f32) {
+        self.modifier = modifier;
+    }
+
+    // `distance` es la distancia a la superficie del cuerpo más cercano
+    pub fn adapt_to_distance(&mut self, distance: f32) {
+        self.distance_scale = (distance / REFERENCE_DISTANCE).clamp(MIN_DISTANCE_SCALE, MAX_DISTANCE_SCALE);
+    }
+
+    pub fn speed_scale(&self) -> f32 {
+        self.distance_scale * self.modifier
+    }
+
+    pub fn max_speed(&self) -> f32 {
+        MAX_SPEED * self.speed_scale()
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.velocity.magnitude()
+    }
+
+    pub fn thrust(&self) -> f32 {
+        self.thrust
+    }
+
+    // Aplica el empuje en los ejes de la cámara (el vertical, en el eje Y del mundo); sin empuje la nave sigue a la deriva.
+    // Con `keep_momentum` la velocidad ganada por otras fuerzas (la gravedad) no se recorta
+    pub fn accelerate(&mut self, delta_time: f32, forward: &Vec3, right: &Vec3, keep_momentum: bool) {
+        let scale = self.speed_scale();
+        let initial_speed = self.velocity.magnitude();
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let push = forward * (self.thrust * ACCELERATION) + right * (self.lateral * LATERAL_ACCELERATION) + up * (self.vertical * VERTICAL_ACCELERATION);
+        self.velocity += push * (scale * delta_time);
+
+        if self.braking {
+            let speed = self.velocity.magnitude();
+            let reduced = (speed - BRAKE_DECELERATION * scale * delta_time).max(0.0);
+            self.velocity = if speed > f32::EPSILON { self.velocity * (reduced / speed) } else { Vec3::new(0.0, 0.0, 0.0) };
+        }
+
+        // Por encima del máximo la nave se frena de a poco en lugar de detenerse de golpe
+        let speed = self.velocity.magnitude();
+        let max_speed = self.max_speed();
+        if speed > max_speed {
+            let target = if keep_momentum {
+                // El empuje no puede pasar del máximo, pero tampoco quita la velocidad que ya se tenía
+                initial_speed.max(max_speed).min(speed)
+            } else {
+                speed + (max_speed - speed) * (OVERSPEED_RESPONSE * delta_time).min(1.0)
+            };
+            self.velocity *= target / speed;
+        }
+
+        self.update_tilt(delta_time);
+    }
+
+    // Piloto automático: la velocidad se acerca a `desired` con una respuesta suave. El cambio se
+    // expresa como entrada en los ejes de la cámara, así el escape y la inclinación visual
+    // responden igual que en vuelo manual
+    pub fn steer(&mut self, delta_time: f32, desired: &Vec3, forward: &Vec3, right: &Vec3) {
+        let change = (desired - self.velocity) * (STEER_RESPONSE * delta_time).min(1.0);
+        self.velocity += change;
+
+        let full_thrust = ACCELERATION * self.speed_scale() * delta_time;
+        if full_thrust > f32::EPSILON {
+            self.thrust = (change.dot(forward) / full_thrust).clamp(-1.0, 1.0);
+            self.lateral = (change.dot(right) / full_thrust).clamp(-1.0, 1.0);
+        }
+        self.vertical = 0.0;
+        self.braking = false;
+        self.update_tilt(delta_time);
+    }
+
+    // La inclinación visual sigue la entrada con una respuesta suave
+    fn update_tilt(&mut self, delta_time: f32) {
+        let response = (TILT_RESPONSE * delta_time).min(1.0);
+        self.bank += (-self.lateral * MAX_BANK - self.bank) * response;
+        self.pitch_tilt += (-self.thrust * MAX_PITCH_TILT - self.pitch_tilt) * response;
+    }
+
+    // Anula la componente de la velocidad que entra en una superficie con normal `normal`
+    pub fn stop_into(&mut self, normal: &Vec3) {
+        let into_surface = self.velocity.dot(normal);
+        if into_surface < 0.0 {
+            self.velocity -= normal * into_surface;
+        }
+    }
+
+    // Refleja la componente de la velocidad que entra en una superficie con normal `normal` y
+    // amortigua el resto (impacto a velocidad alta)
+    pub fn bounce_off(&mut self, normal: &Vec3) {
+        let into_surface = self.velocity.dot(normal);
+        if into_surface < 0.0 {
+            let along = self.velocity - normal * into_surface;
+            self.velocity = along * BOUNCE_FRICTION - normal * (into_surface * BOUNCE_RESTITUTION);
+        }
+    }
+
+    // Deja la nave quieta en `position` (durante warps, vista de pájaro o modo órbita)
+    pub fn park(&mut self, position: Vec3) {
+        self.position = position;
+        self.velocity = Vec3::new(0.0, 0.0, 0.0);
+        self.bank = 0.0;
+        self.pitch_tilt = 0.0;
+    }
+
+    // Orientación del modelo: la de la cámara más la inclinación visual
+    pub fn orient(&mut self, base_rotation: Vec3) {
+        self.rotation = base_rotation + Vec3::new(self.pitch_tilt, 0.0, self.bank);
+    }
+}
@@ -0,0 +1,83 @@
+// Fondo de estrellas procedural: direcciones fijas a distancia infinita (sin paralaje)
+use nalgebra_glm::{Vec3, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Uniforms;
+
+const STAR_COUNT: usize = 3000;
+const STAR_SEED: u64 = 2024;
+
+// Fracción de estrellas brillantes, con un tinte de color y un poco de emisión para el bloom
+const BRIGHT_FRACTION: f64 = 0.02;
+
+// Las estrellas se escriben detrás de todo (pero antes del infinito del clear) para que los planetas las tapen
+const STAR_DEPTH: f32 = f32::MAX;
+
+struct Star {
+    direction: Vec3,
+    color: u32,
+    emission: u32,
+}
+
+pub struct Starfield {
+    stars: Vec<Star>,
+}
+
+impl Starfield {
+    pub fn new() -> Self {
+        let mut rng = StdRng::seed_from_u64(STAR_SEED);
+        let tints = [
+            Color::from_float(0.7, 0.8, 1.0),   // Azuladas
+            Color::from_float(1.0, 0.9, 0.7),   // Amarillentas
+            Color::from_float(1.0, 0.75, 0.6),  // Anaranjadas
+        ];
+
+        let stars = (0..STAR_COUNT)
+            .map(|_| {
+                // Dirección uniforme sobre la esfera
+                let z: f32 = rng.gen_range(-1.0..1.0);
+                let angle: f32 = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+                let ring = (1.0 - z * z).sqrt();
+                let direction = Vec3::new(ring * angle.cos(), ring * angle.sin(), z);
+
+                if rng.gen_bool(BRIGHT_FRACTION) {
+                    let tint = tints[rng.gen_range(0..tints.len())];
+                    Star { direction, color: tint.to_hex(), emission: (tint * 0.35).to_hex() }
+                } else {
+                    // La mayoría son tenues; pocas se acercan al blanco
+                    let brightness = 0.15 + 0.6 * rng.gen::<f32>().powf(3.0);
+                    let color = Color::from_float(brightness, brightness, brightness);
+                    Star { direction, color: color.to_hex(), emission: 0 }
+                }
+            })
+            .collect();
+
+        Starfield { stars }
+    }
+
+    // Proyecta las estrellas solo con la rotación de la cámara, así no se desplazan al trasladarse
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let mut rotation_only = uniforms.view_matrix;
+        rotation_only[(0, 3)] = 0.0;
+        rotation_only[(1, 3)] = 0.0;
+        rotation_only[(2, 3)] = 0.0;
+        let transform = uniforms.projection_matrix * rotation_only;
+
+        for star in &self.stars {
+            let clip = transform * Vec4::new(star.direction.x, star.direction.y, star.direction.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = uniforms.viewport_matrix * ndc;
+            if screen.x < 0.0 || screen.y < 0.0 {
+                continue;
+            }
+
+            framebuffer.set_current_color(star.color);
+            framebuffer.point(screen.x as usize, screen.y as usize, STAR_DEPTH, star.emission);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// Estadísticas de rendimiento: FPS promedio y tiempo por etapa del pipeline
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+pub enum Stage {
+    Clear,
+    Vertex,
+    Raster,
+    Shading,
+    Post,
+    Present,
+}
+
+pub const STAGE_COUNT: usize = 6;
+pub const STAGE_NAMES: [&str; STAGE_COUNT] = ["CLEAR", "VERTEX", "RASTER", "SHADING", "POST", "PRESENT"];
+
+// Cada cuánto se refrescan los valores mostrados, para que sean legibles
+const REFRESH_INTERVAL: f32 = 0.25;
+
+pub struct FrameStats {
+    totals: [Duration; STAGE_COUNT],
+    frames: u32,
+    window_start: Instant,
+    pub fps: f32,
+    pub stage_ms: [f32; STAGE_COUNT],
+    pub visible: bool,
+    lods: Vec<(String, usize, f32)>, // Nivel de detalle elegido en el frame actual: cuerpo, nivel y radio en pixeles
+    culled: usize,                   // Objetos descartados por el frustum en el frame actual
+    frame: FrameSample,              // Tiempos y conteos del frame actual, sin promediar
+}
+
+// Medición de un solo frame, para el modo benchmark
+#[derive(Clone, Copy, Default)]
+pub struct FrameSample {
+    pub stages: [Duration; STAGE_COUNT],
+    pub triangles: usize,   // Triángulos enviados al rasterizador
+    pub fragments: usize,   // Fragmentos que pasaron por el fragment shader
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats {
+            totals: [Duration::ZERO; STAGE_COUNT],
+            frames: 0,
+            window_start: Instant::now(),
+            fps: 0.0,
+            stage_ms: [0.0; STAGE_COUNT],
+            visible: false,
+            lods: Vec::new(),
+            culled: 0,
+            frame: FrameSample::default(),
+        }
+    }
+
+    pub fn add(&mut self, stage: Stage, elapsed: Duration) {
+        self.totals[stage as usize] += elapsed;
+        self.frame.stages[stage as usize] += elapsed;
+    }
+
+    pub fn record_triangles(&mut self, count: usize) {
+        self.frame.triangles += count;
+    }
+
+    pub fn record_fragments(&mut self, count: usize) {
+        self.frame.fragments += count;
+    }
+
+    // Medición del frame en curso; se reinicia en `end_frame`
+    pub fn frame_sample(&self) -> FrameSample {
+        self.frame
+    }
+
+    pub fn record_lod(&mut self, body: &str, level: usize, radius: f32) {
+        self.lods.push((body.to_string(), level, radius));
+    }
+
+    pub fn record_culled(&mut self) {
+        self.culled += 1;
+    }
+
+    // Cierra un frame; cada REFRESH_INTERVAL segundos recalcula los promedios
+    pub fn end_frame(&mut self) {
+        self.lods.clear();
+        self.culled = 0;
+        self.frame = FrameSample::default();
+        self.frames += 1;
+        let elapsed = self.window_start.elapsed().as_secs_f32();
+        if elapsed < REFRESH_INTERVAL {
+            return;
+        }
+
+        self.fps = self.frames as f32 / elapsed;
+        for i in 0..STAGE_COUNT {
+            self.stage_ms[i] = self.totals[i].as_secs_f32() * 1000.0 / self.frames as f32;
+        }
+
+        self.totals = [Duration::ZERO; STAGE_COUNT];
+        self.frames = 0;
+        self.window_start = Instant::now();
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("FPS: {:.1}", self.fps)];
+        for (name, ms) in STAGE_NAMES.iter().zip(&self.stage_ms) {
+            lines.push(format!("{}: {:.2} MS", name, ms));
+        }
+        lines.push(format!("CULLED: {}", self.culled));
+        for (body, level, radius) in &self.lods {
+            if radius.is_finite() {
+                lines.push(format!("{}: LOD {} ({:.0} PX)", body, level, radius));
+            } else {
+                lines.push(format!("{}: LOD {}", body, level));
+            }
+        }
+        lines
+    }
+}
@@ -0,0 +1,67 @@
+// Texturas de superficie horneadas: la parte estática de los shaders de los planetas (color base y
+// valor del ruido) se evalúa una sola vez al iniciar sobre una grilla equirectangular y en cada
+// fragmento se muestrea con filtro bilineal en lugar de recalcular el ruido fractal
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{Vec2, Vec3};
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::material::ShaderParams;
+use crate::shaders::StaticSurface;
+
+// Ancho por defecto de las texturas (el alto es la mitad); `--bake-resolution` lo cambia
+pub const DEFAULT_BAKE_RESOLUTION: usize = 1024;
+
+// Cada texel guarda rojo, verde y azul (0 a 255) y el valor del ruido, que el shader usa para sus
+// umbrales; juntos en un arreglo para que el muestreo lea pocas líneas de caché
+pub struct SurfaceTexture {
+    width: usize,
+    height: usize,
+    texels: Vec<[f32; 4]>,
+}
+
+impl SurfaceTexture {
+    // Usa la misma proyección que las coordenadas UV de `Obj::sphere`: u recorre la longitud
+    // desde +x hacia +z y v va del polo norte (0) al polo sur (1)
+    pub fn bake(surface: StaticSurface, noise: &FastNoiseLite, params: &ShaderParams, width: usize) -> Self {
+        let width = width.max(4);
+        let height = (width / 2).max(2);
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let theta = (y as f32 + 0.5) / height as f32 * PI;
+            for x in 0..width {
+                let phi = (x as f32 + 0.5) / width as f32 * 2.0 * PI;
+                let direction = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+                let (color, value) = surface(noise, params, direction);
+                let hex = color.to_hex();
+                texels.push([(hex >> 16 & 0xFF) as f32, (hex >> 8 & 0xFF) as f32, (hex & 0xFF) as f32, value]);
+            }
+        }
+        SurfaceTexture { width, height, texels }
+    }
+
+    // Color y valor interpolados entre los cuatro texels más cercanos; la costura de la longitud
+    // se cierra dando la vuelta y los polos se recortan al borde
+    pub fn sample(&self, uv: Vec2) -> (Color, f32) {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = (uv.y * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (tx, ty) = (x - x.floor(), y - y.floor());
+        let x0 = (x.floor() as isize).rem_euclid(self.width as isize) as usize;
+        let x1 = (x0 + 1) % self.width;
+        let y0 = y as usize;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        let weighted = [
+            (y0 * self.width + x0, (1.0 - tx) * (1.0 - ty)),
+            (y0 * self.width + x1, tx * (1.0 - ty)),
+            (y1 * self.width + x0, (1.0 - tx) * ty),
+            (y1 * self.width + x1, tx * ty),
+        ];
+        let mut sum = [0.0; 4];
+        for (index, weight) in weighted {
+            for (channel, texel) in sum.iter_mut().zip(self.texels[index]) {
+                *channel += texel * weight;
+            }
+        }
+        (Color::new(sum[0].round() as u8, sum[1].round() as u8, sum[2].round() as u8), sum[3])
+    }
+}
@@ -0,0 +1,56 @@
+// Mira en el centro de la pantalla y panel del objetivo en la esquina inferior derecha: nombre,
+// distancia a la superficie, rapidez orbital y escala del cuerpo que está en la mira (T lo fija)
+use crate::framebuffer::Framebuffer;
+use crate::solar_system::SolarSystem;
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::renderer::Uniforms;
+
+const CROSSHAIR_ARM: usize = 6;   // Largo de cada brazo en pixeles
+const CROSSHAIR_GAP: usize = 3;   // Hueco en el centro, para no tapar lo que se apunta
+const CROSSHAIR_COLOR: u32 = 0x9A9A9A;
+const TARGET_COLOR: u32 = 0x7FDBFF;  // Mira y panel con un cuerpo en la mira
+const PANEL_SCALE: usize = 2;
+const PANEL_MARGIN: usize = 8;
+
+pub fn draw(framebuffer: &mut Framebuffer, solar_system: &SolarSystem, uniforms: &Uniforms) {
+    let target = solar_system.crosshair_target();
+    draw_crosshair(framebuffer, if target.is_some() { TARGET_COLOR } else { CROSSHAIR_COLOR });
+
+    let Some(index) = target else { return };
+    let body = &solar_system.bodies[index];
+    let distance = ((body.position - uniforms.camera_position).magnitude() - body.collision_radius).max(0.0);
+    let status = if solar_system.selected_body == Some(index) { "FIJADO" } else { "T: FIJAR" };
+    let lines = [
+        format!("OBJETIVO: {}", body.name),
+        format!("DISTANCIA: {:.1}", distance),
+        format!("VEL. ORBITAL: {:.2} U/S", solar_system.orbital_speed(index)),
+        format!("ESCALA: {:.2}", body.scale),
+        status.to_string(),
+    ];
+
+    // Alineado a la derecha, por encima del borde inferior como el HUD
+    let line_height = (GLYPH_HEIGHT + 2) * PANEL_SCALE;
+    let top = framebuffer.height.saturating_sub(PANEL_MARGIN + lines.len() * line_height);
+    for (i, text) in lines.iter().enumerate() {
+        let x = framebuffer.width.saturating_sub(PANEL_MARGIN + text_width(text, PANEL_SCALE));
+        draw_text(framebuffer, x, top + i * line_height, text, TARGET_COLOR, PANEL_SCALE);
+    }
+}
+
+// Cruz de cuatro brazos con el centro vacío
+fn draw_crosshair(framebuffer: &mut Framebuffer, color: u32) {
+    let (center_x, center_y) = (framebuffer.width / 2, framebuffer.height / 2);
+    for offset in CROSSHAIR_GAP..CROSSHAIR_GAP + CROSSHAIR_ARM {
+        let points = [
+            (center_x + offset, center_y),
+            (center_x.wrapping_sub(offset), center_y),
+            (center_x, center_y + offset),
+            (center_x, center_y.wrapping_sub(offset)),
+        ];
+        for (x, y) in points {
+            if x < framebuffer.width && y < framebuffer.height {
+                framebuffer.buffer[y * framebuffer.width + x] = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// Texturas de imagen: un PNG o JPEG equirectangular que reemplaza el color base procedural de un
+// cuerpo (`texture` en la escena). Se guarda un solo nivel, sin mipmaps, y se muestrea con filtro
+// bilineal con las UV de la esfera; la iluminación, la atmósfera y las nubes se aplican encima
+use image::ImageError;
+use nalgebra_glm::Vec2;
+use std::fmt;
+use std::path::Path;
+use crate::color::Color;
+
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<Color>,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(String, std::io::Error),
+    Decode(String, ImageError),
+    Empty(String),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Io(path, err) => write!(f, "no se pudo leer la textura '{}': {}", path, err),
+            TextureError::Decode(path, err) => write!(f, "textura inválida '{}': {}", path, err),
+            TextureError::Empty(path) => write!(f, "la textura '{}' no tiene pixeles", path),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+impl Texture {
+    // El formato sale del contenido del archivo; la transparencia se descarta
+    pub fn load(path: &Path) -> Result<Self, TextureError> {
+        let name = path.display().to_string();
+        let image = image::open(path).map_err(|err| match err {
+            ImageError::IoError(err) => TextureError::Io(name.clone(), err),
+            err => TextureError::Decode(name.clone(), err),
+        })?;
+        let image = image.into_rgb8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if width == 0 || height == 0 {
+            return Err(TextureError::Empty(name));
+        }
+        let data = image.pixels().map(|pixel| Color::new(pixel[0], pixel[1], pixel[2])).collect();
+        Ok(Texture { width, height, data })
+    }
+
+    // Color interpolado entre los cuatro texels más cercanos, con la misma convención que las
+    // texturas horneadas: la costura de la longitud se cierra dando la vuelta y los polos se recortan.
+    // Las UV de la esfera avanzan hacia el oeste, así que u se invierte para que el mapa no se vea
+    // espejado
+    pub fn sample(&self, uv: Vec2) -> Color {
+        let x = (1.0 - uv.x) * self.width as f32 - 0.5;
+        let y = (uv.y * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (tx, ty) = (x - x.floor(), y - y.floor());
+        let x0 = (x.floor() as isize).rem_euclid(self.width as isize) as usize;
+        let x1 = (x0 + 1) % self.width;
+        let y0 = y as usize;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        let texel = |x: usize, y: usize| self.data[y * self.width + x];
+        let top = texel(x0, y0).lerp(&texel(x1, y0), tx);
+        let bottom = texel(x0, y1).lerp(&texel(x1, y1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
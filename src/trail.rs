@@ -0,0 +1,178 @@
+// Estela de la nave: las últimas posiciones de la nave en vuelo libre, muestreadas a intervalos fijos,
+// dibujadas como una polilínea que brilla cerca de la nave y se desvanece hacia las más viejas.
+// Un salto (warp, vista de pájaro, órbita o viaje a un marcador) corta la estela en lugar de unir
+// los dos extremos con un trazo recto a través del sistema. Las estelas orbitales de los cuerpos
+// (`OrbitTrail`) se dibujan igual, pero muestrean el tiempo de simulación
+use nalgebra_glm::Vec3;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::line::faded_line;
+use crate::renderer::{project_segment, Uniforms};
+
+const CAPACITY: usize = 200;
+const SAMPLE_INTERVAL: f32 = 0.05;   // Segundos entre muestras: la estela cubre los últimos 10 s
+// Distancia entre muestras seguidas a partir de la cual se considera un salto y no un tramo de vuelo
+const MAX_STEP: f32 = 20.0;
+const COLOR: Color = Color::from_hex(0x66B3FF);   // El azul del motor
+const EMISSION_STRENGTH: f32 = 0.6;
+const MAX_ALPHA: f32 = 0.8;
+
+pub struct Trail {
+    points: VecDeque<Option<Vec3>>,   // De la más vieja a la más nueva; None marca un corte
+    since_sample: f32,
+}
+
+impl Trail {
+    pub fn new() -> Self {
+        Trail { points: VecDeque::with_capacity(CAPACITY), since_sample: 0.0 }
+    }
+
+    // `position` es la de la nave si está en vuelo libre; None corta la estela
+    pub fn update(&mut self, delta_time: f32, position: Option<Vec3>) {
+        let position = match position {
+            Some(position) => position,
+            None => {
+                self.push(None);
+                self.since_sample = 0.0;
+                return;
+            }
+        };
+
+        if let Some(Some(last)) = self.points.back() {
+            if (position - last).magnitude() > MAX_STEP {
+                self.push(None);
+            }
+        }
+        self.since_sample += delta_time;
+        if self.since_sample >= SAMPLE_INTERVAL || !matches!(self.points.back(), Some(Some(_))) {
+            self.since_sample = 0.0;
+            self.push(Some(position));
+        }
+    }
+
+    fn push(&mut self, point: Option<Vec3>) {
+        // Un corte seguido de otro no agrega nada
+        if point.is_none() && matches!(self.points.back(), None | Some(None)) {
+            return;
+        }
+        if self.points.len() == CAPACITY {
+            self.points.pop_front();
+        }
+        self.points.push_back(point);
+    }
+
+    // Tramos entre muestras consecutivas y un último tramo hasta la posición actual de la nave. La
+    // opacidad de cada vértice baja con su edad, hasta 0 en una muestra que está por salir del buffer
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, ship: Vec3) {
+        let opacity = |age: usize| MAX_ALPHA * (1.0 - age as f32 / CAPACITY as f32);
+        let color = COLOR.to_hex();
+        let emission = (COLOR * EMISSION_STRENGTH).to_hex();
+        let ship_point = matches!(self.points.back(), Some(Some(_))).then_some(Some(ship));
+
+        // Recorrida de la más nueva (edad 0, la nave) a la más vieja
+        let mut previous: Option<(usize, Vec3)> = None;
+        for (age, point) in ship_point.into_iter().chain(self.points.iter().rev().copied()).enumerate() {
+            if let (Some((previous_age, start)), Some(end)) = (previous, point) {
+                if let Some((screen_start, screen_end)) = project_segment(start, end, uniforms) {
+                    faded_line(framebuffer, screen_start, screen_end, color, emission, opacity(previous_age), opacity(age));
+                }
+            }
+            previous = point.map(|position| (age, position));
+        }
+    }
+}
+
+// Estela orbital: cubre esta fracción de la vuelta del cuerpo con ORBIT_CAPACITY muestras
+const ORBIT_CAPACITY: usize = 96;
+const ORBIT_FRACTION: f32 = 0.25;
+const ORBIT_EMISSION_STRENGTH: f32 = 0.3;
+const ORBIT_MAX_ALPHA: f32 = 0.7;
+
+// Qué se dibuja de las órbitas de los cuerpos; se alterna con Shift + O
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbitDisplay {
+    Circle,   // La órbita completa, fija
+    Trail,    // Solo el recorrido reciente de cada cuerpo
+    Both,
+    Hidden,
+}
+
+impl OrbitDisplay {
+    pub fn next(self) -> Self {
+        match self {
+            OrbitDisplay::Circle => OrbitDisplay::Trail,
+            OrbitDisplay::Trail => OrbitDisplay::Both,
+            OrbitDisplay::Both => OrbitDisplay::Hidden,
+            OrbitDisplay::Hidden => OrbitDisplay::Circle,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OrbitDisplay::Circle => "COMPLETAS",
+            OrbitDisplay::Trail => "ESTELAS",
+            OrbitDisplay::Both => "COMPLETAS Y ESTELAS",
+            OrbitDisplay::Hidden => "OCULTAS",
+        }
+    }
+
+    pub fn shows_circle(self) -> bool {
+        matches!(self, OrbitDisplay::Circle | OrbitDisplay::Both)
+    }
+
+    pub fn shows_trail(self) -> bool {
+        matches!(self, OrbitDisplay::Trail | OrbitDisplay::Both)
+    }
+}
+
+// Posiciones recientes de un cuerpo en el mundo, tomadas en los múltiplos de un intervalo de tiempo
+// de simulación propio del cuerpo: la estela cubre la misma parte de la órbita a cualquier escala
+// de tiempo, y con la pausa queda quieta
+pub struct OrbitTrail {
+    points: VecDeque<Vec3>,     // De la más vieja a la más nueva
+    last_sample: Option<i64>,   // Índice (tiempo / intervalo) de la última muestra
+}
+
+impl OrbitTrail {
+    pub fn new() -> Self {
+        OrbitTrail { points: VecDeque::with_capacity(ORBIT_CAPACITY), last_sample: None }
+    }
+
+    // Segundos de simulación entre muestras para un cuerpo con esta velocidad orbital; None si no orbita
+    pub fn interval(orbital_speed: f32) -> Option<f32> {
+        (orbital_speed != 0.0).then(|| 2.0 * PI / orbital_speed.abs() * ORBIT_FRACTION / ORBIT_CAPACITY as f32)
+    }
+
+    // Índices de las muestras que faltan hasta `time`. La primera vez (y tras un salto más largo que
+    // la estela) son todas las de la estela hacia atrás: la órbita se puede calcular en cualquier instante
+    pub fn pending(&self, time: f32, interval: f32) -> RangeInclusive<i64> {
+        let latest = (time / interval).floor() as i64;
+        let oldest = latest - ORBIT_CAPACITY as i64 + 1;
+        self.last_sample.map_or(oldest, |last| (last + 1).max(oldest))..=latest
+    }
+
+    pub fn push(&mut self, sample: i64, position: Vec3) {
+        if self.points.len() == ORBIT_CAPACITY {
+            self.points.pop_front();
+        }
+        self.points.push_back(position);
+        self.last_sample = Some(sample);
+    }
+
+    // Desde la posición actual del cuerpo hacia la muestra más vieja, que llega a opacidad 0
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, current: Vec3, color: u32) {
+        let opacity = |age: usize| ORBIT_MAX_ALPHA * (1.0 - age as f32 / ORBIT_CAPACITY as f32);
+        let emission = (Color::from_hex(color) * ORBIT_EMISSION_STRENGTH).to_hex();
+
+        let mut previous = current;
+        for (age, &point) in self.points.iter().rev().enumerate() {
+            if let Some((screen_start, screen_end)) = project_segment(previous, point, uniforms) {
+                faded_line(framebuffer, screen_start, screen_end, color, emission, opacity(age), opacity(age + 1));
+            }
+            previous = point;
+        }
+    }
+}
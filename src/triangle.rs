@@ -1,30 +1,41 @@
-use nalgebra_glm::{Vec3, dot, Vec2};
+use nalgebra_glm::{Vec3, Vec2};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::color::Color;
 
-// In triangle.rs
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-    let mut fragments = Vec::new();
+// Appends the fragments covered by the triangle to `fragments`, so callers can reuse one buffer
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, width: usize, height: usize, fragments: &mut Vec<Fragment>) {
     let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
-    // Early frustum culling
+    // Early frustum culling against the current framebuffer size
+    let (screen_width, screen_height) = (width as f32, height as f32);
     if (a.x < 0.0 && b.x < 0.0 && c.x < 0.0) || 
-       (a.x > 800.0 && b.x > 800.0 && c.x > 800.0) ||
+       (a.x > screen_width && b.x > screen_width && c.x > screen_width) ||
        (a.y < 0.0 && b.y < 0.0 && c.y < 0.0) || 
-       (a.y > 600.0 && b.y > 600.0 && c.y > 600.0) {
-        return fragments;
+       (a.y > screen_height && b.y > screen_height && c.y > screen_height) {
+        return;
     }
 
+    // Only walk the part of the bounding box that lands on screen
     let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+    let (min_x, min_y) = (min_x.max(0), min_y.max(0));
+    let (max_x, max_y) = (max_x.min(width as i32 - 1), max_y.min(height as i32 - 1));
     let triangle_area = edge_function(&a, &b, &c);
 
     // Skip if triangle is too small
     if triangle_area.abs() < 0.1 {
-        return fragments;
+        return;
     }
 
-    let light_dir = Vec3::new(0.0, 0.0, 1.0);
+    // z holds the view-space depth; a vertex behind the camera would project mirrored, so drop the triangle
+    if a.z <= 0.0 || b.z <= 0.0 || c.z <= 0.0 {
+        return;
+    }
+
+    // Top-left fill rule: a pixel center lying exactly on an edge belongs only to the
+    // triangle for which that edge is a top or left edge, so shared edges are shaded once
+    let bias1 = edge_bias(&b, &c, triangle_area);
+    let bias2 = edge_bias(&c, &a, triangle_area);
+    let bias3 = edge_bias(&a, &b, triangle_area);
 
     let mut point = Vec3::new(min_x as f32 + 0.5, min_y as f32 + 0.5, 0.0);
     
@@ -33,21 +44,22 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
         
         for x in min_x..=max_x {
             // Calculate barycentric coordinates
-            let w1 = edge_function(&b, &c, &point) / triangle_area;
-            let w2 = edge_function(&c, &a, &point) / triangle_area;
-            let w3 = edge_function(&a, &b, &point) / triangle_area;
+            let w1 = shared_edge_function(&b, &c, &point) / triangle_area;
+            let w2 = shared_edge_function(&c, &a, &point) / triangle_area;
+            let w3 = shared_edge_function(&a, &b, &point) / triangle_area;
+
+            if covers(w1, bias1) && covers(w2, bias2) && covers(w3, bias3) {
+                // 1/depth is linear in screen space: interpolate it and rescale the weights
+                // so every attribute is perspective-correct
+                let (p1, p2, p3) = (w1 / a.z, w2 / b.z, w3 / c.z);
+                let depth = 1.0 / (p1 + p2 + p3);
+                let (w1, w2, w3) = (p1 * depth, p2 * depth, p3 * depth);
 
-            if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
-                // Properly interpolate all vertex attributes
                 let normal = (v1.transformed_normal * w1 + 
                             v2.transformed_normal * w2 + 
                             v3.transformed_normal * w3).normalize();
-                
-                let depth = a.z * w1 + b.z * w2 + c.z * w3;
-                let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
-                // Calculate lighting intensity properly
-                let intensity = dot(&normal, &light_dir).max(0.0);
+                let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
                 // Interpolate texture coordinates directly since they're Vec2
                 let uv = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
@@ -57,7 +69,6 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
                     v1.color,
                     depth,
                     normal,
-                    intensity,
                     vertex_position,
                     Some(uv)  // Wrap in Some since Fragment expects Option<Vec2>
                 ));
@@ -66,8 +77,6 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
         }
         point.y += 1.0;
     }
-
-    fragments
 }
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
@@ -78,6 +87,81 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
+// Whether the edge from `from` to `to` is a top or left edge of a triangle with signed area `area`.
+// The gradient of the normalized weight points into the triangle: left edges have the interior
+// to their right (+x), top edges are horizontal with the interior below them (+y, screen y grows down)
+fn edge_bias(from: &Vec3, to: &Vec3, area: f32) -> bool {
+    let inward_x = (to.y - from.y) / area;
+    let inward_y = (from.x - to.x) / area;
+    inward_x > 0.0 || (inward_x == 0.0 && inward_y > 0.0)
+}
+
+fn covers(weight: f32, top_left: bool) -> bool {
+    weight > 0.0 || (weight == 0.0 && top_left)
+}
+
+// Edge function evaluated with the endpoints in a canonical order, so the two triangles sharing
+// an edge get bit-identical values (only the sign differs) and the tie-break above is exact
+fn shared_edge_function(from: &Vec3, to: &Vec3, point: &Vec3) -> f32 {
+    if (from.y, from.x) > (to.y, to.x) {
+        -edge_function(to, from, point)
+    } else {
+        edge_function(from, to, point)
+    }
+}
+
 fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
     (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 12;
+
+    // Screen-space vertex at depth 1
+    fn corner(x: f32, y: f32) -> Vertex {
+        Vertex::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec2::new(0.0, 0.0))
+    }
+
+    // How many fragments each pixel received from all the triangles
+    fn coverage(triangles: &[[Vertex; 3]]) -> Vec<u32> {
+        let mut fragments = Vec::new();
+        for [a, b, c] in triangles {
+            triangle(a, b, c, SIZE, SIZE, &mut fragments);
+        }
+        let mut counts = vec![0; SIZE * SIZE];
+        for fragment in &fragments {
+            counts[fragment.position.y as usize * SIZE + fragment.position.x as usize] += 1;
+        }
+        counts
+    }
+
+    // The corners sit on pixel centers, so the outer edges and the shared ones pass exactly through
+    // pixel centers: the square [1.5, 9.5) x [1.5, 9.5) must be covered once, pixels 1..=8 on each axis
+    fn assert_square_covered_once(counts: &[u32]) {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let inside = (1..=8).contains(&x) && (1..=8).contains(&y);
+                assert_eq!(counts[y * SIZE + x], inside as u32, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_diagonal_is_shaded_once() {
+        let (a, b, c, d) = (corner(1.5, 1.5), corner(9.5, 1.5), corner(9.5, 9.5), corner(1.5, 9.5));
+        assert_square_covered_once(&coverage(&[[a.clone(), b, c.clone()], [a, c, d]]));
+    }
+
+    #[test]
+    fn fan_around_a_center_is_shaded_once() {
+        let corners = [corner(1.5, 1.5), corner(9.5, 1.5), corner(9.5, 9.5), corner(1.5, 9.5)];
+        let center = corner(5.5, 5.5);
+        let fan: Vec<[Vertex; 3]> = (0..4)
+            .map(|i| [center.clone(), corners[i].clone(), corners[(i + 1) % 4].clone()])
+            .collect();
+        assert_square_covered_once(&coverage(&fan));
+    }
+}
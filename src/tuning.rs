@@ -0,0 +1,66 @@
+// Ajuste en vivo de los parámetros de los shaders (F9 con un cuerpo seleccionado): `[` y `]` eligen
+// el parámetro, `-` y `=` cambian su valor y Enter imprime en stdout los valores del material con
+// el formato de `ShaderParam::default_value`, listos para pegar de vuelta en material.rs
+use minifb::{Key, KeyRepeat};
+use crate::material::{MaterialRegistry, ShaderKind, ShaderParam};
+use crate::session::InputState;
+
+// Teclas que toma el modo mientras está activo; no llegan a la simulación (escala de tiempo y warp)
+pub const KEYS: [Key; 5] = [Key::LeftBracket, Key::RightBracket, Key::Minus, Key::Equal, Key::Enter];
+
+pub struct Tuning {
+    kind: ShaderKind,
+    params: Vec<ShaderParam>,
+    active: usize,
+}
+
+impl Tuning {
+    // Parámetros que lee el material; None si no tiene ninguno ajustable
+    pub fn new(kind: ShaderKind) -> Option<Self> {
+        let params: Vec<ShaderParam> = ShaderParam::ALL.iter()
+            .copied()
+            .filter(|param| param.materials().contains(&kind))
+            .collect();
+        (!params.is_empty()).then_some(Tuning { kind, params, active: 0 })
+    }
+
+    pub fn handle_input(&mut self, input: &InputState, materials: &mut MaterialRegistry) {
+        let count = self.params.len();
+        if input.is_key_pressed(Key::RightBracket, KeyRepeat::No) {
+            self.active = (self.active + 1) % count;
+        }
+        if input.is_key_pressed(Key::LeftBracket, KeyRepeat::No) {
+            self.active = (self.active + count - 1) % count;
+        }
+
+        // Con autorepetición, así mantener la tecla recorre el rango
+        let param = self.params[self.active];
+        let steps = input.is_key_pressed(Key::Equal, KeyRepeat::Yes) as i32 - input.is_key_pressed(Key::Minus, KeyRepeat::Yes) as i32;
+        if steps != 0 {
+            materials.adjust(param, steps as f32 * param.step().0);
+        }
+
+        if input.is_key_pressed(Key::Enter, KeyRepeat::No) {
+            self.print(materials);
+        }
+    }
+
+    // Líneas del HUD: el material y todos sus parámetros, con el activo marcado
+    pub fn hud_lines(&self, materials: &MaterialRegistry) -> Vec<String> {
+        let values = &materials.get(self.kind).params;
+        let mut lines = vec![format!("AJUSTE: {:?} ([ ] ELIGE, - = CAMBIA, ENTER IMPRIME)", self.kind)];
+        for (i, &param) in self.params.iter().enumerate() {
+            let marker = if i == self.active { ">" } else { " " };
+            lines.push(format!("{} {}: {:.3}", marker, param.name(), values.get(param)));
+        }
+        lines
+    }
+
+    fn print(&self, materials: &MaterialRegistry) {
+        let values = &materials.get(self.kind).params;
+        println!("// {:?}", self.kind);
+        for &param in &self.params {
+            println!("ShaderParam::{:?} => {:.3},", param, values.get(param));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Vistas de la escena en rectángulos de la ventana, cada una con su cámara. La primera es la principal:
+// ocupa toda la ventana y lleva el post-procesamiento completo, las etiquetas y el HUD. Las demás se
+// dibujan después, en orden, encima de ella; cada una se rasteriza en su propio framebuffer del tamaño
+// de su rectángulo, así el recorte de los triángulos, la profundidad y el bloom no salen de él
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+
+pub struct Viewport<'a> {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub fov: f32,              // Campo de visión vertical en grados
+    pub camera: &'a Camera,
+}
+
+// Vista cenital (I): un cuarto del tamaño de la ventana en la esquina superior derecha, mirando al Sol
+// desde arriba. El campo de visión es amplio para que entre el sistema exterior
+const OVERHEAD_HEIGHT: f32 = 50.0;
+const OVERHEAD_FRACTION: f32 = 0.25;
+const OVERHEAD_FOV: f32 = 75.0;
+const OVERHEAD_MARGIN: usize = 8;
+const BORDER_COLOR: u32 = 0x4A5A70;
+
+impl<'a> Viewport<'a> {
+    // Vista principal, en toda la ventana
+    pub fn full(width: usize, height: usize, fov: f32, camera: &'a Camera) -> Self {
+        Viewport { x: 0, y: 0, width, height, fov, camera }
+    }
+
+    // Vista cenital para una ventana de `width` x `height`, o ninguna si no queda lugar
+    pub fn overhead(width: usize, height: usize, camera: &'a Camera) -> Option<Self> {
+        let inset_width = (width as f32 * OVERHEAD_FRACTION) as usize;
+        let inset_height = (height as f32 * OVERHEAD_FRACTION) as usize;
+        if inset_width == 0 || inset_height == 0 || width < inset_width + OVERHEAD_MARGIN || height < inset_height + OVERHEAD_MARGIN {
+            return None;
+        }
+        Some(Viewport {
+            x: width - inset_width - OVERHEAD_MARGIN,
+            y: OVERHEAD_MARGIN,
+            width: inset_width,
+            height: inset_height,
+            fov: OVERHEAD_FOV,
+            camera,
+        })
+    }
+}
+
+// Marco de un pixel alrededor del rectángulo de una vista adicional, como el del minimapa
+pub fn draw_border(framebuffer: &mut Framebuffer, x: usize, y: usize, width: usize, height: usize) {
+    let (min_x, max_x) = (x as f32, (x + width).saturating_sub(1) as f32);
+    let (min_y, max_y) = (y as f32, (y + height).saturating_sub(1) as f32);
+    framebuffer.draw_line(min_x, min_y, max_x, min_y, BORDER_COLOR);
+    framebuffer.draw_line(max_x, min_y, max_x, max_y, BORDER_COLOR);
+    framebuffer.draw_line(max_x, max_y, min_x, max_y, BORDER_COLOR);
+    framebuffer.draw_line(min_x, max_y, min_x, min_y, BORDER_COLOR);
+}
+
+// Cámara fija de la vista cenital. El objetivo está apenas hacia -z para fijar el rumbo: así x queda
+// hacia la derecha y z hacia abajo, igual que en el minimapa
+pub fn overhead_camera() -> Camera {
+    let eye = Vec3::new(0.0, OVERHEAD_HEIGHT, 0.0);
+    Camera::new(eye, Vec3::new(0.0, 0.0, -1e-3), Vec3::new(0.0, 1.0, 0.0))
+}
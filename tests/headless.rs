@@ -0,0 +1,46 @@
+// Renderiza sin ventana unos frames del sistema por defecto y revisa los PNG que quedan: cantidad,
+// tamaño de la ventana y que el Sol aparezca brillante en el centro de la imagen
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FRAMES: usize = 3;
+const WIDTH: u32 = 680;
+const HEIGHT: u32 = 800;
+
+fn render_frames(output: &Path) -> Vec<PathBuf> {
+    let _ = std::fs::remove_dir_all(output);
+    let status = Command::new(env!("CARGO_BIN_EXE_Graphics-Space-Travel"))
+        .args(["--headless", "--frames", &FRAMES.to_string(), "--bake-resolution", "64", "--output"])
+        .arg(output)
+        .status()
+        .expect("no se pudo ejecutar el programa");
+    assert!(status.success(), "el modo sin ventana terminó con {}", status);
+
+    let mut frames: Vec<PathBuf> = std::fs::read_dir(output)
+        .expect("no se creó el directorio de salida")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .collect();
+    frames.sort();
+    frames
+}
+
+#[test]
+fn headless_writes_one_png_per_frame() {
+    let output = std::env::temp_dir().join(format!("gst-headless-{}", std::process::id()));
+    let frames = render_frames(&output);
+    assert_eq!(frames.len(), FRAMES);
+    for frame in &frames {
+        assert_eq!(image::image_dimensions(frame).unwrap(), (WIDTH, HEIGHT), "{}", frame.display());
+    }
+
+    // La cámara inicial mira al origen: el Sol ocupa el centro (se mira un poco arriba a la izquierda,
+    // fuera de la mira y de las etiquetas) y el borde izquierdo queda en el espacio oscuro
+    let image = image::open(&frames[0]).unwrap().to_rgb8();
+    let brightness = |x: u32, y: u32| image.get_pixel(x, y).0.iter().map(|&c| c as u32).sum::<u32>();
+    let sun = brightness(WIDTH / 2 - 10, HEIGHT / 2 - 15);
+    assert!(sun > 400, "el centro debería mostrar el Sol, brillo {}", sun);
+    assert!(sun > 4 * brightness(20, HEIGHT / 3));
+
+    let _ = std::fs::remove_dir_all(&output);
+}
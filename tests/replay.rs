@@ -0,0 +1,51 @@
+// Prueba de regresión de imagen: reproduce sin ventana la sesión incluida en assets/sessions y compara
+// el hash de los frames con el esperado. Si un cambio de los shaders o del HUD altera la imagen a
+// propósito, revisar los PNG que quedan en el directorio del mensaje y actualizar EXPECTED_HASH
+use std::path::PathBuf;
+use std::process::Command;
+
+const SESSION: &str = "assets/sessions/flyby.bin";
+const FRAMES: usize = 8;
+const EXPECTED_HASH: u64 = 0x6fe27abdbf3bf68d;
+
+// Renderiza la sesión en un directorio propio de la prueba y devuelve los PNG en orden
+fn replay(name: &str) -> (PathBuf, Vec<PathBuf>) {
+    let output = std::env::temp_dir().join(format!("gst-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&output);
+    let status = Command::new(env!("CARGO_BIN_EXE_Graphics-Space-Travel"))
+        .args(["--headless", "--replay", SESSION, "--bake-resolution", "64", "--output"])
+        .arg(&output)
+        .status()
+        .expect("no se pudo ejecutar el programa");
+    assert!(status.success(), "la reproducción terminó con {}", status);
+
+    let mut frames: Vec<PathBuf> = std::fs::read_dir(&output)
+        .expect("no se creó el directorio de salida")
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    frames.sort();
+    (output, frames)
+}
+
+// FNV-1a de 64 bits sobre los pixeles decodificados de todos los frames, así el hash no depende
+// de cómo se comprima el PNG
+fn hash_frames(frames: &[PathBuf]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for frame in frames {
+        let pixels = image::open(frame).expect("frame ilegible").to_rgb8();
+        for &byte in pixels.as_raw() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[test]
+fn bundled_session_replays_to_the_same_frames() {
+    let (output, frames) = replay("replay");
+    assert_eq!(frames.len(), FRAMES);
+    let hash = hash_frames(&frames);
+    assert_eq!(hash, EXPECTED_HASH, "los frames cambiaron (hash {:#018x}); quedaron en {}", hash, output.display());
+    let _ = std::fs::remove_dir_all(&output);
+}